@@ -386,6 +386,9 @@ impl SectionHeader {
     }
 }
 
+const EI_CLASS: usize = 4;
+const ELFCLASS32: u8 = 1;
+
 const EM_X86_64: u16 = 62;
 const EM_RISCV: u16 = 243;
 
@@ -427,6 +430,23 @@ impl ELFLoader {
             | (self.mapped_file[start + 0] as u64) << 0
     }
 
+    pub fn is_elf32(&self) -> bool {
+        self.mapped_file[EI_CLASS] == ELFCLASS32
+    }
+
+    // Addresses, offsets and sizes are 4 bytes in ELF32 and 8 bytes in ELF64.
+    fn addr_size(&self) -> u64 {
+        if self.is_elf32() { 4 } else { 8 }
+    }
+
+    fn get_addr_elf(&self, start: usize) -> u64 {
+        if self.is_elf32() {
+            self.get_4byte_elf(start) as u64
+        } else {
+            self.get_8byte_elf(start)
+        }
+    }
+
     pub fn get_elf_header(&self) -> ELFHeader {
         if self.mapped_file[0..4] != HEADER_MAGIC {
             panic!("Not ELF File");
@@ -440,12 +460,12 @@ impl ELFLoader {
         elf_off += 2;
         let e_version = self.get_4byte_elf(elf_off as usize);
         elf_off += 4;
-        let e_entry = self.get_8byte_elf(elf_off as usize);
-        elf_off += 8;
-        let e_phoff = self.get_8byte_elf(elf_off as usize);
-        elf_off += 8;
-        let e_shoff = self.get_8byte_elf(elf_off as usize);
-        elf_off += 8;
+        let e_entry = self.get_addr_elf(elf_off as usize);
+        elf_off += self.addr_size();
+        let e_phoff = self.get_addr_elf(elf_off as usize);
+        elf_off += self.addr_size();
+        let e_shoff = self.get_addr_elf(elf_off as usize);
+        elf_off += self.addr_size();
         let e_flags = self.get_4byte_elf(elf_off as usize);
         elf_off += 4;
         let e_ehsize = self.get_2byte_elf(elf_off as usize);
@@ -485,19 +505,27 @@ impl ELFLoader {
 
         let p_type = self.get_4byte_elf(ph_off as usize);
         ph_off += 4;
-        let p_flags = self.get_4byte_elf(ph_off as usize);
-        ph_off += 4;
-        let p_offset = self.get_8byte_elf(ph_off as usize);
-        ph_off += 8;
-        let p_vaddr = self.get_8byte_elf(ph_off as usize);
-        ph_off += 8;
-        let p_paddr = self.get_8byte_elf(ph_off as usize);
-        ph_off += 8;
-        let p_filesz = self.get_8byte_elf(ph_off as usize);
-        ph_off += 8;
-        let p_memsz = self.get_8byte_elf(ph_off as usize);
-        ph_off += 8;
-        let p_align = self.get_8byte_elf(ph_off as usize); // ph_off += 8;
+        // ELF64 places p_flags here, ELF32 after p_memsz.
+        let mut p_flags = 0;
+        if !self.is_elf32() {
+            p_flags = self.get_4byte_elf(ph_off as usize);
+            ph_off += 4;
+        }
+        let p_offset = self.get_addr_elf(ph_off as usize);
+        ph_off += self.addr_size();
+        let p_vaddr = self.get_addr_elf(ph_off as usize);
+        ph_off += self.addr_size();
+        let p_paddr = self.get_addr_elf(ph_off as usize);
+        ph_off += self.addr_size();
+        let p_filesz = self.get_addr_elf(ph_off as usize);
+        ph_off += self.addr_size();
+        let p_memsz = self.get_addr_elf(ph_off as usize);
+        ph_off += self.addr_size();
+        if self.is_elf32() {
+            p_flags = self.get_4byte_elf(ph_off as usize);
+            ph_off += 4;
+        }
+        let p_align = self.get_addr_elf(ph_off as usize); // ph_off += 8;
 
         let phdr_type = match Phdr_Type::from_u64(p_type as u64) {
             Some(phdr_type) => phdr_type,
@@ -519,21 +547,21 @@ impl ELFLoader {
         sh_off += 4;
         let sh_type = self.get_4byte_elf(sh_off as usize);
         sh_off += 4;
-        let sh_flags = self.get_8byte_elf(sh_off as usize);
-        sh_off += 8;
-        let sh_addr = self.get_8byte_elf(sh_off as usize);
-        sh_off += 8;
-        let sh_offset = self.get_8byte_elf(sh_off as usize);
-        sh_off += 8;
-        let sh_size = self.get_8byte_elf(sh_off as usize);
-        sh_off += 8;
+        let sh_flags = self.get_addr_elf(sh_off as usize);
+        sh_off += self.addr_size();
+        let sh_addr = self.get_addr_elf(sh_off as usize);
+        sh_off += self.addr_size();
+        let sh_offset = self.get_addr_elf(sh_off as usize);
+        sh_off += self.addr_size();
+        let sh_size = self.get_addr_elf(sh_off as usize);
+        sh_off += self.addr_size();
         let sh_link = self.get_4byte_elf(sh_off as usize);
         sh_off += 4;
         let sh_info = self.get_4byte_elf(sh_off as usize);
         sh_off += 4;
-        let sh_addralign = self.get_8byte_elf(sh_off as usize);
-        sh_off += 8;
-        let sh_entsize = self.get_8byte_elf(sh_off as usize); // sh_off += 8;
+        let sh_addralign = self.get_addr_elf(sh_off as usize);
+        sh_off += self.addr_size();
+        let sh_entsize = self.get_addr_elf(sh_off as usize); // sh_off += 8;
 
         SectionHeader::new(
             sh_name,
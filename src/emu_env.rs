@@ -3,23 +3,30 @@ use mmap::{MapOption, MemoryMap};
 use arr_macro::arr;
 // use fnv::FnvHashMap;
 use std::mem;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::elf_loader::{ELFLoader};
 use crate::elf_loader::ProgramHeader;
 use crate::elf_loader::SectionHeader;
 
-use crate::target::riscv::riscv::{ExceptCode, PrivMode, TranslateRiscv};
+use crate::target::riscv::riscv::{ExceptCode, Interrupt, PrivMode, TranslateRiscv, CALL_HELPER_IDX, INTERRUPT_PRIORITY};
 use crate::target::riscv::riscv_csr::{CsrAddr, RiscvCsr};
 use crate::target::riscv::riscv_csr_def;
 use crate::target::riscv::riscv_decoder::decode_inst;
+use crate::target::riscv::riscv_decoder_extra::{decode_inst_ext, decode_inst_rv32};
 use crate::target::riscv::riscv_inst_id::RiscvInstId;
-use crate::target::riscv::mmu::{MemAccType};
+use crate::target::riscv::mmu::{MemAccType, RAM_SIZE};
+use crate::target::riscv::pmp::Pmp;
+use crate::target::riscv::tlb::{SoftTlb, TLB_SIZE, TLB_SLOTS};
 use crate::target::riscv::riscv_disassemble::{disassemble_riscv};
+use crate::target::riscv::riscv_custom::{is_custom_csr, CustomInst, HelperFunc, CUSTOM_HELPER_NUM};
 
 use crate::tcg::tcg::{TCGOp, TCG, TCGOpcode};
 use crate::tcg::x86::x86::{TCGX86, X86TargetRM};
 use crate::tcg::x86::disassemble::{disassemble_x86};
 use crate::instr_info::InstrInfo;
+use crate::op_helper_vec::VLENB;
 
 use std::time::{Instant, Duration};
 
@@ -29,14 +36,24 @@ fn calc_hash_func(addr: u64) -> usize {
     ((addr >> 1) & 0x3ff) as usize
 }
 
-const TLB_SIZE: usize = 4096;
-
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum MachineEnum {
     RiscvVirt,
     RiscvSiFiveU,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MisalignEnum {
+    Trap,       // Raise Load/Store Address Misaligned exception
+    Emulate,    // Split into byte accesses, translating each page
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum XlenEnum {
+    Rv32,
+    Rv64,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ArgConfig {
     pub debug: bool, 
@@ -49,6 +66,33 @@ pub struct ArgConfig {
     pub dump_host: bool,
     pub machine: MachineEnum,
     pub opt_reg_fwd: bool,
+    pub misalign: MisalignEnum,
+    pub xlen: Option<XlenEnum>,     // None : follow the ELF class
+    pub cbo_block_size: u64,        // cache-block size for Zicbom/Zicboz, in bytes
+    pub pmp_entries: usize,         // number of PMP entries : 0, 16 or 64
+    pub host_crypto: bool,          // use the host AES-NI/PCLMULQDQ when present
+}
+
+impl Default for ArgConfig {
+    fn default() -> Self {
+        ArgConfig {
+            debug   : false,
+            dump_gpr: false,
+            dump_fpr: false,
+            dump_tcg: false,
+            step    : false,
+            mmu_debug: false,
+            dump_guest: false,
+            dump_host: false,
+            machine : MachineEnum::RiscvVirt,
+            opt_reg_fwd: false,
+            misalign: MisalignEnum::Trap,
+            xlen: None,
+            cbo_block_size: 64,
+            pmp_entries: 16,
+            host_crypto: true,
+        }
+    }
 }
 
 
@@ -56,14 +100,22 @@ pub struct EmuEnv {
     pub head: [u64; 1], // pointer of this struct. Do not move.
 
     pub m_priv: PrivMode,
+    pub m_virt: bool,   // H extension : V=1 in VS/VU-mode
+    pub m_xlen: XlenEnum,
 
     pub m_iregs: [u64; 32],  // Integer Registers
     pub m_fregs: [u64; 32], // Floating Point Registers
+    pub m_vregs: [u8; VLENB * 32], // Vector Registers
     pub m_pc: [u64; 1],
 
     pub m_csr: RiscvCsr<i64>, // CSR implementation
+    pub m_host_mxcsr: [u32; 8],     // Host MXCSR for each rm of the inline SSE2 path
+    pub m_host_mxcsr_tmp: [u32; 1], // stmxcsr destination of the inline SSE2 path
+    pub m_custom_csr: HashMap<u64, i64>,   // CSRs of custom instructions
+    pub m_pmp: Pmp,
 
-    helper_func: [fn(emu: &mut EmuEnv, arg0: u64, arg1: u64, arg2: u64, arg3: u64) -> usize; 59],
+    helper_func: [HelperFunc; 111 + CUSTOM_HELPER_NUM],
+    m_custom_helper_num: usize,
 
     pub m_riscv_trans: TranslateRiscv,
 
@@ -77,14 +129,27 @@ pub struct EmuEnv {
     pub m_tb_text_hash_address: [u64; TCG_HASH_SIZE],
     pub m_tb_text_hash_inst_size: [usize; TCG_HASH_SIZE],
     pub m_tb_text_hash_memmap: [MemoryMap; TCG_HASH_SIZE],
+    pub m_tb_text_hash_fs: [i64; TCG_HASH_SIZE],    // mstatus.FS the block was translated for
+    pub m_tb_text_hash_vs: [i64; TCG_HASH_SIZE],    // mstatus.VS the block was translated for
+    pub m_tb_text_hash_priv: [PrivMode; TCG_HASH_SIZE],    // privilege the block was fetched in
+    pub m_tb_text_hash_asid: [u64; TCG_HASH_SIZE],    // address space the block was fetched from (see fetch_tag())
 
     pub m_host_prologue: [u8; 15],
     pub m_host_epilogue: [u8; 11],
 
     m_updated_pc : bool,
 
-    pub m_tlb_vec: [u64; TLB_SIZE],
-    pub m_tlb_addr_vec: [u64; TLB_SIZE],
+    pub m_tlb_vec: [u64; TLB_SIZE * TLB_SLOTS],
+    pub m_tlb_addr_vec: [u64; TLB_SIZE * TLB_SLOTS],
+    // Translation context the TLB entries were filled in (see tlb_context())
+    pub m_tlb_context: [u64; 4],
+    // Current ASID in inline TLB tag position, OR-ed into the tag by loads and stores
+    pub m_tlb_asid_tag: [u64; 1],
+    pub m_stlb: SoftTlb,
+    // Set by the MMU for the next page fault : the guest physical address
+    // if G-stage translation failed, and whether tval is a guest virtual address.
+    pub m_gpa_fault: Option<u64>,
+    pub m_fault_gva: bool,
     // Configuration
     pub m_arg_config: ArgConfig,
 
@@ -100,11 +165,27 @@ impl EmuEnv {
         EmuEnv {
             head: [0xdeadbeef; 1],
             m_priv: PrivMode::Machine,
+            m_virt: false,
+            m_xlen: XlenEnum::Rv64,
 
             m_iregs: [0; 32],
             m_fregs: [0; 32],
+            m_vregs: [0; VLENB * 32],
             m_pc: [0x8000_0000; 1],
             m_csr: RiscvCsr::new(),
+            m_host_mxcsr: [
+                EmuEnv::host_mxcsr(0b000),
+                EmuEnv::host_mxcsr(0b001),
+                EmuEnv::host_mxcsr(0b010),
+                EmuEnv::host_mxcsr(0b011),
+                EmuEnv::host_mxcsr(0b100),
+                EmuEnv::host_mxcsr(0b101),
+                EmuEnv::host_mxcsr(0b110),
+                EmuEnv::host_mxcsr(0b000), // DYN : follows frm
+            ],
+            m_host_mxcsr_tmp: [0; 1],
+            m_custom_csr: HashMap::new(),
+            m_pmp: Pmp::new(arg_config.pmp_entries),
 
             helper_func: [
                 Self::helper_func_csrrw,
@@ -166,7 +247,77 @@ impl EmuEnv {
                 Self::helper_func_float_store32,
                 Self::helper_func_sfence_vma,
                 Self::helper_func_fcvt,
+                Self::helper_func_check_jump_target,
+                Self::helper_func_fadd_h,
+                Self::helper_func_fsub_h,
+                Self::helper_func_fmul_h,
+                Self::helper_func_fdiv_h,
+                Self::helper_func_fmadd_h,
+                Self::helper_func_fmsub_h,
+                Self::helper_func_fnmsub_h,
+                Self::helper_func_fnmadd_h,
+                Self::helper_func_fsqrt_h,
+                Self::helper_func_feq_h,
+                Self::helper_func_flt_h,
+                Self::helper_func_fle_h,
+                Self::helper_func_fclass_h,
+                Self::helper_func_fmax_h,
+                Self::helper_func_fmin_h,
+                Self::helper_func_fsgnj_h,
+                Self::helper_func_fsgnjn_h,
+                Self::helper_func_fsgnjx_h,
+                Self::helper_func_float_load16,
+                Self::helper_func_float_store16,
+                Self::helper_func_fmv_x_h,
+                Self::helper_func_fmv_h_x,
+                Self::helper_func_vsetvl,
+                Self::helper_func_vector_load,
+                Self::helper_func_vector_store,
+                Self::helper_func_vector_arith,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_cbo,
+                Self::helper_func_hlv_hsv,
+                Self::helper_func_hfence,
+                Self::helper_func_fs_dirty,
+                Self::helper_func_fli_s,
+                Self::helper_func_fminm_s,
+                Self::helper_func_fmaxm_s,
+                Self::helper_func_fround_s,
+                Self::helper_func_froundnx_s,
+                Self::helper_func_fleq_s,
+                Self::helper_func_fltq_s,
+                Self::helper_func_fli_d,
+                Self::helper_func_fminm_d,
+                Self::helper_func_fmaxm_d,
+                Self::helper_func_fround_d,
+                Self::helper_func_froundnx_d,
+                Self::helper_func_fleq_d,
+                Self::helper_func_fltq_d,
+                Self::helper_func_fcvtmod_w_d,
+                Self::helper_func_fmvh_x_d,
+                Self::helper_func_fmvp_d_x,
+                Self::helper_func_crypto,
+                Self::helper_func_vs_dirty,
+                Self::helper_func_wfi,
+                // Custom instruction slots
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
+                Self::helper_func_illegal_inst,
             ],
+            m_custom_helper_num: 0,
             m_riscv_trans: TranslateRiscv::new(),
 
             m_tcg_raw_vec: vec![],
@@ -178,6 +329,10 @@ impl EmuEnv {
             // m_tb_text_hashmap: FnvHashMap::with_capacity_and_hasher (0, Default::default()),
             m_tb_text_hash_address: [0; TCG_HASH_SIZE],
             m_tb_text_hash_inst_size: [0; TCG_HASH_SIZE],
+            m_tb_text_hash_fs: [0; TCG_HASH_SIZE],
+            m_tb_text_hash_vs: [0; TCG_HASH_SIZE],
+            m_tb_text_hash_priv: [PrivMode::Machine; TCG_HASH_SIZE],
+            m_tb_text_hash_asid: [0; TCG_HASH_SIZE],
             m_tb_text_hash_memmap: arr![MemoryMap::new(0x2000, &[
                 MapOption::MapReadable,
                 MapOption::MapWritable,
@@ -185,7 +340,7 @@ impl EmuEnv {
             ]).unwrap(); 1024],
 
             m_guest_mem: match MemoryMap::new(
-                0x80000,
+                RAM_SIZE as usize,
                 &[
                     MapOption::MapReadable,
                     MapOption::MapWritable,
@@ -215,8 +370,13 @@ impl EmuEnv {
 
             m_curr_hash_key: 0,
             // TLB format
-            m_tlb_vec: [0xdeadbeef_01234567; TLB_SIZE],
-            m_tlb_addr_vec: [0x0; TLB_SIZE],
+            m_tlb_vec: [0xdeadbeef_01234567; TLB_SIZE * TLB_SLOTS],
+            m_tlb_addr_vec: [0x0; TLB_SIZE * TLB_SLOTS],
+            m_tlb_context: [0; 4],
+            m_tlb_asid_tag: [0; 1],
+            m_stlb: SoftTlb::new(),
+            m_gpa_fault: None,
+            m_fault_gva: false,
             m_arg_config: arg_config,
 
             loop_idx: 0,
@@ -227,6 +387,35 @@ impl EmuEnv {
         }
     }
 
+    // Register a custom instruction extension. Must be called before run().
+    pub fn register_custom_inst(&mut self, ext: Rc<dyn CustomInst>) {
+        if let Some((other, _)) = self.m_riscv_trans.m_custom_inst.iter()
+            .find(|(other, _)| (ext.match_bits() ^ other.match_bits()) & ext.mask() & other.mask() == 0) {
+            panic!("Custom instruction {} overlaps with {}.", ext.name(), other.name());
+        }
+        if let Some(range) = ext.csr_range() {
+            if !range.clone().all(is_custom_csr) {
+                panic!("CSRs {:03x}-{:03x} of {} are not in the custom CSR space.", range.start(), range.end(), ext.name());
+            }
+            if range.clone().any(|addr| self.m_riscv_trans.find_custom_csr(addr).is_some()) {
+                panic!("CSRs {:03x}-{:03x} of {} are already used.", range.start(), range.end(), ext.name());
+            }
+        }
+        let helper_idx = match ext.helper() {
+            Some(helper) => {
+                if self.m_custom_helper_num == CUSTOM_HELPER_NUM {
+                    panic!("No helper slot left for custom instruction {}.", ext.name());
+                }
+                let idx = CALL_HELPER_IDX::CALL_CUSTOM_BASE_IDX as usize + self.m_custom_helper_num;
+                self.helper_func[idx] = helper;
+                self.m_custom_helper_num += 1;
+                Some(idx)
+            }
+            None => None,
+        };
+        self.m_riscv_trans.m_custom_inst.push((ext, helper_idx));
+    }
+
     // fn dummy_helper(
     //     _emu: &mut EmuEnv,
     //     _dest: u32,
@@ -270,6 +459,14 @@ impl EmuEnv {
     }
 
     pub fn run(&mut self, filename: &String) {
+        self.load_elf(filename);
+        self.emit_prologue_epilogue();
+        self.run_loop();
+    }
+
+    // Load the sections of an ELF image into guest memory. Without an
+    // explicit --xlen, the ELF class selects RV32 or RV64.
+    pub fn load_elf(&mut self, filename: &String) {
         let loader = match ELFLoader::new(filename) {
             Ok(loader) => loader,
             Err(error) => panic!("There was a problem opening the file: {:?}, {:}", error, filename),
@@ -278,6 +475,15 @@ impl EmuEnv {
         let elf_header = loader.get_elf_header();
         elf_header.dump();
 
+        self.m_xlen = match self.m_arg_config.xlen {
+            Some(xlen) => xlen,
+            None => if loader.is_elf32() { XlenEnum::Rv32 } else { XlenEnum::Rv64 },
+        };
+        if self.m_xlen == XlenEnum::Rv32 {
+            // misa.MXL = 1 : 32-bit
+            self.m_csr.m_misa.m_csr = 0x4000_0000 | (self.m_csr.m_misa.m_csr & 0x03ff_ffff);
+        }
+
         let mut ph_headers = Vec::new();
         for ph_idx in 0..elf_header.e_phnum {
             let phdr: ProgramHeader = loader.get_program_header(
@@ -323,6 +529,34 @@ impl EmuEnv {
                 }
             }
         }
+    }
+
+    // Place raw instruction words in guest memory, for running code without
+    // an ELF image. The PC is set to the first one, and blocks translated
+    // from earlier code are dropped.
+    pub fn load_code(&mut self, guest_phy_addr: u64, code: &[u32]) {
+        for (idx, inst) in code.iter().enumerate() {
+            self.write_mem_4byte(guest_phy_addr + idx as u64 * 4, *inst);
+        }
+        for addr in self.m_tb_text_hash_address.iter_mut() {
+            *addr = 0xdeadbeef;
+        }
+        self.m_pc[0] = guest_phy_addr;
+    }
+
+    // Run count translated blocks from the current PC. With step enabled
+    // each block is a single instruction.
+    pub fn run_blocks(&mut self, count: usize) {
+        self.emit_prologue_epilogue();
+        for _ in 0..count {
+            self.run_block();
+        }
+    }
+
+    fn emit_prologue_epilogue(&mut self) {
+        if !self.m_tcg_raw_vec.is_empty() {
+            return;
+        }
 
         // Emit Prologue
         for b in &self.m_host_prologue {
@@ -338,8 +572,6 @@ impl EmuEnv {
             let v = self.m_tcg_raw_vec.as_slice();
             Self::reflect(v)
         };
-
-        self.run_loop();
     }
 
     fn execute_func(&self, tb_text: *mut u8) {
@@ -360,26 +592,7 @@ impl EmuEnv {
         let loop_max = 10000000;
         self.loop_idx = 5;
         while self.loop_idx < loop_max {
-            if self.m_arg_config.debug {
-                eprintln!("========= BLOCK START =========");
-            }
-
-            assert!(self.m_pc[0] >= 0x8000_0000);
-            self.m_curr_hash_key = calc_hash_func(self.m_pc[0]);
-            if self.m_arg_config.debug {
-                self.decode_and_run();
-            } else {
-                if self.m_tb_text_hash_address[self.m_curr_hash_key] == self.m_pc[0] {
-                    let inst_size = self.m_tb_text_hash_inst_size[self.m_curr_hash_key];
-                    // let mem_map = &self.m_tb_text_hash_memmap[hash_key];
-
-                    self.m_pc[0] = self.m_pc[0] + inst_size as u64;
-                } else {
-                    self.decode_and_run();
-                }
-            };
-
-            self.execute_func(self.m_tb_text_hash_memmap[self.m_curr_hash_key].data());
+            self.run_block();
 
             // if self.m_arg_config.dump_gpr {
             //     self.dump_gpr();
@@ -416,6 +629,34 @@ impl EmuEnv {
     }
 
 
+    fn run_block(&mut self) {
+        if self.m_arg_config.debug {
+            eprintln!("========= BLOCK START =========");
+        }
+
+        self.check_interrupt();
+        assert!(self.m_pc[0] >= 0x8000_0000);
+        self.m_curr_hash_key = calc_hash_func(self.m_pc[0]);
+        if self.m_arg_config.debug {
+            self.decode_and_run();
+        } else {
+            if self.m_tb_text_hash_address[self.m_curr_hash_key] == self.m_pc[0] &&
+                self.m_tb_text_hash_fs[self.m_curr_hash_key] == self.get_fs() &&
+                self.m_tb_text_hash_vs[self.m_curr_hash_key] == self.get_vs() &&
+                self.m_tb_text_hash_priv[self.m_curr_hash_key] == self.m_priv &&
+                self.is_fetch_tag_current(self.m_tb_text_hash_asid[self.m_curr_hash_key]) {
+                let inst_size = self.m_tb_text_hash_inst_size[self.m_curr_hash_key];
+                // let mem_map = &self.m_tb_text_hash_memmap[hash_key];
+
+                self.m_pc[0] = self.m_pc[0] + inst_size as u64;
+            } else {
+                self.decode_and_run();
+            }
+        };
+
+        self.execute_func(self.m_tb_text_hash_memmap[self.m_curr_hash_key].data());
+    }
+
     fn sys_write(&mut self, tohost: u64) {
         let _fd = self.read_mem_8byte(tohost + 8);
         let pbuf = self.read_mem_8byte(tohost + 16);
@@ -530,6 +771,28 @@ impl EmuEnv {
         diff
     }
 
+    pub fn calc_host_mxcsr_relat_address(&self, rm: u64) -> isize {
+        let mxcsr_ptr = self.m_host_mxcsr.as_ptr() as *const u8;
+        let self_ptr = self.head.as_ptr() as *const u8;
+        let mut diff = unsafe { mxcsr_ptr.offset_from(self_ptr) };
+        diff += rm as isize * mem::size_of::<u32>() as isize;
+        diff
+    }
+
+    pub fn calc_host_mxcsr_tmp_relat_address(&self) -> isize {
+        let mxcsr_ptr = self.m_host_mxcsr_tmp.as_ptr() as *const u8;
+        let self_ptr = self.head.as_ptr() as *const u8;
+        let diff = unsafe { mxcsr_ptr.offset_from(self_ptr) };
+        diff
+    }
+
+    pub fn calc_fcsr_relat_address(&self) -> isize {
+        let fcsr_ptr = &self.m_csr.m_fcsr.m_csr as *const i64 as *const u8;
+        let self_ptr = self.head.as_ptr() as *const u8;
+        let diff = unsafe { fcsr_ptr.offset_from(self_ptr) };
+        diff
+    }
+
     pub fn calc_tlb_addr_relat_address(&self) -> isize {
         let tlb_ptr = self.m_tlb_addr_vec.as_ptr() as *const u8;
         let self_ptr = self.head.as_ptr() as *const u8;
@@ -538,6 +801,12 @@ impl EmuEnv {
         diff
     }
 
+    pub fn calc_tlb_asid_relat_address(&self) -> isize {
+        let asid_ptr = self.m_tlb_asid_tag.as_ptr() as *const u8;
+        let self_ptr = self.head.as_ptr() as *const u8;
+        unsafe { asid_ptr.offset_from(self_ptr) }
+    }
+
     pub fn extract_bit_field(hex: i64, left: u8, right: u8) -> i64 {
         let mask: i64 = (1 << (left - right + 1)) - 1;
         return (hex >> right) & mask;
@@ -549,101 +818,104 @@ impl EmuEnv {
     }
 
     pub fn generate_exception(&mut self, guest_pc: u64, code: ExceptCode, tval: i64) {
+        // A page fault raised after a failed G-stage translation is a guest-page fault.
+        let (code, gpa) = match (code, self.m_gpa_fault.take()) {
+            (ExceptCode::InstPageFault, Some(gpa)) => (ExceptCode::InstGuestPageFault, gpa),
+            (ExceptCode::LoadPageFault, Some(gpa)) => (ExceptCode::LoadGuestPageFault, gpa),
+            (ExceptCode::StorePageFault, Some(gpa)) => (ExceptCode::StoreGuestPageFault, gpa),
+            (code, _) => (code, 0),
+        };
         if self.m_arg_config.debug {
             eprintln!(
                 "<Info: Generate Exception Code={}, TVAL={:016x} PC={:016x}>",
                 code as u32, tval, guest_pc
             );
         }
-        let epc = guest_pc;
+        let gva = Self::is_addr_except(code) && (self.m_virt || self.m_fault_gva);
+        self.take_trap(guest_pc, code as u64, false, tval, gpa, gva);
+    }
 
+    // Trap entry for exceptions and interrupts. A trap is handled in M-mode
+    // unless medeleg/mideleg delegates it and the hart is not in M-mode;
+    // a trap delegated to HS-mode from a guest goes on to VS-mode if
+    // hedeleg/hideleg also delegates it. Traps never lower the privilege.
+    pub fn take_trap(&mut self, epc: u64, code: u64, is_interrupt: bool, tval: i64, gpa: i64, gva: bool) {
         let curr_priv: PrivMode = self.m_priv;
+        let curr_virt: bool = self.m_virt;
+
+        let (mdeleg, hdeleg) = if is_interrupt {
+            (self.m_csr.csrrs(CsrAddr::Mideleg, 0), self.m_csr.csrrs(CsrAddr::Hideleg, 0))
+        } else {
+            (self.m_csr.csrrs(CsrAddr::Medeleg, 0), self.m_csr.csrrs(CsrAddr::Hedeleg, 0))
+        };
+        let deleg_s = curr_priv != PrivMode::Machine && (mdeleg >> code) & 1 != 0;
+        let deleg_vs = deleg_s && curr_virt && (hdeleg >> code) & 1 != 0;
+
+        // VS-level interrupts are reported to VS-mode as their S-level counterparts
+        let code = if deleg_vs && is_interrupt { code - 1 } else { code };
+        let interrupt_bit: i64 = if self.is_rv32() { 1 << 31 } else { 1 << 63 };
+        let cause = code as i64 | if is_interrupt { interrupt_bit } else { 0 };
+        let epc = self.mask_xlen(epc) as i64;
 
-        let mut mstatus: i64;
-        let mut sstatus: i64;
         let tvec: i64;
-        let medeleg = self.m_csr.csrrs(CsrAddr::Medeleg, 0);
-        let mut next_priv: PrivMode = PrivMode::Machine;
+        let next_priv: PrivMode;
 
-        self.m_priv = next_priv;
+        if deleg_vs {
+            self.m_csr.csrrw(CsrAddr::Vsepc, epc);
+            self.m_csr.csrrw(CsrAddr::Vscause, cause);
+            self.m_csr.csrrw(CsrAddr::Vstval, tval);
+            tvec = self.m_csr.csrrs(CsrAddr::Vstvec, 0);
 
-        if (medeleg & (1 << (code as u32))) != 0 {
-            // Delegation
-            self.m_csr.csrrw(CsrAddr::Sepc, epc as i64);
-            self.m_csr.csrrw(CsrAddr::Scause, code as i64);
-            self.m_csr.csrrw(CsrAddr::Stval, tval as i64);
+            let vsstatus = self.m_csr.csrrs(CsrAddr::Vsstatus, 0);
+            let vsstatus = Self::trap_status(vsstatus, curr_priv, PrivMode::Supervisor);
+            self.m_csr.csrrw(CsrAddr::Vsstatus, vsstatus);
+            next_priv = PrivMode::Supervisor;
+        } else if deleg_s {
+            self.m_csr.csrrw(CsrAddr::Sepc, epc);
+            self.m_csr.csrrw(CsrAddr::Scause, cause);
+            self.m_csr.csrrw(CsrAddr::Stval, tval);
+            self.m_csr.csrrw(CsrAddr::Htval, gpa >> 2);
+            self.m_csr.csrrw(CsrAddr::Htinst, 0);
+            tvec = self.m_csr.csrrs(CsrAddr::Stvec, 0);
+
+            let mstatus = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
+            let mstatus = Self::trap_status(mstatus, curr_priv, PrivMode::Supervisor);
+            self.m_csr.csrrw(CsrAddr::Mstatus, mstatus);
 
-            tvec = self.m_csr.csrrs(CsrAddr::Stvec, 0 as i64);
+            let mut hstatus = self.m_csr.csrrs(CsrAddr::Hstatus, 0);
+            hstatus = Self::set_bit_field(hstatus, curr_virt as i64, riscv_csr_def::SYSREG_HSTATUS_SPV_MSB, riscv_csr_def::SYSREG_HSTATUS_SPV_LSB);
+            if curr_virt {
+                hstatus = Self::set_bit_field(hstatus, curr_priv as i64, riscv_csr_def::SYSREG_HSTATUS_SPVP_MSB, riscv_csr_def::SYSREG_HSTATUS_SPVP_LSB);
+            }
+            hstatus = Self::set_bit_field(hstatus, gva as i64, riscv_csr_def::SYSREG_HSTATUS_GVA_MSB, riscv_csr_def::SYSREG_HSTATUS_GVA_LSB);
+            self.m_csr.csrrw(CsrAddr::Hstatus, hstatus);
             next_priv = PrivMode::Supervisor;
         } else {
-            self.m_csr.csrrw(CsrAddr::Mepc, epc as i64);
-            self.m_csr.csrrw(CsrAddr::Mcause, code as i64);
-            self.m_csr.csrrw(CsrAddr::Mtval, tval as i64);
-
-            tvec = self.m_csr.csrrs(CsrAddr::Mtvec, 0 as i64);
-            eprint!("tvec = {:016x}\n", tvec);
-        }
-
-        // Update status CSR
-        if (medeleg & (1 << (code as u32))) != 0 {
-            // Delegation
-            sstatus = self.m_csr.csrrs(CsrAddr::Sstatus, 0 as i64);
-            sstatus = Self::set_bit_field(
-                sstatus,
-                Self::extract_bit_field(
-                    sstatus,
-                    riscv_csr_def::SYSREG_SSTATUS_SIE_MSB,
-                    riscv_csr_def::SYSREG_SSTATUS_SIE_LSB,
-                ),
-                riscv_csr_def::SYSREG_SSTATUS_SPIE_MSB,
-                riscv_csr_def::SYSREG_SSTATUS_SPIE_LSB,
-            );
-            sstatus = Self::set_bit_field(
-                sstatus,
-                curr_priv as i64,
-                riscv_csr_def::SYSREG_SSTATUS_SPP_MSB,
-                riscv_csr_def::SYSREG_SSTATUS_SPP_LSB,
-            );
-            sstatus = Self::set_bit_field(
-                sstatus,
-                0,
-                riscv_csr_def::SYSREG_SSTATUS_SIE_MSB,
-                riscv_csr_def::SYSREG_SSTATUS_SIE_LSB,
-            );
-            self.m_csr.csrrw(CsrAddr::Sstatus, sstatus as i64);
-        } else {
-            mstatus = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
-            mstatus = Self::set_bit_field(
-                mstatus,
-                Self::extract_bit_field(
-                    mstatus,
-                    riscv_csr_def::SYSREG_MSTATUS_MIE_MSB,
-                    riscv_csr_def::SYSREG_MSTATUS_MIE_LSB,
-                ),
-                riscv_csr_def::SYSREG_MSTATUS_MPIE_MSB,
-                riscv_csr_def::SYSREG_MSTATUS_MPIE_LSB,
-            );
-            mstatus = Self::set_bit_field(
-                mstatus,
-                curr_priv as i64,
-                riscv_csr_def::SYSREG_MSTATUS_MPP_MSB,
-                riscv_csr_def::SYSREG_MSTATUS_MPP_LSB,
-            );
-            mstatus = Self::set_bit_field(
-                mstatus,
-                0,
-                riscv_csr_def::SYSREG_MSTATUS_MIE_MSB,
-                riscv_csr_def::SYSREG_MSTATUS_MIE_LSB,
-            );
-
+            self.m_csr.csrrw(CsrAddr::Mepc, epc);
+            self.m_csr.csrrw(CsrAddr::Mcause, cause);
+            self.m_csr.csrrw(CsrAddr::Mtval, tval);
+            self.m_csr.csrrw(CsrAddr::Mtval2, gpa >> 2);
+            self.m_csr.csrrw(CsrAddr::Mtinst, 0);
+            tvec = self.m_csr.csrrs(CsrAddr::Mtvec, 0);
+
+            let mut mstatus = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
+            mstatus = Self::trap_status(mstatus, curr_priv, PrivMode::Machine);
+            if !self.is_rv32() {
+                mstatus = Self::set_bit_field(mstatus, curr_virt as i64, riscv_csr_def::SYSREG_MSTATUS_MPV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPV_LSB);
+                mstatus = Self::set_bit_field(mstatus, gva as i64, riscv_csr_def::SYSREG_MSTATUS_GVA_MSB, riscv_csr_def::SYSREG_MSTATUS_GVA_LSB);
+            }
             self.m_csr.csrrw(CsrAddr::Mstatus, mstatus);
+            next_priv = PrivMode::Machine;
         }
 
-        // self.set_priv_mode(next_priv);
+        self.set_virt(deleg_vs);
         self.m_priv = next_priv;
-        // self.set_pc(tvec as u64);
-        // self.set_update_pc(true);
-        self.m_pc[0] = tvec as u64;
+        self.sync_tlb_context();
+
+        // tvec.MODE = 1 (Vectored) : interrupts jump to BASE + 4 * cause
+        let base = (tvec & !0x3) as u64;
+        let target = if is_interrupt && (tvec & 0x3) == 1 { base.wrapping_add(4 * code) } else { base };
+        self.m_pc[0] = self.mask_xlen(target);
 
         if self.m_arg_config.debug {
             eprintln!(
@@ -653,8 +925,94 @@ impl EmuEnv {
             eprintln!("<Info: Set Program Counter = 0x{:16x}>", self.m_pc[0]);
         }
         self.m_updated_pc = true;
+    }
+
+    // Interrupt lines for devices (CLINT, PLIC, ...). A raised line reads as
+    // pending in mip until it is lowered.
+    pub fn raise_interrupt(&mut self, irq: Interrupt) {
+        self.m_csr.set_irq_line(irq, true);
+    }
+
+    pub fn lower_interrupt(&mut self, irq: Interrupt) {
+        self.m_csr.set_irq_line(irq, false);
+    }
+
+    // Checked between translated blocks : takes the interrupt of highest
+    // priority that is pending in mip, enabled in mie and not masked by the
+    // current privilege. Interrupts not delegated by mideleg go to M-mode and
+    // are enabled below M-mode, or in M-mode with mstatus.MIE. Delegated ones
+    // go to HS-mode, are never taken in M-mode, and are enabled below
+    // HS-mode (VS/VU-mode included), or in HS-mode with sstatus.SIE.
+    // VS-level interrupts also delegated by hideleg go to VS-mode : they are
+    // only taken with V=1, in VU-mode or in VS-mode with vsstatus.SIE.
+    // M-mode interrupts come before HS-mode ones, and those before VS-mode ones.
+    pub fn check_interrupt(&mut self) -> bool {
+        let pending = self.m_csr.pending_interrupts();
+        if pending == 0 {
+            return false;
+        }
+        let mideleg = self.m_csr.csrrs(CsrAddr::Mideleg, 0);
+        let hideleg = self.m_csr.csrrs(CsrAddr::Hideleg, 0);
+        let mstatus = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        let vsstatus = self.m_csr.csrrs(CsrAddr::Vsstatus, 0);
+        let mie = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MIE_LSB) != 0;
+        let sie = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_SIE_MSB, riscv_csr_def::SYSREG_MSTATUS_SIE_LSB) != 0;
+        let vsie = Self::extract_bit_field(vsstatus, riscv_csr_def::SYSREG_MSTATUS_SIE_MSB, riscv_csr_def::SYSREG_MSTATUS_SIE_LSB) != 0;
+        let m_enabled = self.m_priv != PrivMode::Machine || mie;
+        let s_enabled = match self.m_priv {
+            PrivMode::Machine => false,
+            PrivMode::Supervisor => self.m_virt || sie,
+            _ => true,
+        };
+        let vs_enabled = self.m_virt && (self.m_priv == PrivMode::User || vsie);
+        let m_pending = if m_enabled { pending & !mideleg } else { 0 };
+        let s_pending = if s_enabled { pending & mideleg & !hideleg } else { 0 };
+        let vs_pending = if vs_enabled { pending & mideleg & hideleg } else { 0 };
+        let irqs = if m_pending != 0 {
+            m_pending
+        } else if s_pending != 0 {
+            s_pending
+        } else {
+            vs_pending
+        };
+        let irq = match INTERRUPT_PRIORITY.iter().find(|irq| (irqs >> (**irq as u64)) & 1 != 0) {
+            Some(irq) => *irq,
+            None => return false,
+        };
+        if self.m_arg_config.debug {
+            eprintln!("<Info: Interrupt Code={}, PC={:016x}>", irq as u32, self.m_pc[0]);
+        }
+        self.take_trap(self.m_pc[0], irq as u64, true, 0, 0, false);
+        return true;
+    }
 
-        return;
+    // xPIE <= xIE, xIE <= 0, xPP <= the privilege the trap was taken from.
+    fn trap_status(status: i64, curr_priv: PrivMode, next_priv: PrivMode) -> i64 {
+        let mut status = status;
+        if next_priv == PrivMode::Machine {
+            let mie = Self::extract_bit_field(status, riscv_csr_def::SYSREG_MSTATUS_MIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MIE_LSB);
+            status = Self::set_bit_field(status, mie, riscv_csr_def::SYSREG_MSTATUS_MPIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MPIE_LSB);
+            status = Self::set_bit_field(status, 0, riscv_csr_def::SYSREG_MSTATUS_MIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MIE_LSB);
+            status = Self::set_bit_field(status, curr_priv as i64, riscv_csr_def::SYSREG_MSTATUS_MPP_MSB, riscv_csr_def::SYSREG_MSTATUS_MPP_LSB);
+        } else {
+            let sie = Self::extract_bit_field(status, riscv_csr_def::SYSREG_SSTATUS_SIE_MSB, riscv_csr_def::SYSREG_SSTATUS_SIE_LSB);
+            status = Self::set_bit_field(status, sie, riscv_csr_def::SYSREG_SSTATUS_SPIE_MSB, riscv_csr_def::SYSREG_SSTATUS_SPIE_LSB);
+            status = Self::set_bit_field(status, 0, riscv_csr_def::SYSREG_SSTATUS_SIE_MSB, riscv_csr_def::SYSREG_SSTATUS_SIE_LSB);
+            status = Self::set_bit_field(status, curr_priv as i64, riscv_csr_def::SYSREG_SSTATUS_SPP_MSB, riscv_csr_def::SYSREG_SSTATUS_SPP_LSB);
+        }
+        status
+    }
+
+    // Exceptions whose tval holds a faulting address
+    fn is_addr_except(code: ExceptCode) -> bool {
+        match code {
+            ExceptCode::InstAddrMisalign | ExceptCode::InstAccessFault |
+            ExceptCode::LoadAddrMisalign | ExceptCode::LoadAccessFault |
+            ExceptCode::StoreAddrMisalign | ExceptCode::StoreAccessFault |
+            ExceptCode::InstPageFault | ExceptCode::LoadPageFault | ExceptCode::StorePageFault |
+            ExceptCode::InstGuestPageFault | ExceptCode::LoadGuestPageFault | ExceptCode::StoreGuestPageFault => true,
+            _ => false,
+        }
     }
 
     pub fn get_mem(&self, addr: u64) -> u32 {
@@ -732,12 +1090,21 @@ impl EmuEnv {
 
         let mut total_inst_byte = 0;
         let init_pc = self.m_pc[0];
+        self.m_riscv_trans.m_ext_c = self.is_ext_c_enabled();
+        self.m_riscv_trans.m_rv32 = self.is_rv32();
+        self.m_riscv_trans.m_fs = self.get_fs();
+        self.m_riscv_trans.m_vs = self.get_vs();
+        self.m_riscv_trans.m_priv = self.m_priv;
+        let init_fs = self.m_riscv_trans.m_fs;
+        let init_vs = self.m_riscv_trans.m_vs;
+        let init_priv = self.m_priv;
         #[allow(while_true)]
         while true {
             self.loop_idx += 1;
             #[allow(unused_assignments)]
             let mut guest_phy_addr = 0;
-            match self.convert_physical_address(self.m_pc[0], self.m_pc[0], MemAccType::Fetch) {
+            // PMP checks the shortest instruction : a compressed one may end a region
+            match self.convert_physical_address(self.m_pc[0], self.m_pc[0], 2, MemAccType::Fetch) {
                 Ok(addr) => guest_phy_addr = addr,
                 Err(_error) => {
                     continue;
@@ -748,7 +1115,10 @@ impl EmuEnv {
             }
             let guest_inst = self.read_mem_4byte(guest_phy_addr);
         
-            let (id, inst_byte) = match decode_inst(guest_inst) {
+            let custom_inst = self.m_riscv_trans.find_custom_inst(guest_inst);
+            let rv32_inst = if self.is_rv32() { decode_inst_rv32(guest_inst) } else { None };
+            let (id, inst_byte) = match custom_inst.as_ref().map(|_| (RiscvInstId::CUSTOM, 4))
+                .or(rv32_inst).or_else(|| decode_inst_ext(guest_inst)).or_else(|| decode_inst(guest_inst)) {
                 Some((id, inst_byte)) => (id, inst_byte),
                 _ => panic!("Decode Failed. {:08x}", guest_inst),
             };
@@ -765,7 +1135,11 @@ impl EmuEnv {
                 tcg_vec.append(&mut exit_tcg);
             }
             if self.m_arg_config.dump_guest {
-                eprint!(" {:016x}:{:016x} Hostcode {:08x} : {}\n",  self.m_pc[0], guest_phy_addr, inst_info.inst, disassemble_riscv(guest_inst));
+                let disasm = match &custom_inst {
+                    Some((ext, _)) => ext.disassemble(guest_inst),
+                    None => disassemble_riscv(guest_inst),
+                };
+                eprint!(" {:016x}:{:016x} Hostcode {:08x} : {}\n",  self.m_pc[0], guest_phy_addr, inst_info.inst, disasm);
             }
             total_inst_byte += inst_byte;
 
@@ -792,6 +1166,17 @@ impl EmuEnv {
             if id == RiscvInstId::FENCE_I {
                 break;
             }
+
+            // A CSR write may change mstatus.FS, which the block is translated for.
+            if id == RiscvInstId::CSRRW
+                || id == RiscvInstId::CSRRS
+                || id == RiscvInstId::CSRRC
+                || id == RiscvInstId::CSRRWI
+                || id == RiscvInstId::CSRRSI
+                || id == RiscvInstId::CSRRCI
+            {
+                break;
+            }
         
             if self.m_arg_config.step {
                 break;      // When self.m_arg_config.debug Mode, break for each instruction
@@ -826,6 +1211,10 @@ impl EmuEnv {
         }
         self.m_tb_text_hash_address[hash_key] = init_pc;
         self.m_tb_text_hash_inst_size[hash_key] = total_inst_byte;
+        self.m_tb_text_hash_fs[hash_key] = init_fs;
+        self.m_tb_text_hash_vs[hash_key] = init_vs;
+        self.m_tb_text_hash_priv[hash_key] = init_priv;
+        self.m_tb_text_hash_asid[hash_key] = self.fetch_tag(init_pc);
 
         for tcg in tcg_vec.iter_mut() {
             match tcg.op {
@@ -8,25 +8,18 @@ pub mod tcg;
 pub mod op_helper;
 pub mod op_helper_fp_d;
 pub mod op_helper_fp_s;
+pub mod op_helper_fp_h;
 pub mod op_helper_mem;
 pub mod op_helper_fcvt;
-
-use emu_env::MachineEnum;
+pub mod op_helper_vec;
+pub mod op_helper_crypto;
 
 use crate::emu_env::{EmuEnv, ArgConfig};
 
 pub fn run(filename: String, step: bool, exp_gpr: &[u64; 32]) -> usize {
     let arg_config = ArgConfig {
-        step    : step,
-        debug   : false,
-        dump_gpr: false,
-        dump_fpr: false,
-        dump_tcg: false,
-        mmu_debug: false,
-        dump_guest: false,
-        dump_host: false,
-        machine : MachineEnum::RiscvVirt,
-        opt_reg_fwd: false,
+        step,
+        ..Default::default()
     };
 
     let mut emu = EmuEnv::new(arg_config);
@@ -43,16 +36,8 @@ pub fn run(filename: String, step: bool, exp_gpr: &[u64; 32]) -> usize {
 
 pub fn run_riscv_test(filename: String, opt_step: bool) -> u64 {
     let arg_config = ArgConfig {
-        step    : opt_step,
-        debug   : false,
-        dump_gpr: false,
-        dump_fpr: false,
-        dump_tcg: false,
-        mmu_debug : false,
-        dump_guest: false,
-        dump_host: false,
-        machine : MachineEnum::RiscvVirt,
-        opt_reg_fwd: false,
+        step: opt_step,
+        ..Default::default()
     };
 
     let riscv_path = match env::var("RISCV") {
@@ -1,5 +1,5 @@
 use clap::{App,Arg};
-use emu_env::MachineEnum;
+use emu_env::{MachineEnum, MisalignEnum, XlenEnum};
 
 extern crate mmap;
 extern crate clap;
@@ -11,9 +11,12 @@ pub mod target;
 pub mod tcg;
 pub mod op_helper;
 pub mod op_helper_fp_s;
+pub mod op_helper_fp_h;
 pub mod op_helper_fp_d;
 pub mod op_helper_mem;
 pub mod op_helper_fcvt;
+pub mod op_helper_vec;
+pub mod op_helper_crypto;
 
 use crate::emu_env::EmuEnv;
 use crate::emu_env::ArgConfig;
@@ -95,6 +98,40 @@ fn main() {
         .long("opt-reg-fwd")
         .required(false)
     )
+    .arg(
+        Arg::new("misaligned")
+        .about("Misaligned load/store policy : trap (default) or emulate")
+        .value_name("POLICY")
+        .long("misaligned")
+        .required(false)
+    )
+    .arg(
+        Arg::new("xlen")
+        .about("Guest XLEN : 32 or 64 (default: from ELF class)")
+        .value_name("XLEN")
+        .long("xlen")
+        .required(false)
+    )
+    .arg(
+        Arg::new("cbo-block-size")
+        .about("Cache-block size in bytes for cbo.* instructions (default: 64)")
+        .value_name("BYTES")
+        .long("cbo-block-size")
+        .required(false)
+    )
+    .arg(
+        Arg::new("pmp-entries")
+        .about("Number of PMP entries : 0, 16 (default) or 64")
+        .value_name("ENTRIES")
+        .long("pmp-entries")
+        .required(false)
+    )
+    .arg(
+        Arg::new("no-host-crypto")
+        .about("Run the scalar crypto instructions in helpers even when the host has AES-NI/PCLMULQDQ")
+        .long("no-host-crypto")
+        .required(false)
+    )
     .get_matches();
 
     let arg_config_step     = matches.is_present("step");
@@ -112,6 +149,31 @@ fn main() {
         _ => panic!("-machine not specified"),
     };
     let arg_config_opt_reg_fwd = matches.is_present("opt-reg-fwd");
+    let arg_config_misalign = match matches.value_of("misaligned") {
+        None | Some("trap") => MisalignEnum::Trap,
+        Some("emulate") => MisalignEnum::Emulate,
+        Some(other) => panic!("--misaligned {} : unknown policy", other),
+    };
+    let arg_config_xlen = match matches.value_of("xlen") {
+        None => None,
+        Some("32") => Some(XlenEnum::Rv32),
+        Some("64") => Some(XlenEnum::Rv64),
+        Some(other) => panic!("--xlen {} : unknown XLEN", other),
+    };
+    let arg_config_cbo_block_size = match matches.value_of("cbo-block-size") {
+        None => 64,
+        Some(size) => match size.parse::<u64>() {
+            Ok(size) if size.is_power_of_two() && size >= 8 && size <= 4096 => size,
+            _ => panic!("--cbo-block-size {} : must be a power of two in 8..4096", size),
+        },
+    };
+    let arg_config_pmp_entries = match matches.value_of("pmp-entries") {
+        None | Some("16") => 16,
+        Some("0") => 0,
+        Some("64") => 64,
+        Some(other) => panic!("--pmp-entries {} : must be 0, 16 or 64", other),
+    };
+    let arg_config_host_crypto = !matches.is_present("no-host-crypto");
 
     let arg_config = ArgConfig {
         step    : arg_config_step,
@@ -124,6 +186,11 @@ fn main() {
         dump_host: arg_config_dump_host,
         machine: arg_config_machine,
         opt_reg_fwd: arg_config_opt_reg_fwd,
+        misalign: arg_config_misalign,
+        xlen: arg_config_xlen,
+        cbo_block_size: arg_config_cbo_block_size,
+        pmp_entries: arg_config_pmp_entries,
+        host_crypto: arg_config_host_crypto,
     };
 
     let elf_file = matches.values_of("elf-file").unwrap().next().unwrap().to_string();
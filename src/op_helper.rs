@@ -1,7 +1,16 @@
+use softfloat_wrapper::RoundingMode;
 use crate::target::riscv::riscv_csr::{CsrAddr};
 use crate::target::riscv::riscv_csr_def;
-use crate::emu_env::EmuEnv;
+use crate::emu_env::{EmuEnv, XlenEnum};
 use crate::target::riscv::riscv::{ExceptCode, PrivMode};
+use crate::target::riscv::pmp::Pmp;
+use crate::target::riscv::tlb::{tlb_tag_asid, TLB_ASID_SHIFT, TLB_SIZE, TLB_SLOTS};
+
+enum CsrOp {
+    Write,
+    Set,
+    Clear,
+}
 
 impl EmuEnv {
     pub fn helper_func_csrrw(
@@ -9,16 +18,10 @@ impl EmuEnv {
         dest: u64,
         source: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let data = emu.m_iregs[source as usize];
-        let reg_data = emu
-            .m_csr
-            .csrrw(CsrAddr::from_u64(csr_addr as u64), data as i64);
-        if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
-        }
-        return 0;
+        let data = emu.m_iregs[source as usize] as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Write, true, guest_pc);
     }
 
     pub fn helper_func_csrrs(
@@ -26,16 +29,10 @@ impl EmuEnv {
         dest: u64,
         source: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let data = emu.m_iregs[source as usize];
-        let reg_data = emu
-            .m_csr
-            .csrrs(CsrAddr::from_u64(csr_addr as u64), data as i64);
-        if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
-        }
-        return 0;
+        let data = emu.m_iregs[source as usize] as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Set, source != 0, guest_pc);
     }
 
     pub fn helper_func_csrrc(
@@ -43,16 +40,10 @@ impl EmuEnv {
         dest: u64,
         source: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let data = emu.m_iregs[source as usize];
-        let reg_data = emu
-            .m_csr
-            .csrrc(CsrAddr::from_u64(csr_addr as u64), data as i64);
-        if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
-        }
-        return 0;
+        let data = emu.m_iregs[source as usize] as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Clear, source != 0, guest_pc);
     }
 
     pub fn helper_func_csrrwi(
@@ -60,15 +51,10 @@ impl EmuEnv {
         dest: u64,
         imm: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let reg_data = emu
-            .m_csr
-            .csrrw(CsrAddr::from_u64(csr_addr as u64), imm as i64);
-        if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
-        }
-        return 0;
+        let data = imm as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Write, true, guest_pc);
     }
 
     pub fn helper_func_csrrsi(
@@ -76,15 +62,10 @@ impl EmuEnv {
         dest: u64,
         imm: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let reg_data = emu
-            .m_csr
-            .csrrs(CsrAddr::from_u64(csr_addr as u64), imm as i64);
-        if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
-        }
-        return 0;
+        let data = imm as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Set, imm != 0, guest_pc);
     }
 
     pub fn helper_func_csrrci(
@@ -92,83 +73,586 @@ impl EmuEnv {
         dest: u64,
         imm: u64,
         csr_addr: u64,
-        _dummy: u64,
+        guest_pc: u64,
     ) -> usize {
-        let reg_data = emu
-            .m_csr
-            .csrrc(CsrAddr::from_u64(csr_addr as u64), imm as i64);
+        let data = imm as i64;
+        return emu.access_csr(dest, csr_addr, data, CsrOp::Clear, imm != 0, guest_pc);
+    }
+
+    // Read-modify-write of a CSR into rd. csrrs/csrrc with rs1 = x0 (or a
+    // zero uimm) do not write. Returns 1 if it trapped.
+    fn access_csr(&mut self, dest: u64, csr_addr: u64, data: i64, op: CsrOp, write: bool, guest_pc: u64) -> usize {
+        let reg_data = if let Some(ext) = self.m_riscv_trans.find_custom_csr(csr_addr) {
+            if let Some(except) = self.custom_csr_fault(csr_addr, write) {
+                self.generate_exception(guest_pc, except, 0);
+                return 1;
+            }
+            let old = *self.m_custom_csr.get(&csr_addr).unwrap_or(&0);
+            if write {
+                let new = match op {
+                    CsrOp::Write => data,
+                    CsrOp::Set => old | data,
+                    CsrOp::Clear => old & !data,
+                };
+                self.m_custom_csr.insert(csr_addr, ext.csr_write(csr_addr, old, new));
+            }
+            old
+        } else if Pmp::is_pmp_csr(csr_addr) {
+            // PMP CSRs are M-mode only. Fetches are checked when a block is
+            // translated, so a change drops the translated blocks too.
+            let rv32 = self.is_rv32();
+            if self.m_priv != PrivMode::Machine || !Pmp::is_csr_implemented(csr_addr, rv32) {
+                self.generate_exception(guest_pc, ExceptCode::IllegalInst, 0);
+                return 1;
+            }
+            let old = self.m_pmp.read_csr(csr_addr, rv32) as i64;
+            let new = match op {
+                CsrOp::Write => data,
+                CsrOp::Set => old | data,
+                CsrOp::Clear => old & !data,
+            };
+            if new != old {
+                let new = self.mask_xlen(new as u64);
+                self.m_pmp.write_csr(csr_addr, new, rv32);
+                self.flush_translation();
+            }
+            old
+        } else {
+            let csr_addr = match self.check_csr_access(csr_addr, guest_pc) {
+                Some(addr) => addr,
+                None => return 1,
+            };
+            let is_fp_csr = match csr_addr {
+                CsrAddr::FFlags | CsrAddr::Frm | CsrAddr::Fcsr => true,
+                _ => false,
+            };
+            if is_fp_csr && self.get_fs() == riscv_csr_def::SYSREG_MSTATUS_FS_OFF {
+                self.generate_exception(guest_pc, ExceptCode::IllegalInst, 0);
+                return 1;
+            }
+            let is_vec_csr = match csr_addr {
+                CsrAddr::Vstart | CsrAddr::Vxsat | CsrAddr::Vxrm | CsrAddr::Vcsr |
+                CsrAddr::Vl | CsrAddr::Vtype | CsrAddr::Vlenb => true,
+                _ => false,
+            };
+            if is_vec_csr && self.get_vs() == riscv_csr_def::SYSREG_MSTATUS_VS_OFF {
+                self.generate_exception(guest_pc, ExceptCode::IllegalInst, 0);
+                return 1;
+            }
+            // satp/vsatp/hgatp are WARL : a write of an unsupported MODE has no effect
+            let old_atp = match csr_addr {
+                CsrAddr::Satp => Some(self.m_csr.m_satp.m_csr),
+                CsrAddr::Vsatp => Some(self.m_csr.m_vsatp.m_csr),
+                CsrAddr::Hgatp => Some(self.m_csr.m_hgatp.m_csr),
+                _ => None,
+            };
+            let (op, data) = match old_atp {
+                Some(old) => {
+                    let new = match op {
+                        CsrOp::Write => data,
+                        CsrOp::Set => old | data,
+                        CsrOp::Clear => old & !data,
+                    };
+                    if self.is_atp_mode_supported(new) { (op, data) } else { (CsrOp::Set, 0) }
+                }
+                None => (op, data),
+            };
+            let misa = self.m_csr.m_misa.m_csr;
+            let reg_data = match op {
+                CsrOp::Write => self.m_csr.csrrw(csr_addr, data),
+                CsrOp::Set => self.m_csr.csrrs(csr_addr, data),
+                CsrOp::Clear => self.m_csr.csrrc(csr_addr, data),
+            };
+            if is_fp_csr {
+                self.m_csr.set_fs_dirty();
+                self.m_host_mxcsr[0b111] = Self::host_mxcsr(self.m_csr.csrrs(CsrAddr::Frm, 0) as u64);
+            }
+            if is_vec_csr {
+                self.m_csr.set_vs_dirty();
+            }
+            self.check_misa_update(misa, guest_pc);
+            self.sync_tlb_context();
+            reg_data
+        };
         if dest != 0 {
-            emu.m_iregs[dest as usize] = reg_data as u64;
+            self.m_iregs[dest as usize] = self.sext_xlen(reg_data as u64);
         }
         return 0;
     }
 
-    pub fn helper_func_ecall(emu: &mut EmuEnv, _dest: u64, _imm: u64, _csr_addr: u64, guest_pc: u64) -> usize {
-        emu.m_csr.csrrw(CsrAddr::Mepc, emu.m_pc[0] as i64); // MEPC
+    // csr[9:8] is the lowest privilege that may access a custom CSR, and
+    // csr[11:10] = 3 marks it read-only. From VS/VU-mode an access that
+    // HS-mode would be allowed is a virtual instruction exception.
+    fn custom_csr_fault(&self, csr_addr: u64, write: bool) -> Option<ExceptCode> {
+        let required = (csr_addr >> 8) & 0x3;
+        let level = match self.m_priv {
+            PrivMode::User if !self.m_virt => 0,
+            PrivMode::Machine => 3,
+            _ => 2,
+        };
+        if required > level {
+            return Some(ExceptCode::IllegalInst);
+        }
+        if write && ((csr_addr >> 10) & 0x3) == 0x3 {
+            return Some(ExceptCode::IllegalInst);
+        }
+        let virt_level = if self.m_priv == PrivMode::User { 0 } else { 1 };
+        if self.m_virt && required > virt_level {
+            return Some(ExceptCode::VirtualInst);
+        }
+        None
+    }
+
+    // Hypervisor and VS CSRs (csr[9:8] = 2) belong to HS-mode. With V=1
+    // they raise a virtual instruction exception, and the supervisor CSRs
+    // are redirected to their VS counterparts.
+    fn check_csr_access(&mut self, csr_addr: u64, guest_pc: u64) -> Option<CsrAddr> {
+        let is_hyp_csr = ((csr_addr >> 8) & 0x3) == 2;
+        if is_hyp_csr && self.m_virt {
+            self.generate_exception(guest_pc, ExceptCode::VirtualInst, 0);
+            return None;
+        }
+        if is_hyp_csr && self.m_priv == PrivMode::User {
+            self.generate_exception(guest_pc, ExceptCode::IllegalInst, 0);
+            return None;
+        }
+        // hstatus.VTVM hands the guest's satp accesses to the hypervisor
+        if csr_addr == 0x180 && self.m_virt && self.is_hstatus_set(riscv_csr_def::SYSREG_HSTATUS_VTVM_MSB, riscv_csr_def::SYSREG_HSTATUS_VTVM_LSB) {
+            self.generate_exception(guest_pc, ExceptCode::VirtualInst, 0);
+            return None;
+        }
+        // mstatus.TVM does the same for satp and hgatp in HS-mode
+        if (csr_addr == 0x180 || csr_addr == 0x680) && !self.m_virt && self.m_priv == PrivMode::Supervisor && self.is_tvm_set() {
+            self.generate_exception(guest_pc, ExceptCode::IllegalInst, 0);
+            return None;
+        }
+        let csr_addr = match csr_addr {
+            0x100 | 0x104 | 0x105 | 0x140 | 0x141 | 0x142 | 0x143 | 0x144 | 0x180 if self.m_virt => csr_addr + 0x100,
+            _ => csr_addr,
+        };
+        return Some(CsrAddr::from_u64(csr_addr));
+    }
+
+    // Entering or leaving a guest changes how every address is translated.
+    pub fn set_virt(&mut self, virt: bool) {
+        if self.m_virt != virt {
+            self.m_virt = virt;
+            self.flush_translation();
+        }
+    }
+
+    pub fn flush_translation(&mut self) {
+        self.flush_tlb();
+        self.m_stlb.flush(None, None);
+
+        for addr in self.m_tb_text_hash_address.iter_mut() {
+            *addr = 0xdeadbeef;
+        }
+    }
+
+    pub fn flush_tlb(&mut self) {
+        for tag in self.m_tlb_vec.iter_mut() {
+            *tag = 0xdeadbeef_01234567;
+        }
+    }
+
+    // Inline TLB entries are tagged with the ASID they were filled with, so
+    // a fence of one ASID drops only its entries. Global pages are not told
+    // apart there : they are dropped too and refilled from the softmmu TLB.
+    fn flush_tlb_selective(&mut self, vaddr: Option<u64>, asid: Option<u64>) {
+        let idx_range = match vaddr {
+            Some(vaddr) => {
+                let idx = ((vaddr >> 12) & 0xfff) as usize;
+                idx..idx + 1
+            }
+            None => 0..TLB_SIZE,
+        };
+        for slot in 0..TLB_SLOTS {
+            for idx in idx_range.clone() {
+                let tag = &mut self.m_tlb_vec[slot * TLB_SIZE + idx];
+                if asid.map_or(true, |asid| tlb_tag_asid(*tag) == asid) {
+                    *tag = 0xdeadbeef_01234567;
+                }
+            }
+        }
+    }
+
+    // Drops the translated blocks overlapping the page of vaddr and/or
+    // fetched with asid. Blocks of global pages (TB_TAG_GLOBAL) survive an
+    // ASID fence.
+    fn flush_tb_selective(&mut self, vaddr: Option<u64>, asid: Option<u64>) {
+        for key in 0..self.m_tb_text_hash_address.len() {
+            let start = self.m_tb_text_hash_address[key];
+            let end = start.wrapping_add(self.m_tb_text_hash_inst_size[key] as u64);
+            let tag = self.m_tb_text_hash_asid[key];
+            let addr_match = vaddr.map_or(true, |vaddr| {
+                let page = vaddr & !0xfff;
+                start < page + 0x1000 && end > page
+            });
+            let asid_match = asid.map_or(true, |asid| tag == asid);
+            if addr_match && asid_match {
+                self.m_tb_text_hash_address[key] = 0xdeadbeef;
+            }
+        }
+    }
+
+    // Everything that decides how a data access is translated and checked,
+    // besides the privilege which selects the inline TLB table : V,
+    // MPRV/MPP/MPV and SUM/MXR of mstatus and vsstatus, the MODE of satp and
+    // vsatp, and hgatp. Inline TLB entries hold translations checked for one
+    // context, so they are dropped when it changes. Like the softmmu TLB they
+    // are tagged with an ASID and not with the root page table : software
+    // fences an ASID before reusing it with another one, so switching satp
+    // between address spaces keeps the entries of each.
+    fn tlb_context(&self) -> [u64; 4] {
+        let mstatus_mask: u64 = (1 << riscv_csr_def::SYSREG_MSTATUS_MPV_LSB) |
+            (1 << riscv_csr_def::SYSREG_MSTATUS_MXR_LSB) |
+            (1 << riscv_csr_def::SYSREG_MSTATUS_SUM_LSB) |
+            (1 << riscv_csr_def::SYSREG_MSTATUS_MPRV_LSB) |
+            (0x3 << riscv_csr_def::SYSREG_MSTATUS_MPP_LSB);
+        let vsstatus_mask: u64 = (1 << riscv_csr_def::SYSREG_MSTATUS_MXR_LSB) | (1 << riscv_csr_def::SYSREG_MSTATUS_SUM_LSB);
+        let mstatus = self.m_csr.m_mstatus.m_csr as u64;
+        let vsstatus = self.m_csr.m_vsstatus.m_csr as u64;
+        let status = (mstatus & mstatus_mask) | ((vsstatus & vsstatus_mask) << 2) | (self.m_virt as u64);
+        let (mode_msb, mode_lsb) = if self.is_rv32() { (31, 31) } else { (63, 60) };
+        let satp_mode = Self::extract_bit_field(self.m_csr.m_satp.m_csr, mode_msb, mode_lsb) as u64;
+        let vsatp_mode = Self::extract_bit_field(self.m_csr.m_vsatp.m_csr, mode_msb, mode_lsb) as u64;
+        return [status, satp_mode, vsatp_mode, self.m_csr.m_hgatp.m_csr as u64];
+    }
+
+    pub fn sync_tlb_context(&mut self) {
+        let context = self.tlb_context();
+        if context != self.m_tlb_context {
+            self.m_tlb_context = context;
+            self.flush_tlb();
+        }
+        let atp = if self.m_virt { self.m_csr.m_vsatp.m_csr } else { self.m_csr.m_satp.m_csr };
+        self.m_tlb_asid_tag[0] = self.get_asid(atp) << TLB_ASID_SHIFT;
+    }
+
+    // misa.C can be toggled at runtime. Clearing C is ignored if the next
+    // instruction is not 4-byte aligned. Branch checks are generated for the
+    // current C setting, so translated blocks are dropped when it changes.
+    fn check_misa_update(&mut self, old_misa: i64, guest_pc: u64) {
+        let new_misa = self.m_csr.m_misa.m_csr;
+        let old_c = Self::extract_bit_field(old_misa, riscv_csr_def::SYSREG_MISA_C_MSB, riscv_csr_def::SYSREG_MISA_C_LSB);
+        let new_c = Self::extract_bit_field(new_misa, riscv_csr_def::SYSREG_MISA_C_MSB, riscv_csr_def::SYSREG_MISA_C_LSB);
+        if old_c == new_c {
+            return;
+        }
+        if new_c == 0 && (guest_pc.wrapping_add(4) & 0x2) != 0 {
+            self.m_csr.m_misa.m_csr = old_misa;
+            return;
+        }
+        for addr in self.m_tb_text_hash_address.iter_mut() {
+            *addr = 0xdeadbeef;
+        }
+    }
+
+    pub fn is_ext_c_enabled(&self) -> bool {
+        return Self::extract_bit_field(self.m_csr.m_misa.m_csr, riscv_csr_def::SYSREG_MISA_C_MSB, riscv_csr_def::SYSREG_MISA_C_LSB) != 0;
+    }
+
+    pub fn get_fs(&self) -> i64 {
+        return Self::extract_bit_field(self.m_csr.m_mstatus.m_csr, riscv_csr_def::SYSREG_MSTATUS_FS_MSB, riscv_csr_def::SYSREG_MSTATUS_FS_LSB);
+    }
+
+    // mstatus.VS, read as Off while misa.V is clear.
+    pub fn get_vs(&self) -> i64 {
+        if Self::extract_bit_field(self.m_csr.m_misa.m_csr, riscv_csr_def::SYSREG_MISA_V_MSB, riscv_csr_def::SYSREG_MISA_V_LSB) == 0 {
+            return riscv_csr_def::SYSREG_MSTATUS_VS_OFF;
+        }
+        return Self::extract_bit_field(self.m_csr.m_mstatus.m_csr, riscv_csr_def::SYSREG_MSTATUS_VS_MSB, riscv_csr_def::SYSREG_MSTATUS_VS_LSB);
+    }
+
+    pub fn is_rv32(&self) -> bool {
+        return self.m_xlen == XlenEnum::Rv32;
+    }
+
+    // On RV32, registers hold 32-bit values sign-extended to 64 bits,
+    // while the PC and addresses are kept zero-extended.
+    pub fn sext_xlen(&self, data: u64) -> u64 {
+        if self.is_rv32() { data as i32 as i64 as u64 } else { data }
+    }
 
+    pub fn mask_xlen(&self, addr: u64) -> u64 {
+        if self.is_rv32() { addr & 0xffff_ffff } else { addr }
+    }
+
+    // rd, rs1, rs2 and rs3 of an FP instruction
+    pub fn fp_operands(inst: u64) -> (u64, u64, u64, u64) {
+        ((inst >> 7) & 0x1f, (inst >> 15) & 0x1f, (inst >> 20) & 0x1f, (inst >> 27) & 0x1f)
+    }
+
+    // Rounding mode of an FP instruction : the rm field, or frm if rm is DYN.
+    // Reserved values raise an illegal instruction exception.
+    pub fn get_rounding_mode(&mut self, inst: u64, guest_pc: u64) -> Option<RoundingMode> {
+        let rm = match (inst >> 12) & 0x7 {
+            0b111 => self.m_csr.csrrs(CsrAddr::Frm, 0) as u64,
+            rm => rm,
+        };
+        match Self::decode_rounding_mode(rm) {
+            Some(rm) => Some(rm),
+            None => {
+                self.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+                None
+            }
+        }
+    }
+
+    pub fn decode_rounding_mode(rm: u64) -> Option<RoundingMode> {
+        match rm {
+            0b000 => Some(RoundingMode::TiesToEven),
+            0b001 => Some(RoundingMode::TowardZero),
+            0b010 => Some(RoundingMode::TowardNegative),
+            0b011 => Some(RoundingMode::TowardPositive),
+            0b100 => Some(RoundingMode::TiesToAway),
+            _ => None,
+        }
+    }
+
+    // MXCSR for the inline SSE2 path : all exceptions masked, RC from rm.
+    // RMM has no SSE2 equivalent and reserved modes must trap, so both
+    // preset IE, which sends the instruction to its softfloat helper.
+    pub fn host_mxcsr(rm: u64) -> u32 {
+        match rm {
+            0b000 => 0x1f80,
+            0b001 => 0x7f80,
+            0b010 => 0x3f80,
+            0b011 => 0x5f80,
+            _ => 0x1f81,
+        }
+    }
+
+    pub fn helper_func_illegal_inst(emu: &mut EmuEnv, inst: u64, _dummy0: u64, _dummy1: u64, guest_pc: u64) -> usize {
+        emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+        return 1;
+    }
+
+    // Marks the FP state Dirty before the rest of a block that was
+    // translated with FS=Initial or Clean.
+    pub fn helper_func_fs_dirty(emu: &mut EmuEnv, _dummy0: u64, _dummy1: u64, _dummy2: u64, _dummy3: u64) -> usize {
+        emu.m_csr.set_fs_dirty();
+        return 0;
+    }
+
+    // Same for the vector state and mstatus.VS.
+    pub fn helper_func_vs_dirty(emu: &mut EmuEnv, _dummy0: u64, _dummy1: u64, _dummy2: u64, _dummy3: u64) -> usize {
+        emu.m_csr.set_vs_dirty();
+        return 0;
+    }
+
+    // Raise Instruction Address Misaligned if the jump target is not 4-byte
+    // aligned while C is disabled. Static targets are passed with rs1 = x0.
+    pub fn helper_func_check_jump_target(emu: &mut EmuEnv, rs1: u64, imm: u64, _dummy: u64, guest_pc: u64) -> usize {
+        let target = emu.mask_xlen(emu.m_iregs[rs1 as usize].wrapping_add(imm) & !1);
+        if !emu.is_ext_c_enabled() && (target & 0x2) != 0 {
+            emu.generate_exception(guest_pc, ExceptCode::InstAddrMisalign, target as i64);
+            return 1;
+        }
+        return 0;
+    }
+
+    pub fn helper_func_ecall(emu: &mut EmuEnv, _dest: u64, _imm: u64, _csr_addr: u64, guest_pc: u64) -> usize {
         let current_priv: PrivMode = emu.m_priv;
         match current_priv {
             PrivMode::User       => emu.generate_exception(guest_pc, ExceptCode::EcallFromUMode, 0),
+            PrivMode::Supervisor if emu.m_virt => emu.generate_exception(guest_pc, ExceptCode::EcallFromVSMode, 0),
             PrivMode::Supervisor => emu.generate_exception(guest_pc, ExceptCode::EcallFromSMode, 0),
-            PrivMode::Hypervisor => emu.generate_exception(guest_pc, ExceptCode::EcallFromHMode, 0),
+            // MPP never holds 2, so m_priv is never Hypervisor
+            PrivMode::Hypervisor => unreachable!(),
             PrivMode::Machine    => emu.generate_exception(guest_pc, ExceptCode::EcallFromMMode, 0),
         }
 
         return 0;
     }
 
-    pub fn helper_func_mret(emu: &mut EmuEnv, _dest: u64, _imm: u64, _csr_addr: u64, _dummy: u64) -> usize {
-        emu.m_pc[0] = emu.m_csr.csrrc(CsrAddr::Mepc, 0 as i64) as u64;
+    // MIE <= MPIE, MPIE <= 1, MPP <= U and the privilege and V held in
+    // MPP/MPV are restored. Leaving M-mode clears MPRV.
+    pub fn helper_func_mret(emu: &mut EmuEnv, inst: u64, _imm: u64, _csr_addr: u64, guest_pc: u64) -> usize {
+        if emu.m_priv != PrivMode::Machine {
+            emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+            return 1;
+        }
+        let mut mstatus: i64 = emu.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        let next_priv = PrivMode::from_u8(Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPP_MSB, riscv_csr_def::SYSREG_MSTATUS_MPP_LSB) as u8);
+        let next_virt = !emu.is_rv32() && next_priv != PrivMode::Machine &&
+            Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPV_LSB) != 0;
+
+        let mpie = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MPIE_LSB);
+        mstatus = Self::set_bit_field(mstatus, mpie, riscv_csr_def::SYSREG_MSTATUS_MIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MIE_LSB);
+        mstatus = Self::set_bit_field(mstatus, 1, riscv_csr_def::SYSREG_MSTATUS_MPIE_MSB, riscv_csr_def::SYSREG_MSTATUS_MPIE_LSB);
+        mstatus = Self::set_bit_field(mstatus, PrivMode::User as i64, riscv_csr_def::SYSREG_MSTATUS_MPP_MSB, riscv_csr_def::SYSREG_MSTATUS_MPP_LSB);
+        if !emu.is_rv32() {
+            mstatus = Self::set_bit_field(mstatus, 0, riscv_csr_def::SYSREG_MSTATUS_MPV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPV_LSB);
+        }
+        if next_priv != PrivMode::Machine {
+            mstatus = Self::set_bit_field(mstatus, 0, riscv_csr_def::SYSREG_MSTATUS_MPRV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPRV_LSB);
+        }
+        emu.m_csr.csrrw(CsrAddr::Mstatus, mstatus);
+
+        emu.m_priv = next_priv;
+        emu.set_virt(next_virt);
+        emu.sync_tlb_context();
+        emu.m_pc[0] = emu.mask_xlen(emu.m_csr.csrrs(CsrAddr::Mepc, 0) as u64);
+        return 0;
+    }
+
+    pub fn helper_func_sret(emu: &mut EmuEnv, inst: u64, _imm: u64, _csr_addr: u64, guest_pc: u64) -> usize {
+        if emu.m_virt {
+            let hstatus: i64 = emu.m_csr.csrrs(CsrAddr::Hstatus, 0);
+            let vtsr = Self::extract_bit_field(hstatus, riscv_csr_def::SYSREG_HSTATUS_VTSR_MSB, riscv_csr_def::SYSREG_HSTATUS_VTSR_LSB);
+            if emu.m_priv == PrivMode::User || vtsr != 0 {
+                emu.generate_exception(guest_pc, ExceptCode::VirtualInst, inst as i64);
+                return 1;
+            }
+            // VS-mode returns through vsstatus/vsepc and stays virtualized
+            let vsstatus: i64 = emu.m_csr.csrrs(CsrAddr::Vsstatus, 0);
+            let (next_priv, next_vsstatus) = Self::sret_status(vsstatus);
+            emu.m_csr.csrrw(CsrAddr::Vsstatus, next_vsstatus);
+            let ret_pc = emu.m_csr.csrrs(CsrAddr::Vsepc, 0);
+            emu.m_priv = next_priv;
+            emu.sync_tlb_context();
+            emu.m_pc[0] = emu.mask_xlen(ret_pc as u64);
+            return 0;
+        }
+
+        let mstatus: i64 = emu.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        let tsr = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_TSR_MSB, riscv_csr_def::SYSREG_MSTATUS_TSR_LSB);
+        if emu.m_priv == PrivMode::User || (emu.m_priv == PrivMode::Supervisor && tsr != 0) {
+            emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+            return 1;
+        }
+        let (next_priv, next_mstatus) = Self::sret_status(mstatus);
+        let next_mstatus = Self::set_bit_field(next_mstatus, 0, riscv_csr_def::SYSREG_MSTATUS_MPRV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPRV_LSB);
+
+        emu.m_csr.csrrw(CsrAddr::Mstatus, next_mstatus);
+        let ret_pc = emu.m_csr.csrrs(CsrAddr::Sepc, 0);
+        emu.m_priv = next_priv;
+
+        // HS-mode sret enters the guest if hstatus.SPV is set
+        let hstatus: i64 = emu.m_csr.csrrs(CsrAddr::Hstatus, 0);
+        let next_virt = Self::extract_bit_field(hstatus, riscv_csr_def::SYSREG_HSTATUS_SPV_MSB, riscv_csr_def::SYSREG_HSTATUS_SPV_LSB) != 0;
+        let hstatus = Self::set_bit_field(hstatus, 0, riscv_csr_def::SYSREG_HSTATUS_SPV_MSB, riscv_csr_def::SYSREG_HSTATUS_SPV_LSB);
+        emu.m_csr.csrrw(CsrAddr::Hstatus, hstatus);
+        emu.set_virt(next_virt);
+        emu.sync_tlb_context();
+
+        emu.m_pc[0] = emu.mask_xlen(ret_pc as u64);
+        
         return 0;
     }
-    
-    pub fn helper_func_sret(emu: &mut EmuEnv, _dest: u64, _imm: u64, _csr_addr: u64, _dummy: u64) -> usize {
-        let mstatus: i64 = emu.m_csr.csrrs(CsrAddr::Mstatus, PrivMode::Machine as i64);
-        let next_priv_uint: i64 = Self::extract_bit_field( mstatus, riscv_csr_def::SYSREG_MSTATUS_SPP_MSB, riscv_csr_def::SYSREG_MSTATUS_SPP_LSB,
+
+    // SIE <= SPIE, SPIE <= 1, SPP <= U. Returns the privilege held in SPP.
+    fn sret_status(status: i64) -> (PrivMode, i64) {
+        let next_priv_uint: i64 = Self::extract_bit_field( status, riscv_csr_def::SYSREG_MSTATUS_SPP_MSB, riscv_csr_def::SYSREG_MSTATUS_SPP_LSB,
         );
         let next_priv: PrivMode = PrivMode::from_u8(next_priv_uint as u8);
-        let mut next_mstatus: i64 = mstatus;
-        next_mstatus = Self::set_bit_field(
-            next_mstatus,
+        let mut next_status: i64 = status;
+        next_status = Self::set_bit_field(
+            next_status,
             Self::extract_bit_field(
-                mstatus,
+                status,
                 riscv_csr_def::SYSREG_MSTATUS_SPIE_MSB,
                 riscv_csr_def::SYSREG_MSTATUS_SPIE_LSB,
             ),
             riscv_csr_def::SYSREG_MSTATUS_SIE_MSB,
             riscv_csr_def::SYSREG_MSTATUS_SIE_LSB,
         );
-        next_mstatus = Self::set_bit_field(
-            next_mstatus,
+        next_status = Self::set_bit_field(
+            next_status,
             1,
             riscv_csr_def::SYSREG_MSTATUS_SPIE_MSB,
             riscv_csr_def::SYSREG_MSTATUS_SPIE_LSB,
         );
-        next_mstatus = Self::set_bit_field(
-            next_mstatus,
+        next_status = Self::set_bit_field(
+            next_status,
             PrivMode::User as i64,
             riscv_csr_def::SYSREG_MSTATUS_SPP_MSB,
             riscv_csr_def::SYSREG_MSTATUS_SPP_LSB,
         );
+        return (next_priv, next_status);
+    }
 
-        emu.m_csr.csrrw(CsrAddr::Mstatus, next_mstatus);
-        let ret_pc = emu.m_csr.csrrs(CsrAddr::Sepc, 0);
-        emu.m_priv = next_priv;
-
-        emu.m_pc[0] = ret_pc as u64;
-        
+    // sfence.vma rs1, rs2 : rs1 = x0 fences every address, rs2 = x0 every
+    // address space. Translations of global pages survive a fence of one
+    // ASID. In VS-mode the fence applies to vsatp, whose translations are
+    // not kept in the softmmu TLB.
+    pub fn helper_func_sfence_vma(emu: &mut EmuEnv, rs1: u64, rs2: u64, inst: u64, guest_pc: u64) -> usize {
+        if !emu.m_virt && (emu.m_priv == PrivMode::User || (emu.m_priv == PrivMode::Supervisor && emu.is_tvm_set())) {
+            emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+            return 1;
+        }
+        if emu.m_virt && (emu.m_priv == PrivMode::User ||
+                          emu.is_hstatus_set(riscv_csr_def::SYSREG_HSTATUS_VTVM_MSB, riscv_csr_def::SYSREG_HSTATUS_VTVM_LSB)) {
+            emu.generate_exception(guest_pc, ExceptCode::VirtualInst, inst as i64);
+            return 1;
+        }
+        let vaddr = if rs1 == 0 { None } else { Some(emu.mask_xlen(emu.m_iregs[rs1 as usize])) };
+        let asid_bits = if emu.is_rv32() { 9 } else { 16 };
+        let asid = if rs2 == 0 { None } else { Some(emu.m_iregs[rs2 as usize] & ((1 << asid_bits) - 1)) };
+        if vaddr.is_none() && asid.is_none() {
+            emu.flush_translation();
+            return 0;
+        }
+        if !emu.m_virt {
+            emu.m_stlb.flush(vaddr.map(|vaddr| vaddr >> 12), asid);
+        }
+        emu.flush_tlb_selective(vaddr, asid);
+        emu.flush_tb_selective(vaddr, asid);
         return 0;
     }
 
-    pub fn helper_func_sfence_vma(emu: &mut EmuEnv, _dest: u64, _imm: u64, _csr_addr: u64, _dummy: u64) -> usize {
-        // Clear TLB
-        for idx in 0..4096 {
-            emu.m_tlb_vec[idx] = 0xdeadbeef_01234567;
+    // HLV/HSV/HFENCE : illegal in VS/VU-mode (virtual instruction), and in
+    // U-mode unless hstatus.HU allows it (HLV/HSV only).
+    pub fn check_hypervisor_inst(&mut self, inst: u64, allow_hu: bool, guest_pc: u64) -> bool {
+        if self.m_virt {
+            self.generate_exception(guest_pc, ExceptCode::VirtualInst, inst as i64);
+            return false;
         }
+        if self.m_priv == PrivMode::User {
+            let hstatus: i64 = self.m_csr.csrrs(CsrAddr::Hstatus, 0);
+            let hu = Self::extract_bit_field(hstatus, riscv_csr_def::SYSREG_HSTATUS_HU_MSB, riscv_csr_def::SYSREG_HSTATUS_HU_LSB);
+            if !allow_hu || hu == 0 {
+                self.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+                return false;
+            }
+        }
+        return true;
+    }
 
-        for addr in emu.m_tb_text_hash_address.iter_mut() {
-            *addr = 0xdeadbeef;
+    // There is nothing to wait for, so wfi only checks the timeout-wait bits.
+    pub fn helper_func_wfi(emu: &mut EmuEnv, inst: u64, _dummy0: u64, _dummy1: u64, guest_pc: u64) -> usize {
+        let mstatus: i64 = emu.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        let tw = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_TW_MSB, riscv_csr_def::SYSREG_MSTATUS_TW_LSB);
+        if (emu.m_priv == PrivMode::User && !emu.m_virt) || (emu.m_priv != PrivMode::Machine && tw != 0) {
+            emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+            return 1;
+        }
+        if emu.m_virt && (emu.m_priv == PrivMode::User ||
+                          emu.is_hstatus_set(riscv_csr_def::SYSREG_HSTATUS_VTW_MSB, riscv_csr_def::SYSREG_HSTATUS_VTW_LSB)) {
+            emu.generate_exception(guest_pc, ExceptCode::VirtualInst, inst as i64);
+            return 1;
+        }
+        return 0;
+    }
+
+    fn is_tvm_set(&mut self) -> bool {
+        let mstatus: i64 = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_TVM_MSB, riscv_csr_def::SYSREG_MSTATUS_TVM_LSB) != 0
+    }
+
+    fn is_hstatus_set(&mut self, msb: u8, lsb: u8) -> bool {
+        let hstatus: i64 = self.m_csr.csrrs(CsrAddr::Hstatus, 0);
+        Self::extract_bit_field(hstatus, msb, lsb) != 0
+    }
+
+    // hfence.vvma / hfence.gvma : there is no per-stage TLB, so drop everything.
+    pub fn helper_func_hfence(emu: &mut EmuEnv, inst: u64, _dummy0: u64, _dummy1: u64, guest_pc: u64) -> usize {
+        if !emu.check_hypervisor_inst(inst, false, guest_pc) {
+            return 1;
         }
+        emu.flush_translation();
         return 0;
     }
 
@@ -0,0 +1,260 @@
+use crate::target::riscv::riscv::CallCryptoIdx;
+use crate::emu_env::EmuEnv;
+
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const AES_INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+const SM4_SBOX: [u8; 256] = [
+    0xd6, 0x90, 0xe9, 0xfe, 0xcc, 0xe1, 0x3d, 0xb7, 0x16, 0xb6, 0x14, 0xc2, 0x28, 0xfb, 0x2c, 0x05,
+    0x2b, 0x67, 0x9a, 0x76, 0x2a, 0xbe, 0x04, 0xc3, 0xaa, 0x44, 0x13, 0x26, 0x49, 0x86, 0x06, 0x99,
+    0x9c, 0x42, 0x50, 0xf4, 0x91, 0xef, 0x98, 0x7a, 0x33, 0x54, 0x0b, 0x43, 0xed, 0xcf, 0xac, 0x62,
+    0xe4, 0xb3, 0x1c, 0xa9, 0xc9, 0x08, 0xe8, 0x95, 0x80, 0xdf, 0x94, 0xfa, 0x75, 0x8f, 0x3f, 0xa6,
+    0x47, 0x07, 0xa7, 0xfc, 0xf3, 0x73, 0x17, 0xba, 0x83, 0x59, 0x3c, 0x19, 0xe6, 0x85, 0x4f, 0xa8,
+    0x68, 0x6b, 0x81, 0xb2, 0x71, 0x64, 0xda, 0x8b, 0xf8, 0xeb, 0x0f, 0x4b, 0x70, 0x56, 0x9d, 0x35,
+    0x1e, 0x24, 0x0e, 0x5e, 0x63, 0x58, 0xd1, 0xa2, 0x25, 0x22, 0x7c, 0x3b, 0x01, 0x21, 0x78, 0x87,
+    0xd4, 0x00, 0x46, 0x57, 0x9f, 0xd3, 0x27, 0x52, 0x4c, 0x36, 0x02, 0xe7, 0xa0, 0xc4, 0xc8, 0x9e,
+    0xea, 0xbf, 0x8a, 0xd2, 0x40, 0xc7, 0x38, 0xb5, 0xa3, 0xf7, 0xf2, 0xce, 0xf9, 0x61, 0x15, 0xa1,
+    0xe0, 0xae, 0x5d, 0xa4, 0x9b, 0x34, 0x1a, 0x55, 0xad, 0x93, 0x32, 0x30, 0xf5, 0x8c, 0xb1, 0xe3,
+    0x1d, 0xf6, 0xe2, 0x2e, 0x82, 0x66, 0xca, 0x60, 0xc0, 0x29, 0x23, 0xab, 0x0d, 0x53, 0x4e, 0x6f,
+    0xd5, 0xdb, 0x37, 0x45, 0xde, 0xfd, 0x8e, 0x2f, 0x03, 0xff, 0x6a, 0x72, 0x6d, 0x6c, 0x5b, 0x51,
+    0x8d, 0x1b, 0xaf, 0x92, 0xbb, 0xdd, 0xbc, 0x7f, 0x11, 0xd9, 0x5c, 0x41, 0x1f, 0x10, 0x5a, 0xd8,
+    0x0a, 0xc1, 0x31, 0x88, 0xa5, 0xcd, 0x7b, 0xbd, 0x2d, 0x74, 0xd0, 0x12, 0xb8, 0xe5, 0xb4, 0xb0,
+    0x89, 0x69, 0x97, 0x4a, 0x0c, 0x96, 0x77, 0x7e, 0x65, 0xb9, 0xf1, 0x09, 0xc5, 0x6e, 0xc6, 0x84,
+    0x18, 0xf0, 0x7d, 0xec, 0x3a, 0xdc, 0x4d, 0x20, 0x79, 0xee, 0x5f, 0x3e, 0xd7, 0xcb, 0x39, 0x48,
+];
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut r = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            r ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    r
+}
+
+// Lower 64 bits of (Inv)ShiftRows applied to the state rs2:rs1. Byte i of
+// the state is row i % 4 of column i / 4.
+fn aes_shiftrows(rs1: u64, rs2: u64, inverse: bool) -> u64 {
+    let state = ((rs2 as u128) << 64) | rs1 as u128;
+    let mut result = 0;
+    for i in 0..8 {
+        let (col, row) = (i / 4, i % 4);
+        let src_col = if inverse { (col + 4 - row) % 4 } else { (col + row) % 4 };
+        let byte = (state >> ((src_col * 4 + row) * 8)) as u8;
+        result |= (byte as u64) << (i * 8);
+    }
+    result
+}
+
+fn aes_subbytes(x: u64, sbox: &[u8; 256]) -> u64 {
+    let mut bytes = x.to_le_bytes();
+    for b in bytes.iter_mut() {
+        *b = sbox[*b as usize];
+    }
+    u64::from_le_bytes(bytes)
+}
+
+fn aes_mixcolumn(col: u32, inverse: bool) -> u32 {
+    let b = col.to_le_bytes();
+    let m: [u8; 4] = if inverse { [0x0e, 0x0b, 0x0d, 0x09] } else { [0x02, 0x03, 0x01, 0x01] };
+    let mut out = [0u8; 4];
+    for r in 0..4 {
+        for k in 0..4 {
+            out[r] ^= gf_mul(b[k], m[(k + 4 - r) % 4]);
+        }
+    }
+    u32::from_le_bytes(out)
+}
+
+fn aes_mixcolumns(x: u64, inverse: bool) -> u64 {
+    let lo = aes_mixcolumn(x as u32, inverse) as u64;
+    let hi = aes_mixcolumn((x >> 32) as u32, inverse) as u64;
+    (hi << 32) | lo
+}
+
+// aes32* : one S-box lookup of byte bs of rs2, optionally mixed as a
+// column with that byte in row 0, rotated back into place.
+fn aes32(rs1: u64, rs2: u64, bs: u64, inverse: bool, mix: bool) -> u64 {
+    let si = (rs2 >> (bs * 8)) as u8;
+    let so = if inverse { AES_INV_SBOX[si as usize] } else { AES_SBOX[si as usize] };
+    let mixed = if mix { aes_mixcolumn(so as u32, inverse) } else { so as u32 };
+    ((rs1 as u32) ^ mixed.rotate_left((bs * 8) as u32)) as i32 as u64
+}
+
+fn aes_rcon(rnum: u64) -> u32 {
+    const RCON: [u32; 11] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36, 0x00];
+    RCON[rnum as usize]
+}
+
+fn sm4(rs1: u64, rs2: u64, bs: u64, key_schedule: bool) -> u64 {
+    let x = SM4_SBOX[((rs2 >> (bs * 8)) & 0xff) as usize] as u32;
+    let y = if key_schedule {
+        x ^ ((x & 0x07) << 29) ^ ((x & 0xfe) << 7) ^ ((x & 0x01) << 23) ^ ((x & 0xf8) << 13)
+    } else {
+        x ^ (x << 8) ^ (x << 2) ^ (x << 18) ^ ((x & 0x3f) << 26) ^ ((x & 0xc0) << 10)
+    };
+    ((rs1 as u32) ^ y.rotate_left((bs * 8) as u32)) as i32 as u64
+}
+
+fn clmul(a: u64, b: u64) -> u128 {
+    let mut r: u128 = 0;
+    for i in 0..64 {
+        if (b >> i) & 1 != 0 {
+            r ^= (a as u128) << i;
+        }
+    }
+    r
+}
+
+// Each element of rs2 (4 or 8 bits wide) indexes an element of rs1.
+fn xperm(rs1: u64, rs2: u64, width: u32, xlen: u32) -> u64 {
+    let mask = (1u64 << width) - 1;
+    let mut result = 0;
+    for i in (0..xlen).step_by(width as usize) {
+        let pos = ((rs2 >> i) & mask) as u32 * width;
+        if pos < xlen {
+            result |= ((rs1 >> pos) & mask) << i;
+        }
+    }
+    result
+}
+
+fn zip32(x: u32) -> u32 {
+    let mut result = 0;
+    for i in 0..16 {
+        result |= ((x >> i) & 1) << (2 * i);
+        result |= ((x >> (i + 16)) & 1) << (2 * i + 1);
+    }
+    result
+}
+
+fn unzip32(x: u32) -> u32 {
+    let mut result = 0;
+    for i in 0..16 {
+        result |= ((x >> (2 * i)) & 1) << i;
+        result |= ((x >> (2 * i + 1)) & 1) << (i + 16);
+    }
+    result
+}
+
+impl EmuEnv {
+    // Scalar crypto (Zbkb/Zbkc/Zbkx/Zkn/Zks). The registers, bs and rnum
+    // are decoded from the instruction.
+    pub fn helper_func_crypto(emu: &mut EmuEnv, call_idx: u64, inst: u64, _: u64, _: u64) -> usize {
+        let rd = (inst >> 7) & 0x1f;
+        let rs1 = emu.m_iregs[((inst >> 15) & 0x1f) as usize];
+        let rs2 = emu.m_iregs[((inst >> 20) & 0x1f) as usize];
+        let bs = (inst >> 30) & 0x3;
+        let xlen = if emu.is_rv32() { 32 } else { 64 };
+
+        let (rs1_w, rs2_w) = (rs1 as u32, rs2 as u32);
+        let sext32 = |x: u32| x as i32 as u64;
+
+        let result = match CallCryptoIdx::from_u64(call_idx) {
+            CallCryptoIdx::AES64ES   => aes_subbytes(aes_shiftrows(rs1, rs2, false), &AES_SBOX),
+            CallCryptoIdx::AES64ESM  => aes_mixcolumns(aes_subbytes(aes_shiftrows(rs1, rs2, false), &AES_SBOX), false),
+            CallCryptoIdx::AES64DS   => aes_subbytes(aes_shiftrows(rs1, rs2, true), &AES_INV_SBOX),
+            CallCryptoIdx::AES64DSM  => aes_mixcolumns(aes_subbytes(aes_shiftrows(rs1, rs2, true), &AES_INV_SBOX), true),
+            CallCryptoIdx::AES64IM   => aes_mixcolumns(rs1, true),
+            CallCryptoIdx::AES64KS1I => {
+                let rnum = (inst >> 20) & 0xf;
+                let temp = (rs1 >> 32) as u32;
+                let temp = if rnum == 0xa { temp } else { temp.rotate_right(8) };
+                let word = aes_subbytes(temp as u64, &AES_SBOX) as u32 ^ aes_rcon(rnum);
+                ((word as u64) << 32) | word as u64
+            }
+            CallCryptoIdx::AES64KS2  => {
+                let w0 = ((rs1 >> 32) as u32) ^ rs2_w;
+                let w1 = w0 ^ ((rs2 >> 32) as u32);
+                ((w1 as u64) << 32) | w0 as u64
+            }
+            CallCryptoIdx::AES32ESI  => aes32(rs1, rs2, bs, false, false),
+            CallCryptoIdx::AES32ESMI => aes32(rs1, rs2, bs, false, true),
+            CallCryptoIdx::AES32DSI  => aes32(rs1, rs2, bs, true, false),
+            CallCryptoIdx::AES32DSMI => aes32(rs1, rs2, bs, true, true),
+
+            CallCryptoIdx::SHA256SIG0 => sext32(rs1_w.rotate_right(7) ^ rs1_w.rotate_right(18) ^ (rs1_w >> 3)),
+            CallCryptoIdx::SHA256SIG1 => sext32(rs1_w.rotate_right(17) ^ rs1_w.rotate_right(19) ^ (rs1_w >> 10)),
+            CallCryptoIdx::SHA256SUM0 => sext32(rs1_w.rotate_right(2) ^ rs1_w.rotate_right(13) ^ rs1_w.rotate_right(22)),
+            CallCryptoIdx::SHA256SUM1 => sext32(rs1_w.rotate_right(6) ^ rs1_w.rotate_right(11) ^ rs1_w.rotate_right(25)),
+            CallCryptoIdx::SHA512SIG0 => rs1.rotate_right(1) ^ rs1.rotate_right(8) ^ (rs1 >> 7),
+            CallCryptoIdx::SHA512SIG1 => rs1.rotate_right(19) ^ rs1.rotate_right(61) ^ (rs1 >> 6),
+            CallCryptoIdx::SHA512SUM0 => rs1.rotate_right(28) ^ rs1.rotate_right(34) ^ rs1.rotate_right(39),
+            CallCryptoIdx::SHA512SUM1 => rs1.rotate_right(14) ^ rs1.rotate_right(18) ^ rs1.rotate_right(41),
+            // RV32 : rs1 and rs2 hold the two halves of a 64-bit operand
+            CallCryptoIdx::SHA512SIG0H => sext32((rs1_w >> 1) ^ (rs1_w >> 7) ^ (rs1_w >> 8) ^ (rs2_w << 31) ^ (rs2_w << 24)),
+            CallCryptoIdx::SHA512SIG0L => sext32((rs1_w >> 1) ^ (rs1_w >> 7) ^ (rs1_w >> 8) ^ (rs2_w << 31) ^ (rs2_w << 25) ^ (rs2_w << 24)),
+            CallCryptoIdx::SHA512SIG1H => sext32((rs1_w << 3) ^ (rs1_w >> 6) ^ (rs1_w >> 19) ^ (rs2_w >> 29) ^ (rs2_w << 13)),
+            CallCryptoIdx::SHA512SIG1L => sext32((rs1_w << 3) ^ (rs1_w >> 6) ^ (rs1_w >> 19) ^ (rs2_w >> 29) ^ (rs2_w << 26) ^ (rs2_w << 13)),
+            CallCryptoIdx::SHA512SUM0R => sext32((rs1_w << 25) ^ (rs1_w << 30) ^ (rs1_w >> 28) ^ (rs2_w >> 7) ^ (rs2_w >> 2) ^ (rs2_w << 4)),
+            CallCryptoIdx::SHA512SUM1R => sext32((rs1_w << 23) ^ (rs1_w >> 14) ^ (rs1_w >> 18) ^ (rs2_w >> 9) ^ (rs2_w << 18) ^ (rs2_w << 14)),
+
+            CallCryptoIdx::SM3P0 => sext32(rs1_w ^ rs1_w.rotate_left(9) ^ rs1_w.rotate_left(17)),
+            CallCryptoIdx::SM3P1 => sext32(rs1_w ^ rs1_w.rotate_left(15) ^ rs1_w.rotate_left(23)),
+            CallCryptoIdx::SM4ED => sm4(rs1, rs2, bs, false),
+            CallCryptoIdx::SM4KS => sm4(rs1, rs2, bs, true),
+
+            CallCryptoIdx::CLMUL  => if xlen == 32 { clmul(rs1_w as u64, rs2_w as u64) as u64 } else { clmul(rs1, rs2) as u64 },
+            CallCryptoIdx::CLMULH => if xlen == 32 { (clmul(rs1_w as u64, rs2_w as u64) >> 32) as u64 } else { (clmul(rs1, rs2) >> 64) as u64 },
+            CallCryptoIdx::XPERM4 => xperm(rs1, rs2, 4, xlen),
+            CallCryptoIdx::XPERM8 => xperm(rs1, rs2, 8, xlen),
+
+            CallCryptoIdx::PACK   => if xlen == 32 { ((rs2 & 0xffff) << 16) | (rs1 & 0xffff) } else { (rs2 << 32) | (rs1 & 0xffff_ffff) },
+            CallCryptoIdx::PACKH  => ((rs2 & 0xff) << 8) | (rs1 & 0xff),
+            CallCryptoIdx::PACKW  => sext32((((rs2 & 0xffff) << 16) | (rs1 & 0xffff)) as u32),
+            CallCryptoIdx::BREV8  => {
+                let mut bytes = rs1.to_le_bytes();
+                for b in bytes.iter_mut() {
+                    *b = b.reverse_bits();
+                }
+                u64::from_le_bytes(bytes)
+            }
+            CallCryptoIdx::ZIP    => zip32(rs1_w) as u64,
+            CallCryptoIdx::UNZIP  => unzip32(rs1_w) as u64,
+        };
+        if rd != 0 {
+            emu.m_iregs[rd as usize] = emu.sext_xlen(result);
+        }
+        return 0;
+    }
+}
@@ -1,38 +1,54 @@
-use softfloat_wrapper::{ExceptionFlags, Float, F64, F32, RoundingMode};
+use softfloat_wrapper::{ExceptionFlags, Float, F64, F32, F16};
 use crate::target::riscv::riscv_csr::{CsrAddr};
 use crate::target::riscv::riscv::CallFcvtIdx;
 use crate::emu_env::EmuEnv;
 
 impl EmuEnv {
-    pub fn helper_func_fcvt(emu: &mut EmuEnv, call_idx: u64, rd: u64, rs1: u64, _: u64) -> usize {
+    pub fn helper_func_fcvt(emu: &mut EmuEnv, call_idx: u64, inst: u64, _: u64, guest_pc: u64) -> usize {
+        let (rd, rs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let mut flag = ExceptionFlags::default();
         flag.set();
         let helper_idx = CallFcvtIdx::from_u64(call_idx);
         match helper_idx {
-            CallFcvtIdx::W_S  => { let to_data = F32::from_bits(emu.m_fregs[rs1 as usize] as u32).to_i32(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::WU_S => { let to_data = F32::from_bits(emu.m_fregs[rs1 as usize] as u32).to_u32(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as i32 as u64; },
-            CallFcvtIdx::S_W  => { let to_data = F32::from_i32 (emu.m_iregs[rs1 as usize] as i32, RoundingMode::TowardZero)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::S_WU => { let to_data = F32::from_u32 (emu.m_iregs[rs1 as usize] as u32, RoundingMode::TiesToEven)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::S_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_f32(RoundingMode::TowardZero); emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::D_S  => { let to_data = F32::from_bits(emu.m_fregs[rs1 as usize] as u32).to_f64(RoundingMode::TowardZero); emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::W_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_i32(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::WU_D => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_u32(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as i32 as u64; },
-            CallFcvtIdx::D_W  => { let to_data = F64::from_i32 (emu.m_iregs[rs1 as usize] as i32, RoundingMode::TowardZero)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::D_WU => { let to_data = F64::from_u32 (emu.m_iregs[rs1 as usize] as u32, RoundingMode::TowardZero)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::L_S  => { let to_data = F32::from_bits(emu.m_fregs[rs1 as usize] as u32).to_i64(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::LU_S => { let to_data = F32::from_bits(emu.m_fregs[rs1 as usize] as u32).to_u64(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::S_L  => { let to_data = F32::from_i64 (emu.m_iregs[rs1 as usize] as i64, RoundingMode::TowardZero)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::S_LU => { let to_data = F32::from_u64 (emu.m_iregs[rs1 as usize] as u64, RoundingMode::TiesToEven)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::L_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_i64(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::LU_D => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_u64(RoundingMode::TowardZero, true); emu.m_iregs[rd as usize] = to_data        as u64; },
-            CallFcvtIdx::D_L  => { let to_data = F64::from_i64 (emu.m_iregs[rs1 as usize] as i64, RoundingMode::TowardZero)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
-            CallFcvtIdx::D_LU => { let to_data = F64::from_u64 (emu.m_iregs[rs1 as usize] as u64, RoundingMode::TiesToEven)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::W_S  => { let to_data = Self::read_freg_s(emu, rs1).to_i32(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::WU_S => { let to_data = Self::read_freg_s(emu, rs1).to_u32(rm, true); emu.m_iregs[rd as usize] = to_data        as i32 as u64; },
+            CallFcvtIdx::S_W  => { let to_data = F32::from_i32 (emu.m_iregs[rs1 as usize] as i32, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::S_WU => { let to_data = F32::from_u32 (emu.m_iregs[rs1 as usize] as u32, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::S_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_f32(rm); emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::D_S  => { let to_data = Self::read_freg_s(emu, rs1).to_f64(rm); emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::W_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_i32(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::WU_D => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_u32(rm, true); emu.m_iregs[rd as usize] = to_data        as i32 as u64; },
+            CallFcvtIdx::D_W  => { let to_data = F64::from_i32 (emu.m_iregs[rs1 as usize] as i32, rm)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::D_WU => { let to_data = F64::from_u32 (emu.m_iregs[rs1 as usize] as u32, rm)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::L_S  => { let to_data = Self::read_freg_s(emu, rs1).to_i64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::LU_S => { let to_data = Self::read_freg_s(emu, rs1).to_u64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::S_L  => { let to_data = F32::from_i64 (emu.m_iregs[rs1 as usize] as i64, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::S_LU => { let to_data = F32::from_u64 (emu.m_iregs[rs1 as usize] as u64, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::L_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_i64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::LU_D => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_u64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::D_L  => { let to_data = F64::from_i64 (emu.m_iregs[rs1 as usize] as i64, rm)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::D_LU => { let to_data = F64::from_u64 (emu.m_iregs[rs1 as usize] as u64, rm)     ; emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::H_S  => { let to_data = Self::read_freg_s(emu, rs1).to_f16(rm); emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
+            CallFcvtIdx::S_H  => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_f32(rm); emu.m_fregs[rd as usize] = Self::nan_boxing_s(to_data.bits()); },
+            CallFcvtIdx::H_D  => { let to_data = F64::from_bits(emu.m_fregs[rs1 as usize] as u64).to_f16(rm); emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
+            CallFcvtIdx::D_H  => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_f64(rm); emu.m_fregs[rd as usize] = to_data.bits() as u64; },
+            CallFcvtIdx::W_H  => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_i32(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::WU_H => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_u32(rm, true); emu.m_iregs[rd as usize] = to_data        as i32 as u64; },
+            CallFcvtIdx::H_W  => { let to_data = F16::from_i32 (emu.m_iregs[rs1 as usize] as i32, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
+            CallFcvtIdx::H_WU => { let to_data = F16::from_u32 (emu.m_iregs[rs1 as usize] as u32, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
+            CallFcvtIdx::L_H  => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_i64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::LU_H => { let to_data = F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[rs1 as usize])).to_u64(rm, true); emu.m_iregs[rd as usize] = to_data        as u64; },
+            CallFcvtIdx::H_L  => { let to_data = F16::from_i64 (emu.m_iregs[rs1 as usize] as i64, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
+            CallFcvtIdx::H_LU => { let to_data = F16::from_u64 (emu.m_iregs[rs1 as usize] as u64, rm)     ; emu.m_fregs[rd as usize] = Self::nan_boxing_h(to_data.bits()); },
         };
 
         flag.get();
         let ret_flag = flag.bits();
-        println!("ret_flags = {:x}", ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
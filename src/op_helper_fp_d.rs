@@ -1,178 +1,198 @@
-use softfloat_wrapper::{ExceptionFlags, Float, RoundingMode, F64};
+use softfloat_wrapper::{ExceptionFlags, Float, F64};
 use crate::target::riscv::riscv_csr::{CsrAddr};
 use crate::emu_env::EmuEnv;
 
 impl EmuEnv {
-    pub fn helper_func_fadd_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+    pub fn helper_func_fadd_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.add(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.add(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fsub_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+    pub fn helper_func_fsub_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.sub(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.sub(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
-        println!(
-            "fsub({:?}, {:?}, {:?}) is called!",
-            fs1_data, fs2_data, fd_data
-        );
         return 0;
     }
 
-    pub fn helper_func_fmul_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+    pub fn helper_func_fmul_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.mul(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.mul(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
-
-        println!("fmul(emu, {:}, {:}, 0x{:03x}) is called!", fd, fs1, fs2);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fdiv_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+    pub fn helper_func_fdiv_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.div(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.div(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
-
-        println!("fdiv(emu, {:}, {:}, 0x{:03x}) is called!", fd, fs1, fs2);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fmadd_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fmadd(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
+    pub fn helper_func_fmadd_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let fs3_data = F64::from_bits(emu.m_fregs[fs3 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
-            .add(fs3_data, RoundingMode::TiesToEven);
+            .mul(fs2_data, rm)
+            .add(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fmsub_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fmsub(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
+    pub fn helper_func_fmsub_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let fs3_data = F64::from_bits(emu.m_fregs[fs3 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
-            .sub(fs3_data, RoundingMode::TiesToEven);
+            .mul(fs2_data, rm)
+            .sub(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fnmsub_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fnmsub(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
+    pub fn helper_func_fnmsub_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let fs3_data = F64::from_bits(emu.m_fregs[fs3 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
+            .mul(fs2_data, rm)
             .neg()
-            .add(fs3_data, RoundingMode::TiesToEven);
+            .add(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fnmadd_d(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fnmadd(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
+    pub fn helper_func_fnmadd_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let fs3_data = F64::from_bits(emu.m_fregs[fs3 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
+            .mul(fs2_data, rm)
             .neg()
-            .sub(fs3_data, RoundingMode::TiesToEven);
+            .sub(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fsqrt_d(emu: &mut EmuEnv, fd: u64, fs1: u64, _: u64, _: u64) -> usize {
-        println!("fsqrt(emu, {:}, {:}) is called!", fd, fs1);
+    pub fn helper_func_fsqrt_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
 
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.sqrt(RoundingMode::TiesToEven);
+        let fd_data = fs1_data.sqrt(rm);
         flag.get();
         let ret_flag = flag.bits();
 
         emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
@@ -185,8 +205,7 @@ impl EmuEnv {
         emu.m_iregs[rd as usize] = fs1_data.eq(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        println!("feq(emu, {:}, {:}, {:}) => {:} is called!", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
@@ -198,13 +217,11 @@ impl EmuEnv {
         emu.m_iregs[rd as usize] = fs1_data.lt(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        println!("flt(emu, {:}, {:}, {:}) is called! => {:}", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_fle_d(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        println!("fle(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
         let mut flag = ExceptionFlags::default();
@@ -212,12 +229,11 @@ impl EmuEnv {
         emu.m_iregs[rd as usize] = fs1_data.le(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_fclass_d(emu: &mut EmuEnv, rd: u64, fs1: u64, _fs2: u64, _: u64) -> usize {
-        println!("fclass(emu, {:}, {:}) is called!", rd, fs1);
         let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
         #[allow(unused_assignments)]
         let mut result = 0;
@@ -264,8 +280,7 @@ impl EmuEnv {
         };
         flag.get();
         let ret_flag = flag.bits();
-        println!("fmax_d(emu, {:}, {:}, {:}) is called! => {:}", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
@@ -284,8 +299,172 @@ impl EmuEnv {
         };
         flag.get();
         let ret_flag = flag.bits();
-        println!("fmin_d(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    /* Zfa */
+
+    // fli constants, indexed by rs1. Entry 1 is the minimum positive normal
+    // and entry 31 the canonical NaN, which depend on the format.
+    pub const FLI_TABLE: [f64; 32] = [
+        -1.0, 0.0, 1.52587890625e-05, 3.0517578125e-05,
+        0.00390625, 0.0078125, 0.0625, 0.125,
+        0.25, 0.3125, 0.375, 0.4375,
+        0.5, 0.625, 0.75, 0.875,
+        1.0, 1.25, 1.5, 1.75,
+        2.0, 2.5, 3.0, 4.0,
+        8.0, 16.0, 128.0, 256.0,
+        32768.0, 65536.0, f64::INFINITY, 0.0,
+    ];
+
+    pub fn helper_func_fli_d(emu: &mut EmuEnv, rd: u64, idx: u64, _: u64, _: u64) -> usize {
+        emu.m_fregs[rd as usize] = match idx {
+            1 => 0x0010_0000_0000_0000,
+            31 => F64::quiet_nan().bits() as u64,
+            _ => Self::FLI_TABLE[idx as usize].to_bits(),
+        };
+        return 0;
+    }
+
+    // fminm/fmaxm return the canonical NaN if either input is NaN.
+    pub fn helper_func_fmaxm_d(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
+        let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_fregs[rd as usize] = 
+        if fs1_data.is_nan() || fs2_data.is_nan() { 
+            let _ = fs1_data.lt_quiet(fs2_data);  // NV for a signaling NaN
+            F64::quiet_nan().bits() as u64
+        } else if fs2_data.lt_quiet(fs1_data) || fs1_data.eq(fs2_data) && fs2_data.is_negative() {
+            fs1_data.bits() as u64
+        } else {
+            fs2_data.bits() as u64
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fminm_d(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
+        let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_fregs[rd as usize] = 
+        if fs1_data.is_nan() || fs2_data.is_nan() { 
+            let _ = fs1_data.lt_quiet(fs2_data);  // NV for a signaling NaN
+            F64::quiet_nan().bits() as u64
+        } else if fs1_data.lt_quiet(fs2_data) || fs1_data.eq(fs2_data) && fs1_data.is_negative() {
+            fs1_data.bits() as u64
+        } else {
+            fs2_data.bits() as u64
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fround_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::fround_d(emu, inst, guest_pc, false)
+    }
+
+    pub fn helper_func_froundnx_d(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::fround_d(emu, inst, guest_pc, true)
+    }
+
+    // froundnx also raises NX when the result differs from a non-NaN input.
+    fn fround_d(emu: &mut EmuEnv, inst: u64, guest_pc: u64, exact: bool) -> usize {
+        let (fd, fs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.round_to_integral(rm);
+        flag.get();
+        let mut ret_flag = flag.bits();
+        if exact && !fs1_data.is_nan() && fd_data.bits() != fs1_data.bits() {
+            ret_flag |= 0x1;
+        }
+
+        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fleq_d(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
+        let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.le_quiet(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fltq_d(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = F64::from_bits(emu.m_fregs[fs1 as usize]);
+        let fs2_data = F64::from_bits(emu.m_fregs[fs2 as usize]);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.lt_quiet(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    // Truncate to an integer and keep its low 32 bits. NV if the value is
+    // NaN, infinite or out of the int32 range, otherwise NX if inexact.
+    pub fn helper_func_fcvtmod_w_d(emu: &mut EmuEnv, rd: u64, fs1: u64, _: u64, _: u64) -> usize {
+        let bits = emu.m_fregs[fs1 as usize];
+        let sign = (bits >> 63) != 0;
+        let exp = ((bits >> 52) & 0x7ff) as i64;
+        let mant = (bits & 0x000f_ffff_ffff_ffff) | 0x0010_0000_0000_0000;
+
+        let (result, invalid, inexact) = if exp == 0x7ff {
+            (0, true, false)
+        } else if exp < 1023 {
+            (0, false, (bits & 0x7fff_ffff_ffff_ffff) != 0)
+        } else {
+            let shift = exp - 1075;
+            let (int_part, inexact) = if shift >= 64 {
+                (0, false)
+            } else if shift >= 0 {
+                (mant << shift, false)
+            } else {
+                (mant >> -shift, (mant & ((1 << -shift) - 1)) != 0)
+            };
+            let int_part = int_part as u32;
+            let result = if sign { int_part.wrapping_neg() } else { int_part };
+            // |x| >= 2^31, except for exactly -2^31
+            let invalid = exp - 1023 >= 31 && !(sign && exp - 1023 == 31 && mant == 0x0010_0000_0000_0000);
+            (result, invalid, inexact)
+        };
+
+        let ret_flag = if invalid { 0x10 } else if inexact { 0x1 } else { 0 };
+        emu.m_iregs[rd as usize] = result as i32 as i64 as u64;
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    // RV32 : upper half of a double, and a double from a pair of registers
+    pub fn helper_func_fmvh_x_d(emu: &mut EmuEnv, rd: u64, fs1: u64, _: u64, _: u64) -> usize {
+        emu.m_iregs[rd as usize] = (emu.m_fregs[fs1 as usize] >> 32) as i32 as i64 as u64;
+        return 0;
+    }
+
+    pub fn helper_func_fmvp_d_x(emu: &mut EmuEnv, rd: u64, rs1: u64, rs2: u64, _: u64) -> usize {
+        emu.m_fregs[rd as usize] = (emu.m_iregs[rs2 as usize] << 32) | (emu.m_iregs[rs1 as usize] & 0xffff_ffff);
         return 0;
     }
 }
@@ -0,0 +1,343 @@
+use softfloat_wrapper::{ExceptionFlags, Float, F16};
+use crate::target::riscv::riscv_csr::{CsrAddr};
+use crate::emu_env::EmuEnv;
+
+impl EmuEnv {
+    #[inline]
+    pub fn convert_nan_boxing_h (i: u64) -> u16 {
+        if i & 0xffffffff_ffff0000 == 0xffffffff_ffff0000 {
+            (i & 0xffff) as u16
+        } else {
+            0x7e00
+        }
+    }
+
+    #[inline]
+    pub fn nan_boxing_h (h: u16) -> u64 {
+        h as u64 | 0xffffffff_ffff0000
+    }
+
+    #[inline]
+    fn read_freg_h (emu: &EmuEnv, fs: u64) -> F16 {
+        F16::from_bits(Self::convert_nan_boxing_h(emu.m_fregs[fs as usize]))
+    }
+
+    pub fn helper_func_fadd_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.add(fs2_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fsub_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.sub(fs2_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fmul_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.mul(fs2_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fdiv_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.div(fs2_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fmadd_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let fs3_data = Self::read_freg_h(emu, fs3);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.fused_mul_add(fs2_data, fs3_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fmsub_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let fs3_data = Self::read_freg_h(emu, fs3);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.fused_mul_add(fs2_data, fs3_data.neg(), rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fnmsub_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let fs3_data = Self::read_freg_h(emu, fs3);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.neg().fused_mul_add(fs2_data, fs3_data, rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fnmadd_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let fs3_data = Self::read_freg_h(emu, fs3);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.neg().fused_mul_add(fs2_data, fs3_data.neg(), rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fsqrt_h(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.sqrt(rm);
+        flag.get();
+        let ret_flag = flag.bits();
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_feq_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.eq(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_flt_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.lt(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fle_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.le(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fclass_h(emu: &mut EmuEnv, rd: u64, fs1: u64, _fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let result = if fs1_data.is_negative_infinity() {
+            1 << 0
+        } else if fs1_data.is_positive_infinity() {
+            1 << 7
+        } else if fs1_data.is_negative_zero() {
+            1 << 3
+        } else if fs1_data.is_positive_zero() {
+            1 << 4
+        } else if fs1_data.is_negative_subnormal() {
+            1 << 2
+        } else if fs1_data.is_positive_subnormal() {
+            1 << 5
+        } else if fs1_data.is_nan() {
+            if (fs1_data.fraction() & (1 << (F16::EXPONENT_POS - 1))) != 0 {
+                1 << 9
+            } else {
+                1 << 8
+            }
+        } else if fs1_data.is_negative() {
+            1 << 1
+        } else {
+            1 << 6
+        };
+        emu.m_iregs[rd as usize] = result as u64;
+        return 0;
+    }
+
+    pub fn helper_func_fmax_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data =
+        if fs1_data.is_nan() && fs2_data.is_nan() {
+            F16::quiet_nan()
+        } else if fs2_data.lt_quiet(fs1_data) || fs2_data.is_nan() || fs1_data.eq(fs2_data) && fs2_data.is_negative() {
+            fs1_data
+        } else {
+            fs2_data
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_fregs[rd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fmin_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_h(emu, fs1);
+        let fs2_data = Self::read_freg_h(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data =
+        if fs1_data.is_nan() && fs2_data.is_nan() {
+            F16::quiet_nan()
+        } else if fs1_data.lt_quiet(fs2_data) || fs2_data.is_nan() || fs1_data.eq(fs2_data) && fs1_data.is_negative() {
+            fs1_data
+        } else {
+            fs2_data
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_fregs[rd as usize] = Self::nan_boxing_h(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fsgnj_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::convert_nan_boxing_h(emu.m_fregs[fs1 as usize]);
+        let fs2_data = Self::convert_nan_boxing_h(emu.m_fregs[fs2 as usize]);
+        emu.m_fregs[rd as usize] = Self::nan_boxing_h(fs1_data & 0x7fff | fs2_data & 0x8000);
+        return 0;
+    }
+
+    pub fn helper_func_fsgnjn_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::convert_nan_boxing_h(emu.m_fregs[fs1 as usize]);
+        let fs2_data = Self::convert_nan_boxing_h(emu.m_fregs[fs2 as usize]);
+        emu.m_fregs[rd as usize] = Self::nan_boxing_h(fs1_data & 0x7fff | !fs2_data & 0x8000);
+        return 0;
+    }
+
+    pub fn helper_func_fsgnjx_h(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::convert_nan_boxing_h(emu.m_fregs[fs1 as usize]);
+        let fs2_data = Self::convert_nan_boxing_h(emu.m_fregs[fs2 as usize]);
+        emu.m_fregs[rd as usize] = Self::nan_boxing_h(fs1_data & 0x7fff | (fs1_data ^ fs2_data) & 0x8000);
+        return 0;
+    }
+
+    pub fn helper_func_fmv_x_h(emu: &mut EmuEnv, rd: u64, fs1: u64, _: u64, _: u64) -> usize {
+        emu.m_iregs[rd as usize] = emu.m_fregs[fs1 as usize] as u16 as i16 as i64 as u64;
+        return 0;
+    }
+
+    pub fn helper_func_fmv_h_x(emu: &mut EmuEnv, fd: u64, rs1: u64, _: u64, _: u64) -> usize {
+        emu.m_fregs[fd as usize] = Self::nan_boxing_h(emu.m_iregs[rs1 as usize] as u16);
+        return 0;
+    }
+}
@@ -1,10 +1,10 @@
-use softfloat_wrapper::{ExceptionFlags, Float, RoundingMode, F32};
+use softfloat_wrapper::{ExceptionFlags, Float, F32};
 use crate::target::riscv::riscv_csr::{CsrAddr};
 use crate::emu_env::EmuEnv;
 
 impl EmuEnv {
     #[inline]
-    fn convert_nan_boxing (i: u64) -> u32 {
+    pub fn convert_nan_boxing (i: u64) -> u32 {
         if i & 0xffffffff_00000000 == 0xffffffff_00000000 {
             (i & 0xffffffff) as u32
         } else {
@@ -12,223 +12,248 @@ impl EmuEnv {
         }
     }
 
-    pub fn helper_func_fadd_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        println!("fadd(emu, {:}, {:}, {:}) is called!", fd, fs1, fs2);
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+    #[inline]
+    pub fn nan_boxing_s (f: u32) -> u64 {
+        f as u64 | 0xffffffff_00000000
+    }
+
+    #[inline]
+    pub fn read_freg_s (emu: &EmuEnv, fs: u64) -> F32 {
+        F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs as usize]))
+    }
+
+    pub fn helper_func_fadd_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.add(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.add(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fsub_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+    pub fn helper_func_fsub_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.sub(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.sub(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
-        println!(
-            "fsub({:?}, {:?}, {:?}) is called!",
-            fs1_data, fs2_data, fd_data
-        );
         return 0;
     }
 
-    pub fn helper_func_fmul_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+    pub fn helper_func_fmul_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.mul(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.mul(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
-
-        println!("fmul(emu, {:}, {:}, 0x{:03x}) is called!", fd, fs1, fs2);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fdiv_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+    pub fn helper_func_fdiv_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.div(fs2_data, RoundingMode::TiesToEven);
+        let fd_data = fs1_data.div(fs2_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
-
-        println!("fdiv(emu, {:}, {:}, 0x{:03x}) is called!", fd, fs1, fs2);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fmadd_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fmadd(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
-        let fs3_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs3 as usize]) as u32);
+    pub fn helper_func_fmadd_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let fs3_data = Self::read_freg_s(emu, fs3);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
-            .add(fs3_data, RoundingMode::TiesToEven);
+            .mul(fs2_data, rm)
+            .add(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fmsub_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fmsub(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
-        let fs3_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs3 as usize]) as u32);
+    pub fn helper_func_fmsub_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let fs3_data = Self::read_freg_s(emu, fs3);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
-            .sub(fs3_data, RoundingMode::TiesToEven);
+            .mul(fs2_data, rm)
+            .sub(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fnmsub_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fnmsub(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
-        let fs3_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs3 as usize]) as u32);
+    pub fn helper_func_fnmsub_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let fs3_data = Self::read_freg_s(emu, fs3);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
+            .mul(fs2_data, rm)
             .neg()
-            .add(fs3_data, RoundingMode::TiesToEven);
+            .add(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fnmadd_s(emu: &mut EmuEnv, fd: u64, fs1: u64, fs2: u64, fs3: u64) -> usize {
-        println!(
-            "fnmadd(emu, {:}, {:}, {:}, {:}) is called!",
-            fd, fs1, fs2, fs3
-        );
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
-        let fs3_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs3 as usize]) as u32);
+    pub fn helper_func_fnmadd_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, fs2, fs3) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let fs3_data = Self::read_freg_s(emu, fs3);
         let mut flag = ExceptionFlags::default();
         flag.set();
         let fd_data = fs1_data
-            .mul(fs2_data, RoundingMode::TiesToEven)
+            .mul(fs2_data, rm)
             .neg()
-            .sub(fs3_data, RoundingMode::TiesToEven);
+            .sub(fs3_data, rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
-    pub fn helper_func_fsqrt_s(emu: &mut EmuEnv, fd: u64, fs1: u64, _: u64, _: u64) -> usize {
-        println!("fsqrt(emu, {:}, {:}) is called!", fd, fs1);
+    pub fn helper_func_fsqrt_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let (fd, fs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
 
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
         let mut flag = ExceptionFlags::default();
         flag.set();
-        let fd_data = fs1_data.sqrt(RoundingMode::TiesToEven);
+        let fd_data = fs1_data.sqrt(rm);
         flag.get();
         let ret_flag = flag.bits();
 
-        emu.m_fregs[fd as usize] = fd_data.bits() as u64;
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
 
         return 0;
     }
 
     pub fn helper_func_feq_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
         emu.m_iregs[rd as usize] = fs1_data.eq(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        println!("feq(emu, {:}, {:}, {:}) => {:} is called!", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_flt_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
         emu.m_iregs[rd as usize] = fs1_data.lt(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        println!("flt(emu, {:}, {:}, {:}) is called! => {:}", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_fle_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        println!("fle(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        let fs1_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs1 as usize]) as u32);
-        let fs2_data = F32::from_bits(Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
         emu.m_iregs[rd as usize] = fs1_data.le(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_fclass_s(emu: &mut EmuEnv, rd: u64, fs1: u64, _fs2: u64, _: u64) -> usize {
-        println!("fclass_s(emu, {:}, {:}) is called!", rd, fs1);
-        let fs1_data = F32::from_bits((emu.m_fregs[fs1 as usize] & 0x0ffffffff) as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
         #[allow(unused_assignments)]
         let mut result = 0;
         if fs1_data.is_negative_infinity() {
@@ -260,42 +285,40 @@ impl EmuEnv {
     }
 
     pub fn helper_func_fmax_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(emu.m_fregs[fs1 as usize] as u32);
-        let fs2_data = F32::from_bits(emu.m_fregs[fs2 as usize] as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
         emu.m_fregs[rd as usize] = 
         if fs1_data.is_nan() && fs2_data.is_nan() { 
-            F32::quiet_nan().bits() as u64
+            Self::nan_boxing_s(F32::quiet_nan().bits())
         } else if fs2_data.lt_quiet(fs1_data) || fs2_data.is_nan() || fs1_data.eq(fs2_data) && fs2_data.is_negative() {
-            fs1_data.bits() as u64
+            Self::nan_boxing_s(fs1_data.bits())
         } else {
-            fs2_data.bits() as u64
+            Self::nan_boxing_s(fs2_data.bits())
         };
         flag.get();
         let ret_flag = flag.bits();
-        println!("fmax_d(emu, {:}, {:}, {:}) is called! => {:}", rd, fs1, fs2, ret_flag);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
     pub fn helper_func_fmin_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
-        let fs1_data = F32::from_bits(emu.m_fregs[fs1 as usize] as u32);
-        let fs2_data = F32::from_bits(emu.m_fregs[fs2 as usize] as u32);
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
         let mut flag = ExceptionFlags::default();
         flag.set();
         emu.m_fregs[rd as usize] = 
         if fs1_data.is_nan() && fs2_data.is_nan() { 
-            F32::quiet_nan().bits() as u64
+            Self::nan_boxing_s(F32::quiet_nan().bits())
         } else if fs1_data.lt_quiet(fs2_data) || fs2_data.is_nan() || fs1_data.eq(fs2_data) && fs1_data.is_negative() {
-            fs1_data.bits() as u64
+            Self::nan_boxing_s(fs1_data.bits())
         } else {
-            fs2_data.bits() as u64
+            Self::nan_boxing_s(fs2_data.bits())
         };
         flag.get();
         let ret_flag = flag.bits();
-        println!("fmin_d(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
@@ -304,11 +327,10 @@ impl EmuEnv {
         let fs2_data = Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32;
         let mut flag = ExceptionFlags::default();
         flag.set();
-        emu.m_fregs[rd as usize] = (fs1_data & 0x7fffffff | fs2_data & 0x80000000) as u64 | 0xffffffff_00000000;
+        emu.m_fregs[rd as usize] = Self::nan_boxing_s(fs1_data & 0x7fffffff | fs2_data & 0x80000000);
         flag.get();
         let ret_flag = flag.bits();
-        println!("fsgnj_s(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
@@ -317,11 +339,10 @@ impl EmuEnv {
         let fs2_data = Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32;
         let mut flag = ExceptionFlags::default();
         flag.set();
-        emu.m_fregs[rd as usize] = (fs1_data & 0x7fffffff | !fs2_data & 0x80000000) as u64 | 0xffffffff_00000000;
+        emu.m_fregs[rd as usize] = Self::nan_boxing_s(fs1_data & 0x7fffffff | !fs2_data & 0x80000000);
         flag.get();
         let ret_flag = flag.bits();
-        println!("fsgnjn_s(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 
@@ -331,11 +352,118 @@ impl EmuEnv {
         let fs2_data = Self::convert_nan_boxing(emu.m_fregs[fs2 as usize]) as u32;
         let mut flag = ExceptionFlags::default();
         flag.set();
-        emu.m_fregs[rd as usize] = (fs1_data & 0x7fffffff | (fs1_data ^ fs2_data) & 0x80000000) as u64 | 0xffffffff_00000000;
+        emu.m_fregs[rd as usize] = Self::nan_boxing_s(fs1_data & 0x7fffffff | (fs1_data ^ fs2_data) & 0x80000000);
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    /* Zfa */
+
+    pub fn helper_func_fli_s(emu: &mut EmuEnv, rd: u64, idx: u64, _: u64, _: u64) -> usize {
+        let fd_data = match idx {
+            1 => 0x0080_0000,
+            31 => F32::quiet_nan().bits(),
+            _ => (Self::FLI_TABLE[idx as usize] as f32).to_bits(),
+        };
+        emu.m_fregs[rd as usize] = Self::nan_boxing_s(fd_data);
+        return 0;
+    }
+
+    // fminm/fmaxm return the canonical NaN if either input is NaN.
+    pub fn helper_func_fmaxm_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_fregs[rd as usize] = 
+        if fs1_data.is_nan() || fs2_data.is_nan() { 
+            let _ = fs1_data.lt_quiet(fs2_data);  // NV for a signaling NaN
+            Self::nan_boxing_s(F32::quiet_nan().bits())
+        } else if fs2_data.lt_quiet(fs1_data) || fs1_data.eq(fs2_data) && fs2_data.is_negative() {
+            Self::nan_boxing_s(fs1_data.bits())
+        } else {
+            Self::nan_boxing_s(fs2_data.bits())
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fminm_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_fregs[rd as usize] = 
+        if fs1_data.is_nan() || fs2_data.is_nan() { 
+            let _ = fs1_data.lt_quiet(fs2_data);  // NV for a signaling NaN
+            Self::nan_boxing_s(F32::quiet_nan().bits())
+        } else if fs1_data.lt_quiet(fs2_data) || fs1_data.eq(fs2_data) && fs1_data.is_negative() {
+            Self::nan_boxing_s(fs1_data.bits())
+        } else {
+            Self::nan_boxing_s(fs2_data.bits())
+        };
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fround_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::fround_s(emu, inst, guest_pc, false)
+    }
+
+    pub fn helper_func_froundnx_s(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::fround_s(emu, inst, guest_pc, true)
+    }
+
+    // froundnx also raises NX when the result differs from a non-NaN input.
+    fn fround_s(emu: &mut EmuEnv, inst: u64, guest_pc: u64, exact: bool) -> usize {
+        let (fd, fs1, _, _) = Self::fp_operands(inst);
+        let rm = match emu.get_rounding_mode(inst, guest_pc) {
+            Some(rm) => rm,
+            None => return 1,
+        };
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let fd_data = fs1_data.round_to_integral(rm);
+        flag.get();
+        let mut ret_flag = flag.bits();
+        if exact && !fs1_data.is_nan() && fd_data.bits() != fs1_data.bits() {
+            ret_flag |= 0x1;
+        }
+
+        emu.m_fregs[fd as usize] = Self::nan_boxing_s(fd_data.bits());
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+
+        return 0;
+    }
+
+    pub fn helper_func_fleq_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.le_quiet(fs2_data) as u64;
+        flag.get();
+        let ret_flag = flag.bits();
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
+        return 0;
+    }
+
+    pub fn helper_func_fltq_s(emu: &mut EmuEnv, rd: u64, fs1: u64, fs2: u64, _: u64) -> usize {
+        let fs1_data = Self::read_freg_s(emu, fs1);
+        let fs2_data = Self::read_freg_s(emu, fs2);
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        emu.m_iregs[rd as usize] = fs1_data.lt_quiet(fs2_data) as u64;
         flag.get();
         let ret_flag = flag.bits();
-        println!("fsgnjx_s(emu, {:}, {:}, {:}) is called!", rd, fs1, fs2);
-        emu.m_csr.csrrw(CsrAddr::FFlags, ret_flag as i64);
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag as i64);
         return 0;
     }
 }
@@ -1,27 +1,125 @@
-use crate::emu_env::{EmuEnv, MachineEnum};
+use crate::emu_env::{EmuEnv, MachineEnum, MisalignEnum};
 use crate::target::riscv::mmu::{MemAccType, MemResult};
-use crate::target::riscv::riscv::ExceptCode;
+use crate::target::riscv::riscv::{ExceptCode, PrivMode};
+use crate::target::riscv::riscv_csr::CsrAddr;
+use crate::target::riscv::riscv_csr_def;
+use crate::target::riscv::tlb::{tlb_slot, TLB_SIZE};
 
 impl EmuEnv {
+    pub(crate) fn is_misaligned(addr: u64, size: u64) -> bool {
+        return addr & (size - 1) != 0;
+    }
+
+    // Load or store page fault, or access fault, for a failed data access
+    // translation, with the virtual address in tval. AMOs report as stores.
+    pub(crate) fn raise_mem_fault(&mut self, guest_pc: u64, addr: u64, is_store: bool, error: &MemResult) {
+        let code = match (error, is_store) {
+            (MemResult::AccessFault, false) => ExceptCode::LoadAccessFault,
+            (MemResult::AccessFault, true) => ExceptCode::StoreAccessFault,
+            (_, false) => ExceptCode::LoadPageFault,
+            (_, true) => ExceptCode::StorePageFault,
+        };
+        self.generate_exception(guest_pc, code, addr as i64);
+    }
+
+    // Inline TLB refill after a successful translation, in the load or store
+    // table of the current privilege. A writable page is also readable, so a
+    // store fills both. The tag holds the current ASID. A page that is only
+    // partly covered by a PMP entry is left to the helpers.
+    pub(crate) fn fill_tlb(&mut self, addr: u64, guest_phy_addr: u64, acc_type: MemAccType) {
+        if !self.m_pmp.is_page_uniform(guest_phy_addr & !0xfff) {
+            return;
+        }
+        let is_store = match acc_type {
+            MemAccType::Write => true,
+            _ => false,
+        };
+        let idx = ((addr >> 12) & 0xfff) as usize;
+        let slots = [tlb_slot(self.m_priv, false), tlb_slot(self.m_priv, is_store)];
+        let tag = (addr >> (12 + 12)) | self.m_tlb_asid_tag[0];
+        for slot in slots.iter() {
+            self.m_tlb_vec[slot * TLB_SIZE + idx] = tag;
+            self.m_tlb_addr_vec[slot * TLB_SIZE + idx] = guest_phy_addr & !0xfff;
+            if self.m_arg_config.mmu_debug {
+                println!("update tlb_vec[{:}] = {:016x}", slot * TLB_SIZE + idx, tag);
+            }
+        }
+    }
+
+    // Misaligned load : trap, or read byte by byte so that an access crossing
+    // a page boundary is translated (and faults) on each page.
+    pub(crate) fn load_misaligned(emu: &mut EmuEnv, guest_pc: u64, addr: u64, size: u64) -> Result<u64, MemResult> {
+        if emu.m_arg_config.misalign == MisalignEnum::Trap {
+            emu.generate_exception(guest_pc, ExceptCode::LoadAddrMisalign, addr as i64);
+            return Err(MemResult::MisAlign);
+        }
+        let mut data: u64 = 0;
+        for i in 0..size {
+            let byte_addr = addr.wrapping_add(i);
+            match emu.convert_physical_address(guest_pc, byte_addr, 1, MemAccType::Read) {
+                Ok(guest_phy_addr) => {
+                    data |= (emu.read_mem_1byte(guest_phy_addr) as u64) << (i * 8);
+                }
+                Err(error) => {
+                    emu.raise_mem_fault(guest_pc, byte_addr, false, &error);
+                    return Err(error);
+                }
+            }
+        }
+        if emu.m_arg_config.mmu_debug {
+            println!("load misaligned : {:016x} ({:} bytes) = {:016x}", addr, size, data);
+        }
+        return Ok(data);
+    }
+
+    // Misaligned store : all bytes are translated before anything is written,
+    // so a fault on the second page leaves memory untouched.
+    pub(crate) fn store_misaligned(emu: &mut EmuEnv, guest_pc: u64, addr: u64, size: u64, data: u64) -> Result<(), MemResult> {
+        if emu.m_arg_config.misalign == MisalignEnum::Trap {
+            emu.generate_exception(guest_pc, ExceptCode::StoreAddrMisalign, addr as i64);
+            return Err(MemResult::MisAlign);
+        }
+        let mut phy_addr_vec = vec![];
+        for i in 0..size {
+            let byte_addr = addr.wrapping_add(i);
+            match emu.convert_physical_address(guest_pc, byte_addr, 1, MemAccType::Write) {
+                Ok(guest_phy_addr) => phy_addr_vec.push(guest_phy_addr),
+                Err(error) => {
+                    emu.raise_mem_fault(guest_pc, byte_addr, true, &error);
+                    return Err(error);
+                }
+            }
+        }
+        for (i, guest_phy_addr) in phy_addr_vec.iter().enumerate() {
+            emu.write_mem_1byte(*guest_phy_addr, (data >> (i * 8)) as u8);
+        }
+        if emu.m_arg_config.mmu_debug {
+            println!("store misaligned : {:016x} ({:} bytes) <= {:016x}", addr, size, data);
+        }
+        return Ok(());
+    }
+
     pub fn helper_func_load64(emu: &mut EmuEnv,rd: u64,rs1: u64,imm: u64,guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
-            Ok(guest_phy_addr) => { 
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
+        if Self::is_misaligned(addr, 8) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 8) {
+                Ok(data) => {
+                    emu.m_iregs[rd as usize] = data;
+                    MemResult::NoExcept as usize
                 }
+                Err(error) => error as usize,
+            };
+        }
+        match emu.convert_physical_address(guest_pc, addr, 8, MemAccType::Read) {
+            Ok(guest_phy_addr) => { 
                 // Update TLB List
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_8byte(guest_phy_addr) as u64;
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -30,8 +128,17 @@ impl EmuEnv {
     pub fn helper_func_load32(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 4) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 4) {
+                Ok(data) => {
+                    emu.m_iregs[rd as usize] = data as i32 as u64;
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 4, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("load32 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
@@ -50,17 +157,12 @@ impl EmuEnv {
                     }                  
                     return MemResult::NoExcept as usize;
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_4byte(guest_phy_addr) as i32 as u64; 
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -69,23 +171,27 @@ impl EmuEnv {
     pub fn helper_func_load16(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 2) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 2) {
+                Ok(data) => {
+                    emu.m_iregs[rd as usize] = data as i16 as u64;
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 2, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("load16 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_2byte(guest_phy_addr) as i16 as u64; 
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -95,22 +201,17 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 1, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("load8 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_1byte(guest_phy_addr) as i8 as u64; 
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -119,23 +220,27 @@ impl EmuEnv {
     pub fn helper_func_loadu32(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 4) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 4) {
+                Ok(data) => {
+                    emu.m_iregs[rd as usize] = data;
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 4, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("loadu32 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_4byte(guest_phy_addr) as u64; 
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -144,23 +249,27 @@ impl EmuEnv {
     pub fn helper_func_loadu16(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 2) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 2) {
+                Ok(data) => {
+                    emu.m_iregs[rd as usize] = data;
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 2, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("loadu16 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_2byte(guest_phy_addr) as u64;
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -170,22 +279,17 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 1, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("loadu8 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_iregs[rd as usize] = emu.read_mem_1byte(guest_phy_addr) as u64; 
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -201,23 +305,24 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let rs2_data = emu.m_iregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 8) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 8, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 8, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("store64 : converted address: {:016x} --> {:016x} <= {:016x}", addr, guest_phy_addr, rs2_data);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_8byte(guest_phy_addr, rs2_data); 
                 return MemResult::NoExcept as usize; 
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
@@ -227,8 +332,14 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let rs2_data = emu.m_iregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 4) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 4, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 4, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("store32 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
@@ -251,17 +362,12 @@ impl EmuEnv {
                     emu.m_notify_exit = true;
                     return MemResult::NoExcept as usize;
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_4byte(guest_phy_addr, rs2_data as u32);
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
@@ -277,23 +383,24 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let rs2_data = emu.m_iregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 2) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 2, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 2, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("store16 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_2byte(guest_phy_addr, rs2_data as u16);
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
@@ -310,22 +417,17 @@ impl EmuEnv {
         let rs2_data = emu.m_iregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 1, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("store8 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_1byte(guest_phy_addr, rs2_data as u8);
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
@@ -334,22 +436,27 @@ impl EmuEnv {
     pub fn helper_func_float_load64(emu: &mut EmuEnv,rd: u64,rs1: u64,imm: u64,guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 8) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 8) {
+                Ok(data) => {
+                    emu.m_fregs[rd as usize] = data;
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 8, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("loadf64 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
-                if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
-                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
                 emu.m_fregs[rd as usize] = emu.read_mem_8byte(guest_phy_addr) as u64;
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -358,23 +465,56 @@ impl EmuEnv {
     pub fn helper_func_float_load32(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 4) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 4) {
+                Ok(data) => {
+                    emu.m_fregs[rd as usize] = Self::nan_boxing_s(data as u32);
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Read) {
+        match emu.convert_physical_address(guest_pc, addr, 4, MemAccType::Read) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("loadf32 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
+                emu.m_fregs[rd as usize] = Self::nan_boxing_s(emu.read_mem_4byte(guest_phy_addr));
+                return MemResult::NoExcept as usize;
+            }
+            Err(error) => {
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
+                return error as usize;
+            }
+        };
+    }
+
+    pub fn helper_func_float_load16(emu: &mut EmuEnv, rd: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
+        let rs1_data = emu.m_iregs[rs1 as usize];
+        let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 2) {
+            return match Self::load_misaligned(emu, guest_pc, addr, 2) {
+                Ok(data) => {
+                    emu.m_fregs[rd as usize] = Self::nan_boxing_h(data as u16);
+                    MemResult::NoExcept as usize
+                }
+                Err(error) => error as usize,
+            };
+        }
+
+        match emu.convert_physical_address(guest_pc, addr, 2, MemAccType::Read) {
+            Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
-                    println!("update tlb_vec[{:}] = {:016x}", ((addr >> 12) & 0xfff) as usize, addr >> (12 + 12));
+                    println!("loadf16 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_fregs[rd as usize] = emu.read_mem_4byte(guest_phy_addr) as u64 | 0xffffffff00000000;  // NaN Boxing 
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Read);
+                emu.m_fregs[rd as usize] = Self::nan_boxing_h(emu.read_mem_2byte(guest_phy_addr));
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::LoadPageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, false, &error);
                 return error as usize;
             }
         };
@@ -384,20 +524,24 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let rs2_data = emu.m_fregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 8) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 8, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 8, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("storef64 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_8byte(guest_phy_addr, rs2_data); 
                 return MemResult::NoExcept as usize; 
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
@@ -407,23 +551,185 @@ impl EmuEnv {
         let rs1_data = emu.m_iregs[rs1 as usize];
         let rs2_data = emu.m_fregs[rs2 as usize];
         let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 4) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 4, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
 
-        match emu.convert_physical_address(guest_pc, addr, MemAccType::Write) {
+        match emu.convert_physical_address(guest_pc, addr, 4, MemAccType::Write) {
             Ok(guest_phy_addr) => { 
                 if emu.m_arg_config.mmu_debug {
                     println!("storef32 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
                 }
-                emu.m_tlb_vec[((addr >> 12) & 0xfff) as usize] = addr >> (12 + 12);
-                emu.m_tlb_addr_vec[((addr >> 12) & 0xfff) as usize] = guest_phy_addr & !0xfff;
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
                 emu.write_mem_4byte(guest_phy_addr, rs2_data as u32);
                 return MemResult::NoExcept as usize;
             }
             Err(error) => {
-                print!("Read Error: {:?}\n", error);
-                emu.generate_exception(guest_pc, ExceptCode::StorePageFault, addr as i64);
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
+                return error as usize;
+            }
+        };
+    }
+
+    pub fn helper_func_float_store16(emu: &mut EmuEnv, rs2: u64, rs1: u64, imm: u64, guest_pc: u64) -> usize {
+        let rs1_data = emu.m_iregs[rs1 as usize];
+        let rs2_data = emu.m_fregs[rs2 as usize];
+        let addr = rs1_data.wrapping_add(imm as i32 as u64);
+        if Self::is_misaligned(addr, 2) {
+            return match Self::store_misaligned(emu, guest_pc, addr, 2, rs2_data) {
+                Ok(()) => MemResult::NoExcept as usize,
+                Err(error) => error as usize,
+            };
+        }
+
+        match emu.convert_physical_address(guest_pc, addr, 2, MemAccType::Write) {
+            Ok(guest_phy_addr) => { 
+                if emu.m_arg_config.mmu_debug {
+                    println!("storef16 : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
+                }
+                emu.fill_tlb(addr, guest_phy_addr, MemAccType::Write);
+                emu.write_mem_2byte(guest_phy_addr, rs2_data as u16);
+                return MemResult::NoExcept as usize;
+            }
+            Err(error) => {
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
                 return error as usize;
             }
         };
     }
 
+    // Zicbom / Zicboz. Below M-mode each operation must be enabled in
+    // menvcfg, and in U-mode also in senvcfg. Returns the exception to raise
+    // when it is not : a denial by henvcfg, or by senvcfg in VU-mode, is a
+    // virtual instruction exception.
+    fn cbo_denied(&self, op: u64) -> Option<ExceptCode> {
+        let (msb, lsb) = match op {
+            0x000 => (riscv_csr_def::SYSREG_ENVCFG_CBIE_MSB, riscv_csr_def::SYSREG_ENVCFG_CBIE_LSB),
+            0x001 | 0x002 => (riscv_csr_def::SYSREG_ENVCFG_CBCFE_MSB, riscv_csr_def::SYSREG_ENVCFG_CBCFE_LSB),
+            _ => (riscv_csr_def::SYSREG_ENVCFG_CBZE_MSB, riscv_csr_def::SYSREG_ENVCFG_CBZE_LSB),
+        };
+        let m_enabled = Self::extract_bit_field(self.m_csr.m_menvcfg.m_csr, msb, lsb) != 0;
+        let s_enabled = Self::extract_bit_field(self.m_csr.m_senvcfg.m_csr, msb, lsb) != 0;
+        let h_enabled = Self::extract_bit_field(self.m_csr.m_henvcfg.m_csr, msb, lsb) != 0;
+        if self.m_priv == PrivMode::Machine {
+            return None;
+        }
+        if !m_enabled {
+            return Some(ExceptCode::IllegalInst);
+        }
+        if self.m_virt && !h_enabled {
+            return Some(ExceptCode::VirtualInst);
+        }
+        if self.m_priv == PrivMode::User && !s_enabled {
+            return Some(if self.m_virt { ExceptCode::VirtualInst } else { ExceptCode::IllegalInst });
+        }
+        None
+    }
+
+    // cbo.clean/flush/inval have no cache to act on, but still need load or
+    // store permission on the block. cbo.zero writes the whole block.
+    // Faults are reported as store/AMO faults with the rs1 value in tval.
+    pub fn helper_func_cbo(emu: &mut EmuEnv, inst: u64, rs1: u64, _dummy: u64, guest_pc: u64) -> usize {
+        let op = (inst >> 20) & 0xfff;
+        if let Some(except) = emu.cbo_denied(op) {
+            emu.generate_exception(guest_pc, except, inst as i64);
+            return 1;
+        }
+
+        let rs1_data = emu.m_iregs[rs1 as usize];
+        let block_size = emu.m_arg_config.cbo_block_size;
+        let addr = rs1_data & !(block_size - 1);
+
+        let translated = if op == 0x004 {
+            emu.convert_physical_address(guest_pc, addr, block_size, MemAccType::Write)
+        } else {
+            emu.convert_physical_address(guest_pc, addr, block_size, MemAccType::Read)
+                .or_else(|_| emu.convert_physical_address(guest_pc, addr, block_size, MemAccType::Write))
+        };
+        let guest_phy_addr = match translated {
+            Ok(guest_phy_addr) => guest_phy_addr,
+            Err(error) => {
+                emu.raise_mem_fault(guest_pc, rs1_data, true, &error);
+                return error as usize;
+            }
+        };
+        if emu.m_arg_config.mmu_debug {
+            println!("cbo : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
+        }
+
+        if op == 0x004 {
+            for offset in (0..block_size).step_by(8) {
+                emu.write_mem_8byte(guest_phy_addr + offset, 0);
+            }
+        }
+        return MemResult::NoExcept as usize;
+    }
+
+    // hlv.* / hlvx.* / hsv.* : access memory as the guest would, with the
+    // privilege in hstatus.SPVP and two-stage translation, from HS/M-mode.
+    pub fn helper_func_hlv_hsv(emu: &mut EmuEnv, inst: u64, _dummy0: u64, _dummy1: u64, guest_pc: u64) -> usize {
+        if !emu.check_hypervisor_inst(inst, true, guest_pc) {
+            return 1;
+        }
+        let funct7 = (inst >> 25) & 0x7f;
+        let rs2 = (inst >> 20) & 0x1f;
+        let rs1 = (inst >> 15) & 0x1f;
+        let rd = (inst >> 7) & 0x1f;
+        let is_store = (funct7 & 0x1) != 0;
+        let size = 1u64 << ((funct7 >> 1) & 0x3);
+        let is_unsigned = (rs2 & 0x1) != 0;
+        let acc_type = match (is_store, rs2) {
+            (true, _) => MemAccType::Write,
+            (false, 3) => MemAccType::ReadExec,
+            (false, _) => MemAccType::Read,
+        };
+
+        let hstatus: i64 = emu.m_csr.csrrs(CsrAddr::Hstatus, 0);
+        let spvp = Self::extract_bit_field(hstatus, riscv_csr_def::SYSREG_HSTATUS_SPVP_MSB, riscv_csr_def::SYSREG_HSTATUS_SPVP_LSB);
+        let priv_mode = if spvp != 0 { PrivMode::Supervisor } else { PrivMode::User };
+
+        let addr = emu.mask_xlen(emu.m_iregs[rs1 as usize]);
+        if Self::is_misaligned(addr, size) {
+            let code = if is_store { ExceptCode::StoreAddrMisalign } else { ExceptCode::LoadAddrMisalign };
+            emu.generate_exception(guest_pc, code, addr as i64);
+            return MemResult::MisAlign as usize;
+        }
+        let guest_phy_addr = match emu.convert_guest_address(guest_pc, addr, size, acc_type, priv_mode, true) {
+            Ok(guest_phy_addr) => guest_phy_addr,
+            Err(error) => {
+                emu.raise_mem_fault(guest_pc, addr, is_store, &error);
+                return error as usize;
+            }
+        };
+        if emu.m_arg_config.mmu_debug {
+            println!("hlv/hsv : converted address: {:016x} --> {:016x}", addr, guest_phy_addr);
+        }
+
+        if is_store {
+            let data = emu.m_iregs[rs2 as usize];
+            match size {
+                1 => emu.write_mem_1byte(guest_phy_addr, data as u8),
+                2 => emu.write_mem_2byte(guest_phy_addr, data as u16),
+                4 => emu.write_mem_4byte(guest_phy_addr, data as u32),
+                _ => emu.write_mem_8byte(guest_phy_addr, data),
+            }
+        } else {
+            let data = match (size, is_unsigned) {
+                (1, false) => emu.read_mem_1byte(guest_phy_addr) as i8 as i64 as u64,
+                (1, true) => emu.read_mem_1byte(guest_phy_addr) as u64,
+                (2, false) => emu.read_mem_2byte(guest_phy_addr) as i16 as i64 as u64,
+                (2, true) => emu.read_mem_2byte(guest_phy_addr) as u64,
+                (4, false) => emu.read_mem_4byte(guest_phy_addr) as i32 as i64 as u64,
+                (4, true) => emu.read_mem_4byte(guest_phy_addr) as u64,
+                _ => emu.read_mem_8byte(guest_phy_addr),
+            };
+            if rd != 0 {
+                emu.m_iregs[rd as usize] = emu.sext_xlen(data);
+            }
+        }
+        return MemResult::NoExcept as usize;
+    }
 }
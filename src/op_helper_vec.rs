@@ -0,0 +1,1407 @@
+use softfloat_wrapper::{ExceptionFlags, Float, RoundingMode, F16, F32, F64};
+
+use crate::emu_env::{EmuEnv, MisalignEnum};
+use crate::target::riscv::mmu::{MemAccType, MemResult};
+use crate::target::riscv::riscv::ExceptCode;
+use crate::target::riscv::riscv_csr::CsrAddr;
+use crate::target::riscv::riscv_csr_def;
+
+pub const VLEN: usize = 128;
+pub const VLENB: usize = VLEN / 8;
+const ELEN: usize = 64;
+
+const VTYPE_VILL: u64 = 1 << 63;
+
+// Operate on a vector element as the softfloat type matching SEW (in bytes).
+macro_rules! vfp_arith {
+    ($sew:expr, $a:expr, $b:expr, $c:expr, |$x:ident, $y:ident, $z:ident| $body:expr) => {
+        match $sew {
+            2 => {
+                let ($x, $y, $z) = (F16::from_bits($a as u16), F16::from_bits($b as u16), F16::from_bits($c as u16));
+                let _ = (&$x, &$y, &$z);
+                $body.bits() as u64
+            }
+            4 => {
+                let ($x, $y, $z) = (F32::from_bits($a as u32), F32::from_bits($b as u32), F32::from_bits($c as u32));
+                let _ = (&$x, &$y, &$z);
+                $body.bits() as u64
+            }
+            _ => {
+                let ($x, $y, $z) = (F64::from_bits($a), F64::from_bits($b), F64::from_bits($c));
+                let _ = (&$x, &$y, &$z);
+                $body.bits() as u64
+            }
+        }
+    };
+}
+
+macro_rules! vfp_test {
+    ($sew:expr, $a:expr, $b:expr, |$x:ident, $y:ident| $body:expr) => {
+        match $sew {
+            2 => {
+                let ($x, $y) = (F16::from_bits($a as u16), F16::from_bits($b as u16));
+                let _ = (&$x, &$y);
+                $body
+            }
+            4 => {
+                let ($x, $y) = (F32::from_bits($a as u32), F32::from_bits($b as u32));
+                let _ = (&$x, &$y);
+                $body
+            }
+            _ => {
+                let ($x, $y) = (F64::from_bits($a), F64::from_bits($b));
+                let _ = (&$x, &$y);
+                $body
+            }
+        }
+    };
+}
+
+// Decoded OP-V instruction together with the vtype / vl state it runs under.
+struct VecInst {
+    funct6: u32,
+    funct3: u32,
+    vm: bool,
+    vd: usize,
+    vs1: usize,
+    vs2: usize,
+    scalar: u64, // x[rs1], f[rs1] or simm5 depending on funct3
+    sew: usize,  // element width in bytes
+    lmul_log2: i32,
+    vl: usize,
+    vstart: usize,
+}
+
+impl VecInst {
+    fn is_vv(&self) -> bool {
+        self.funct3 == 0 || self.funct3 == 1 || self.funct3 == 2
+    }
+
+    fn is_vi(&self) -> bool {
+        self.funct3 == 3
+    }
+
+    fn bits(&self) -> usize {
+        self.sew * 8
+    }
+
+    fn vlmax(&self) -> usize {
+        EmuEnv::vlmax(self.sew, self.lmul_log2)
+    }
+
+    // Number of registers in a group of elements of eew bytes.
+    fn group_regs(&self, eew: usize) -> usize {
+        let emul_log2 = self.lmul_log2 + eew.trailing_zeros() as i32 - self.sew.trailing_zeros() as i32;
+        if emul_log2 > 0 { 1 << emul_log2 } else { 1 }
+    }
+
+    fn aligned(&self, reg: usize, eew: usize) -> bool {
+        let regs = self.group_regs(eew);
+        reg % regs == 0 && reg + regs <= 32
+    }
+}
+
+fn sext(val: u64, bits: usize) -> i64 {
+    if bits >= 64 {
+        val as i64
+    } else {
+        ((val << (64 - bits)) as i64) >> (64 - bits)
+    }
+}
+
+fn zext(val: u64, bits: usize) -> u64 {
+    if bits >= 64 {
+        val
+    } else {
+        val & ((1 << bits) - 1)
+    }
+}
+
+fn smax(bits: usize) -> i64 {
+    (u64::MAX >> (65 - bits)) as i64
+}
+
+fn smin(bits: usize) -> i64 {
+    -smax(bits) - 1
+}
+
+fn umax(bits: usize) -> u64 {
+    u64::MAX >> (64 - bits)
+}
+
+// Fixed-point rounding of (v >> d) according to vxrm.
+fn vx_roundoff(v: i128, d: usize, vxrm: u64) -> i128 {
+    if d == 0 {
+        return v;
+    }
+    let bit = |n: usize| (v >> n) & 1;
+    let low_nonzero = |n: usize| v & ((1i128 << n).wrapping_sub(1)) != 0;
+    let r = match vxrm & 0x3 {
+        0 => bit(d - 1),
+        1 => bit(d - 1) & ((low_nonzero(d - 1) as i128) | bit(d)),
+        2 => 0,
+        _ => ((bit(d) == 0) && low_nonzero(d)) as i128,
+    };
+    (v >> d) + r
+}
+
+fn fp_sign(sew: usize) -> u64 {
+    1 << (sew * 8 - 1)
+}
+
+fn fp_is_nan(v: u64, sew: usize) -> bool {
+    vfp_test!(sew, v, 0, |x, _y| x.is_nan())
+}
+
+fn fp_canonical_nan(sew: usize) -> u64 {
+    match sew {
+        2 => 0x7e00,
+        4 => 0x7fc0_0000,
+        _ => 0x7ff8_0000_0000_0000,
+    }
+}
+
+fn fp_minmax(a: u64, b: u64, sew: usize, is_max: bool) -> u64 {
+    // The quiet compare raises invalid for signaling NaN inputs.
+    let b_lt_a = vfp_test!(sew, a, b, |x, y| y.lt_quiet(x));
+    let a_lt_b = vfp_test!(sew, a, b, |x, y| x.lt_quiet(y));
+    match (fp_is_nan(a, sew), fp_is_nan(b, sew)) {
+        (true, true) => fp_canonical_nan(sew),
+        (true, false) => b,
+        (false, true) => a,
+        _ => {
+            if a_lt_b {
+                if is_max { b } else { a }
+            } else if b_lt_a {
+                if is_max { a } else { b }
+            } else if (a & fp_sign(sew) != 0) == is_max {
+                b
+            } else {
+                a
+            }
+        }
+    }
+}
+
+fn fp_class(v: u64, sew: usize) -> u64 {
+    let bits = sew * 8;
+    let (exp_bits, frac_bits) = match sew {
+        2 => (5, 10),
+        4 => (8, 23),
+        _ => (11, 52),
+    };
+    let neg = (v >> (bits - 1)) & 1 != 0;
+    let exp = (v >> frac_bits) & ((1 << exp_bits) - 1);
+    let frac = v & ((1 << frac_bits) - 1);
+    let exp_max = (1 << exp_bits) - 1;
+    let class = if exp == exp_max {
+        if frac == 0 {
+            if neg { 0 } else { 7 }
+        } else if frac >> (frac_bits - 1) == 0 {
+            8 // signaling NaN
+        } else {
+            9 // quiet NaN
+        }
+    } else if exp == 0 {
+        if frac == 0 {
+            if neg { 3 } else { 4 }
+        } else if neg {
+            2
+        } else {
+            5
+        }
+    } else if neg {
+        1
+    } else {
+        6
+    };
+    1 << class
+}
+
+// fflags bits raised by the estimate instructions
+const FFLAGS_NV: i64 = 0x10;
+const FFLAGS_DZ: i64 = 0x08;
+const FFLAGS_OF: i64 = 0x04;
+const FFLAGS_NX: i64 = 0x01;
+
+// vfrsqrt7 : indexed by the exponent LSB and the 6 MSBs of the significand
+const RSQRT7_TABLE: [u8; 128] = [
+    52, 51, 50, 48, 47, 46, 44, 43, 42, 41, 40, 39, 38, 36, 35, 34,
+    33, 32, 31, 30, 30, 29, 28, 27, 26, 25, 24, 23, 23, 22, 21, 20,
+    19, 19, 18, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10, 9,
+    9, 8, 7, 7, 6, 6, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+    127, 125, 123, 121, 119, 118, 116, 114, 113, 111, 109, 108, 106, 105, 103, 102,
+    100, 99, 97, 96, 95, 93, 92, 91, 90, 88, 87, 86, 85, 84, 83, 82,
+    80, 79, 78, 77, 76, 75, 74, 73, 72, 71, 70, 70, 69, 68, 67, 66,
+    65, 64, 63, 63, 62, 61, 60, 59, 59, 58, 57, 56, 56, 55, 54, 53,
+];
+
+// vfrec7 : indexed by the 7 MSBs of the significand
+const REC7_TABLE: [u8; 128] = [
+    127, 125, 123, 121, 119, 117, 116, 114, 112, 110, 109, 107, 105, 104, 102, 100,
+    99, 97, 96, 94, 93, 91, 90, 88, 87, 85, 84, 83, 81, 80, 79, 77,
+    76, 75, 74, 72, 71, 70, 69, 68, 66, 65, 64, 63, 62, 61, 60, 59,
+    58, 57, 56, 55, 54, 53, 52, 51, 50, 49, 48, 47, 46, 45, 44, 43,
+    42, 41, 40, 40, 39, 38, 37, 36, 35, 35, 34, 33, 32, 31, 31, 30,
+    29, 28, 28, 27, 26, 25, 25, 24, 23, 23, 22, 21, 21, 20, 19, 19,
+    18, 17, 17, 16, 15, 15, 14, 14, 13, 12, 12, 11, 11, 10, 9, 9,
+    8, 8, 7, 7, 6, 5, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+];
+
+fn fp_format(sew: usize) -> (u32, u32) {
+    match sew {
+        2 => (5, 10),
+        4 => (8, 23),
+        _ => (11, 52),
+    }
+}
+
+// Biased exponent and fraction of a non-zero finite value. A
+// subnormal is normalized, leaving an exponent of 0 or below.
+fn fp_normalize(v: u64, exp_bits: u32, frac_bits: u32) -> (i64, u64) {
+    let frac_mask = (1u64 << frac_bits) - 1;
+    let mut exp = ((v >> frac_bits) & ((1 << exp_bits) - 1)) as i64;
+    let mut sig = v & frac_mask;
+    if exp == 0 {
+        while sig & (1 << (frac_bits - 1)) == 0 {
+            exp -= 1;
+            sig <<= 1;
+        }
+        sig = (sig << 1) & frac_mask;
+    }
+    (exp, sig)
+}
+
+// vfrsqrt7.v : reciprocal square root estimate to 7 bits. Returns (value, fflags).
+fn fp_rsqrt7(v: u64, sew: usize) -> (u64, i64) {
+    let (exp_bits, frac_bits) = fp_format(sew);
+    let sign = v & fp_sign(sew);
+    let inf = ((1u64 << exp_bits) - 1) << frac_bits;
+    match fp_class(v, sew) {
+        // -inf, -normal, -subnormal and signaling NaN
+        0x001 | 0x002 | 0x004 | 0x100 => (fp_canonical_nan(sew), FFLAGS_NV),
+        0x200 => (fp_canonical_nan(sew), 0),
+        0x008 | 0x010 => (sign | inf, FFLAGS_DZ),
+        0x080 => (0, 0),
+        _ => {
+            let (exp, sig) = fp_normalize(v, exp_bits, frac_bits);
+            let bias = (1i64 << (exp_bits - 1)) - 1;
+            let idx = (((exp & 1) as usize) << 6) | (sig >> (frac_bits - 6)) as usize;
+            let out_exp = ((3 * bias - 1 - exp) / 2) as u64;
+            ((out_exp << frac_bits) | ((RSQRT7_TABLE[idx] as u64) << (frac_bits - 7)), 0)
+        }
+    }
+}
+
+// vfrec7.v : reciprocal estimate to 7 bits. Returns (value, fflags). A
+// subnormal input whose reciprocal overflows rounds according to frm.
+fn fp_rec7(v: u64, sew: usize, frm: u64) -> (u64, i64) {
+    let (exp_bits, frac_bits) = fp_format(sew);
+    let sign = v & fp_sign(sew);
+    let inf = ((1u64 << exp_bits) - 1) << frac_bits;
+    match fp_class(v, sew) {
+        0x001 | 0x080 => (sign, 0),
+        0x008 | 0x010 => (sign | inf, FFLAGS_DZ),
+        0x100 => (fp_canonical_nan(sew), FFLAGS_NV),
+        0x200 => (fp_canonical_nan(sew), 0),
+        _ => {
+            let (exp, sig) = fp_normalize(v, exp_bits, frac_bits);
+            if exp < -1 {
+                let to_max = frm == 1 || (frm == 2 && sign == 0) || (frm == 3 && sign != 0);
+                let out = if to_max { sign | (inf - 1) } else { sign | inf };
+                return (out, FFLAGS_OF | FFLAGS_NX);
+            }
+            let bias = (1i64 << (exp_bits - 1)) - 1;
+            let mut out_sig = (REC7_TABLE[(sig >> (frac_bits - 7)) as usize] as u64) << (frac_bits - 7);
+            let mut out_exp = 2 * bias - 1 - exp;
+            // A subnormal result : shift the implicit bit into the fraction
+            if out_exp == 0 || out_exp == -1 {
+                out_sig = (out_sig >> 1) | (1 << (frac_bits - 1));
+                if out_exp == -1 {
+                    out_sig >>= 1;
+                    out_exp = 0;
+                }
+            }
+            (sign | ((out_exp as u64) << frac_bits) | out_sig, 0)
+        }
+    }
+}
+
+fn fp_to_fp(v: u64, from: usize, to: usize, rm: RoundingMode) -> u64 {
+    match (from, to) {
+        (2, 4) => F16::from_bits(v as u16).to_f32(rm).bits() as u64,
+        (4, 8) => F32::from_bits(v as u32).to_f64(rm).bits() as u64,
+        (4, 2) => F32::from_bits(v as u32).to_f16(rm).bits() as u64,
+        (8, 4) => F64::from_bits(v).to_f32(rm).bits() as u64,
+        _ => v,
+    }
+}
+
+// Narrowing conversion rounding towards odd : truncate, and force the LSB
+// when the result is inexact.
+fn fp_to_fp_rod(v: u64, from: usize, to: usize) -> u64 {
+    let r = fp_to_fp(v, from, to, RoundingMode::TowardZero);
+    if fp_is_nan(v, from) {
+        return r;
+    }
+    if fp_to_fp(r, to, from, RoundingMode::TowardZero) != v {
+        r | 1
+    } else {
+        r
+    }
+}
+
+// Float to integer of `to` bytes, saturating. Returns (value, invalid).
+fn fp_to_int(v: u64, from: usize, to: usize, signed: bool, rm: RoundingMode) -> (u64, bool) {
+    let bits = to * 8;
+    if signed {
+        let r = vfp_test!(from, v, 0, |x, _y| x.to_i64(rm, true));
+        if r > smax(bits) {
+            (smax(bits) as u64, bits < 64)
+        } else if r < smin(bits) {
+            (smin(bits) as u64, bits < 64)
+        } else {
+            (r as u64, false)
+        }
+    } else {
+        let r = vfp_test!(from, v, 0, |x, _y| x.to_u64(rm, true));
+        if r > umax(bits) {
+            (umax(bits), true)
+        } else {
+            (r, false)
+        }
+    }
+}
+
+fn int_to_fp(v: u64, from: usize, to: usize, signed: bool, rm: RoundingMode) -> u64 {
+    let bits = from * 8;
+    if signed {
+        let i = sext(v, bits);
+        match to {
+            2 => F16::from_i64(i, rm).bits() as u64,
+            4 => F32::from_i64(i, rm).bits() as u64,
+            _ => F64::from_i64(i, rm).bits() as u64,
+        }
+    } else {
+        let u = zext(v, bits);
+        match to {
+            2 => F16::from_u64(u, rm).bits() as u64,
+            4 => F32::from_u64(u, rm).bits() as u64,
+            _ => F64::from_u64(u, rm).bits() as u64,
+        }
+    }
+}
+
+impl EmuEnv {
+    fn vlmax(sew: usize, lmul_log2: i32) -> usize {
+        let elems = VLENB / sew;
+        if lmul_log2 >= 0 {
+            elems << lmul_log2
+        } else {
+            elems >> -lmul_log2
+        }
+    }
+
+    // Returns (SEW in bytes, log2(LMUL)), or None when vtype is not supported.
+    fn decode_vtype(vtype: u64) -> Option<(usize, i32)> {
+        if vtype >> 8 != 0 {
+            return None;
+        }
+        let vsew = (vtype >> 3) & 0x7;
+        if vsew > 3 {
+            return None;
+        }
+        let sew = 1 << vsew;
+        let lmul_log2 = match vtype & 0x7 {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => 3,
+            5 => -3,
+            6 => -2,
+            7 => -1,
+            _ => return None,
+        };
+        if lmul_log2 < 0 && sew * 8 > (ELEN >> -lmul_log2) {
+            return None;
+        }
+        Some((sew, lmul_log2))
+    }
+
+    #[inline]
+    fn vreg_offset(reg: usize, idx: usize, eew: usize) -> usize {
+        (reg * VLENB + idx * eew) % (VLENB * 32)
+    }
+
+    fn vreg_read(&self, reg: usize, idx: usize, eew: usize) -> u64 {
+        let offset = Self::vreg_offset(reg, idx, eew);
+        let mut data: u64 = 0;
+        for b in 0..eew {
+            data |= (self.m_vregs[offset + b] as u64) << (b * 8);
+        }
+        data
+    }
+
+    fn vreg_write(&mut self, reg: usize, idx: usize, eew: usize, data: u64) {
+        let offset = Self::vreg_offset(reg, idx, eew);
+        for b in 0..eew {
+            self.m_vregs[offset + b] = (data >> (b * 8)) as u8;
+        }
+    }
+
+    fn vmask_read(&self, reg: usize, idx: usize) -> bool {
+        (self.m_vregs[Self::vreg_offset(reg, idx / 8, 1)] >> (idx % 8)) & 1 != 0
+    }
+
+    fn vmask_write(&mut self, reg: usize, idx: usize, bit: bool) {
+        let offset = Self::vreg_offset(reg, idx / 8, 1);
+        if bit {
+            self.m_vregs[offset] |= 1 << (idx % 8);
+        } else {
+            self.m_vregs[offset] &= !(1 << (idx % 8));
+        }
+    }
+
+    #[inline]
+    fn vactive(&self, vm: bool, idx: usize) -> bool {
+        vm || self.vmask_read(0, idx)
+    }
+
+    fn vsrc1(&self, d: &VecInst, idx: usize) -> u64 {
+        if d.is_vv() {
+            self.vreg_read(d.vs1, idx, d.sew)
+        } else {
+            zext(d.scalar, d.bits())
+        }
+    }
+
+    fn vector_illegal(emu: &mut EmuEnv, inst: u64, guest_pc: u64) -> usize {
+        emu.generate_exception(guest_pc, ExceptCode::IllegalInst, inst as i64);
+        MemResult::NotDefined as usize
+    }
+
+    pub fn helper_func_vsetvl(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, _guest_pc: u64) -> usize {
+        let inst = inst as u32;
+        let rd = ((inst >> 7) & 0x1f) as usize;
+        let rs1 = ((inst >> 15) & 0x1f) as usize;
+        let rs2 = ((inst >> 20) & 0x1f) as usize;
+
+        let avl = if rs1 != 0 {
+            emu.m_iregs[rs1]
+        } else if rd != 0 {
+            u64::MAX
+        } else {
+            emu.m_csr.m_vl.m_csr as u64
+        };
+        let (vtype, avl) = if inst >> 31 == 0 {
+            (((inst >> 20) & 0x7ff) as u64, avl) // vsetvli
+        } else if inst >> 30 == 0x3 {
+            (((inst >> 20) & 0x3ff) as u64, rs1 as u64) // vsetivli
+        } else {
+            (emu.m_iregs[rs2], avl) // vsetvl
+        };
+
+        let vl = match Self::decode_vtype(vtype) {
+            Some((sew, lmul_log2)) => {
+                emu.m_csr.m_vtype.m_csr = vtype as i64;
+                avl.min(Self::vlmax(sew, lmul_log2) as u64)
+            }
+            None => {
+                emu.m_csr.m_vtype.m_csr = VTYPE_VILL as i64;
+                0
+            }
+        };
+        emu.m_csr.m_vl.m_csr = vl as i64;
+        emu.m_csr.m_vstart.m_csr = 0;
+        if rd != 0 {
+            emu.m_iregs[rd] = vl;
+        }
+        return 0;
+    }
+
+    // Element access. With raise == false a fault is reported without
+    // generating the exception (used by fault-only-first loads).
+    fn vmem_read(emu: &mut EmuEnv, guest_pc: u64, addr: u64, size: usize, raise: bool) -> Result<u64, MemResult> {
+        if Self::is_misaligned(addr, size as u64) {
+            if !raise {
+                if emu.m_arg_config.misalign == MisalignEnum::Trap {
+                    return Err(MemResult::MisAlign);
+                }
+                for probe in [addr, addr.wrapping_add(size as u64 - 1)].iter() {
+                    emu.convert_physical_address(guest_pc, *probe, 1, MemAccType::Read)?;
+                }
+            }
+            return Self::load_misaligned(emu, guest_pc, addr, size as u64);
+        }
+        match emu.convert_physical_address(guest_pc, addr, size as u64, MemAccType::Read) {
+            Ok(guest_phy_addr) => Ok(match size {
+                1 => emu.read_mem_1byte(guest_phy_addr) as u64,
+                2 => emu.read_mem_2byte(guest_phy_addr) as u64,
+                4 => emu.read_mem_4byte(guest_phy_addr) as u64,
+                _ => emu.read_mem_8byte(guest_phy_addr),
+            }),
+            Err(error) => {
+                if raise {
+                    emu.raise_mem_fault(guest_pc, addr, false, &error);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    fn vmem_write(emu: &mut EmuEnv, guest_pc: u64, addr: u64, size: usize, data: u64) -> Result<(), MemResult> {
+        if Self::is_misaligned(addr, size as u64) {
+            return Self::store_misaligned(emu, guest_pc, addr, size as u64, data);
+        }
+        match emu.convert_physical_address(guest_pc, addr, size as u64, MemAccType::Write) {
+            Ok(guest_phy_addr) => {
+                match size {
+                    1 => emu.write_mem_1byte(guest_phy_addr, data as u8),
+                    2 => emu.write_mem_2byte(guest_phy_addr, data as u16),
+                    4 => emu.write_mem_4byte(guest_phy_addr, data as u32),
+                    _ => emu.write_mem_8byte(guest_phy_addr, data),
+                }
+                Ok(())
+            }
+            Err(error) => {
+                emu.raise_mem_fault(guest_pc, addr, true, &error);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn helper_func_vector_load(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::vector_mem(emu, inst, guest_pc, false)
+    }
+
+    pub fn helper_func_vector_store(emu: &mut EmuEnv, inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        Self::vector_mem(emu, inst, guest_pc, true)
+    }
+
+    // Unit-stride, strided and indexed loads / stores, including segment,
+    // whole-register, mask and fault-only-first forms.
+    fn vector_mem(emu: &mut EmuEnv, raw_inst: u64, guest_pc: u64, is_store: bool) -> usize {
+        let inst = raw_inst as u32;
+        let nf = ((inst >> 29) & 0x7) as usize + 1;
+        let mew = (inst >> 28) & 0x1;
+        let mop = (inst >> 26) & 0x3;
+        let vm = (inst >> 25) & 0x1 != 0;
+        let umop = (inst >> 20) & 0x1f;
+        let rs1 = ((inst >> 15) & 0x1f) as usize;
+        let rs2 = ((inst >> 20) & 0x1f) as usize;
+        let vd = ((inst >> 7) & 0x1f) as usize;
+        let eew = match (inst >> 12) & 0x7 {
+            0 => 1,
+            5 => 2,
+            6 => 4,
+            7 => 8,
+            _ => return Self::vector_illegal(emu, raw_inst, guest_pc),
+        };
+
+        let whole_reg = mop == 0 && umop == 0x08;
+        let mask_acc = mop == 0 && umop == 0x0b;
+        let fault_first = mop == 0 && umop == 0x10 && !is_store;
+        if mew != 0 || (mop == 0 && umop != 0 && !whole_reg && !mask_acc && !fault_first) {
+            return Self::vector_illegal(emu, raw_inst, guest_pc);
+        }
+
+        let (evl, data_eew, index_eew, nf, group_regs, vm) = if whole_reg {
+            if !nf.is_power_of_two() || vd % nf != 0 {
+                return Self::vector_illegal(emu, raw_inst, guest_pc);
+            }
+            let eew = if is_store { 1 } else { eew };
+            (nf * VLENB / eew, eew, 0, 1, 1, true)
+        } else {
+            let (sew, lmul_log2) = match Self::decode_vtype(emu.m_csr.m_vtype.m_csr as u64) {
+                Some(vtype) => vtype,
+                None => return Self::vector_illegal(emu, raw_inst, guest_pc),
+            };
+            let vl = emu.m_csr.m_vl.m_csr as usize;
+            if mask_acc {
+                if eew != 1 || nf != 1 {
+                    return Self::vector_illegal(emu, raw_inst, guest_pc);
+                }
+                ((vl + 7) / 8, 1, 0, 1, 1, true)
+            } else {
+                let (data_eew, index_eew) = if mop & 0x1 != 0 { (sew, eew) } else { (eew, 0) };
+                let emul_log2 = lmul_log2 + data_eew.trailing_zeros() as i32 - sew.trailing_zeros() as i32;
+                if emul_log2 < -3 || emul_log2 > 3 {
+                    return Self::vector_illegal(emu, raw_inst, guest_pc);
+                }
+                let group_regs = if emul_log2 > 0 { 1 << emul_log2 } else { 1 };
+                if nf * group_regs > 8 || vd % group_regs != 0 || vd + nf * group_regs > 32 {
+                    return Self::vector_illegal(emu, raw_inst, guest_pc);
+                }
+                (vl, data_eew, index_eew, nf, group_regs, vm)
+            }
+        };
+
+        let base = emu.m_iregs[rs1];
+        let stride = if mop == 2 { emu.m_iregs[rs2] } else { (nf * data_eew) as u64 };
+        for i in emu.m_csr.m_vstart.m_csr as usize..evl {
+            if !emu.vactive(vm, i) {
+                continue;
+            }
+            let elem_addr = if mop & 0x1 != 0 {
+                base.wrapping_add(emu.vreg_read(rs2, i, index_eew))
+            } else {
+                base.wrapping_add(stride.wrapping_mul(i as u64))
+            };
+            for f in 0..nf {
+                let addr = elem_addr.wrapping_add((f * data_eew) as u64);
+                let reg = vd + f * group_regs;
+                if is_store {
+                    let data = emu.vreg_read(reg, i, data_eew);
+                    if let Err(error) = Self::vmem_write(emu, guest_pc, addr, data_eew, data) {
+                        emu.m_csr.m_vstart.m_csr = i as i64;
+                        return error as usize;
+                    }
+                } else {
+                    match Self::vmem_read(emu, guest_pc, addr, data_eew, !fault_first || i == 0) {
+                        Ok(data) => emu.vreg_write(reg, i, data_eew, data),
+                        Err(error) => {
+                            if fault_first && i != 0 {
+                                emu.m_csr.m_vl.m_csr = i as i64;
+                                emu.m_csr.m_vstart.m_csr = 0;
+                                return MemResult::NoExcept as usize;
+                            }
+                            emu.m_csr.m_vstart.m_csr = i as i64;
+                            return error as usize;
+                        }
+                    }
+                }
+            }
+        }
+        emu.m_csr.m_vstart.m_csr = 0;
+        return MemResult::NoExcept as usize;
+    }
+
+    pub fn helper_func_vector_arith(emu: &mut EmuEnv, raw_inst: u64, _: u64, _: u64, guest_pc: u64) -> usize {
+        let inst = raw_inst as u32;
+        let (sew, lmul_log2) = match Self::decode_vtype(emu.m_csr.m_vtype.m_csr as u64) {
+            Some(vtype) => vtype,
+            None => return Self::vector_illegal(emu, raw_inst, guest_pc),
+        };
+        let funct3 = (inst >> 12) & 0x7;
+        // OPFVV/OPFVF also need the FP unit enabled
+        let is_opf = funct3 == 1 || funct3 == 5;
+        if is_opf && emu.get_fs() == riscv_csr_def::SYSREG_MSTATUS_FS_OFF {
+            return Self::vector_illegal(emu, raw_inst, guest_pc);
+        }
+        let rs1 = ((inst >> 15) & 0x1f) as usize;
+        let scalar = match funct3 {
+            3 => (((inst as i32) << 12) >> 27) as i64 as u64,
+            4 | 6 => emu.m_iregs[rs1],
+            5 => match sew {
+                2 => Self::convert_nan_boxing_h(emu.m_fregs[rs1]) as u64,
+                4 => Self::convert_nan_boxing(emu.m_fregs[rs1]) as u64,
+                _ => emu.m_fregs[rs1],
+            },
+            _ => 0,
+        };
+        let d = VecInst {
+            funct6: inst >> 26,
+            funct3: funct3,
+            vm: (inst >> 25) & 0x1 != 0,
+            vd: ((inst >> 7) & 0x1f) as usize,
+            vs1: rs1,
+            vs2: ((inst >> 20) & 0x1f) as usize,
+            scalar: scalar,
+            sew: sew,
+            lmul_log2: lmul_log2,
+            vl: emu.m_csr.m_vl.m_csr as usize,
+            vstart: emu.m_csr.m_vstart.m_csr as usize,
+        };
+        let result = match funct3 {
+            0 | 3 | 4 => Self::vector_opi(emu, &d),
+            2 | 6 => Self::vector_opm(emu, &d),
+            _ => Self::vector_opf(emu, &d),
+        };
+        match result {
+            Ok(()) => {
+                if is_opf {
+                    emu.m_csr.set_fs_dirty();
+                }
+                emu.m_csr.m_vstart.m_csr = 0;
+                0
+            }
+            Err(()) => Self::vector_illegal(emu, raw_inst, guest_pc),
+        }
+    }
+
+    // vd[i] = f(vs2[i], src1[i]) on each active element.
+    fn vloop_binary<F: FnMut(u64, u64) -> u64>(emu: &mut EmuEnv, d: &VecInst, mut f: F) -> Result<(), ()> {
+        if !d.aligned(d.vd, d.sew) || !d.aligned(d.vs2, d.sew) {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let a = emu.vreg_read(d.vs2, i, d.sew);
+            let b = emu.vsrc1(d, i);
+            emu.vreg_write(d.vd, i, d.sew, f(a, b));
+        }
+        Ok(())
+    }
+
+    // vd[i] = f(vd[i], vs2[i], src1[i]) on each active element.
+    fn vloop_ternary<F: FnMut(u64, u64, u64) -> u64>(emu: &mut EmuEnv, d: &VecInst, mut f: F) -> Result<(), ()> {
+        if !d.aligned(d.vd, d.sew) || !d.aligned(d.vs2, d.sew) {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let c = emu.vreg_read(d.vd, i, d.sew);
+            let a = emu.vreg_read(d.vs2, i, d.sew);
+            let b = emu.vsrc1(d, i);
+            emu.vreg_write(d.vd, i, d.sew, f(c, a, b));
+        }
+        Ok(())
+    }
+
+    // vd.mask[i] = f(vs2[i], src1[i]) on each active element.
+    fn vloop_compare<F: FnMut(u64, u64) -> bool>(emu: &mut EmuEnv, d: &VecInst, mut f: F) -> Result<(), ()> {
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let a = emu.vreg_read(d.vs2, i, d.sew);
+            let b = emu.vsrc1(d, i);
+            let bit = f(a, b);
+            emu.vmask_write(d.vd, i, bit);
+        }
+        Ok(())
+    }
+
+    // Widening : vd (2*SEW) = f(vd[i], vs2[i], src1[i]), where vs2 is 2*SEW
+    // for the .w forms.
+    fn vloop_widen<F: FnMut(u64, u64, u64) -> u64>(emu: &mut EmuEnv, d: &VecInst, wide_vs2: bool, mut f: F) -> Result<(), ()> {
+        let weew = d.sew * 2;
+        let vs2_eew = if wide_vs2 { weew } else { d.sew };
+        if d.sew > 4 || d.lmul_log2 >= 3 || !d.aligned(d.vd, weew) || !d.aligned(d.vs2, vs2_eew) {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let c = emu.vreg_read(d.vd, i, weew);
+            let a = emu.vreg_read(d.vs2, i, vs2_eew);
+            let b = emu.vsrc1(d, i);
+            emu.vreg_write(d.vd, i, weew, f(c, a, b));
+        }
+        Ok(())
+    }
+
+    // Narrowing : vd (SEW) = f(vs2[i] (2*SEW), src1[i]).
+    fn vloop_narrow<F: FnMut(u64, u64) -> u64>(emu: &mut EmuEnv, d: &VecInst, mut f: F) -> Result<(), ()> {
+        let weew = d.sew * 2;
+        if d.sew > 4 || d.lmul_log2 >= 3 || !d.aligned(d.vd, d.sew) || !d.aligned(d.vs2, weew) {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let a = emu.vreg_read(d.vs2, i, weew);
+            let b = emu.vsrc1(d, i);
+            emu.vreg_write(d.vd, i, d.sew, f(a, b));
+        }
+        Ok(())
+    }
+
+    // Reduction : vd[0] = f(...f(vs1[0], vs2[0])..., vs2[vl-1]), accumulator of acc_eew bytes.
+    fn vreduce<F: FnMut(u64, u64) -> u64>(emu: &mut EmuEnv, d: &VecInst, acc_eew: usize, mut f: F) -> Result<(), ()> {
+        if d.vstart != 0 || acc_eew > 8 || !d.aligned(d.vs2, d.sew) {
+            return Err(());
+        }
+        if d.vl == 0 {
+            return Ok(());
+        }
+        let mut acc = emu.vreg_read(d.vs1, 0, acc_eew);
+        for i in 0..d.vl {
+            if emu.vactive(d.vm, i) {
+                acc = f(acc, emu.vreg_read(d.vs2, i, d.sew));
+            }
+        }
+        emu.vreg_write(d.vd, 0, acc_eew, acc);
+        Ok(())
+    }
+
+    // vmerge / vmv.v (vm set) : vd[i] = v0.mask[i] ? src1[i] : vs2[i]
+    fn vmerge(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        if d.vm && d.vs2 != 0 || !d.aligned(d.vd, d.sew) {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            let data = if d.vm || emu.vmask_read(0, i) {
+                emu.vsrc1(d, i)
+            } else {
+                emu.vreg_read(d.vs2, i, d.sew)
+            };
+            emu.vreg_write(d.vd, i, d.sew, data);
+        }
+        Ok(())
+    }
+
+    fn vslide_up(emu: &mut EmuEnv, d: &VecInst, offset: u64) -> Result<(), ()> {
+        if d.vd == d.vs2 {
+            return Err(());
+        }
+        let start = if offset > d.vstart as u64 { offset.min(d.vl as u64) as usize } else { d.vstart };
+        for i in start..d.vl {
+            if emu.vactive(d.vm, i) {
+                let data = emu.vreg_read(d.vs2, i - offset as usize, d.sew);
+                emu.vreg_write(d.vd, i, d.sew, data);
+            }
+        }
+        Ok(())
+    }
+
+    fn vslide_down(emu: &mut EmuEnv, d: &VecInst, offset: u64) -> Result<(), ()> {
+        let vlmax = d.vlmax() as u64;
+        for i in d.vstart..d.vl {
+            if emu.vactive(d.vm, i) {
+                let src = (i as u64).saturating_add(offset);
+                let data = if src < vlmax { emu.vreg_read(d.vs2, src as usize, d.sew) } else { 0 };
+                emu.vreg_write(d.vd, i, d.sew, data);
+            }
+        }
+        Ok(())
+    }
+
+    fn vslide1_up(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        if d.vd == d.vs2 {
+            return Err(());
+        }
+        for i in d.vstart..d.vl {
+            if emu.vactive(d.vm, i) {
+                let data = if i == 0 { d.scalar } else { emu.vreg_read(d.vs2, i - 1, d.sew) };
+                emu.vreg_write(d.vd, i, d.sew, data);
+            }
+        }
+        Ok(())
+    }
+
+    fn vslide1_down(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        for i in d.vstart..d.vl {
+            if emu.vactive(d.vm, i) {
+                let data = if i + 1 < d.vl { emu.vreg_read(d.vs2, i + 1, d.sew) } else { d.scalar };
+                emu.vreg_write(d.vd, i, d.sew, data);
+            }
+        }
+        Ok(())
+    }
+
+    fn vrgather(emu: &mut EmuEnv, d: &VecInst, index_eew: usize) -> Result<(), ()> {
+        if d.vd == d.vs2 || d.is_vv() && d.vd == d.vs1 {
+            return Err(());
+        }
+        let vlmax = d.vlmax() as u64;
+        for i in d.vstart..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let index = if d.is_vv() {
+                emu.vreg_read(d.vs1, i, index_eew)
+            } else if d.is_vi() {
+                d.vs1 as u64
+            } else {
+                d.scalar
+            };
+            let data = if index < vlmax { emu.vreg_read(d.vs2, index as usize, d.sew) } else { 0 };
+            emu.vreg_write(d.vd, i, d.sew, data);
+        }
+        Ok(())
+    }
+
+    // Integer (OPIVV / OPIVX / OPIVI) instructions.
+    fn vector_opi(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        let bits = d.bits();
+        let vxrm = emu.m_csr.m_vxrm.m_csr as u64;
+        let mut sat = false;
+        let offset = if d.is_vi() { d.vs1 as u64 } else { d.scalar };
+
+        let result = match d.funct6 {
+            0x00 => Self::vloop_binary(emu, d, |a, b| a.wrapping_add(b)),
+            0x02 if !d.is_vi() => Self::vloop_binary(emu, d, |a, b| a.wrapping_sub(b)),
+            0x03 if !d.is_vv() => Self::vloop_binary(emu, d, |a, b| b.wrapping_sub(a)),
+            0x04 => Self::vloop_binary(emu, d, |a, b| a.min(b)),
+            0x05 => Self::vloop_binary(emu, d, |a, b| if sext(a, bits) < sext(b, bits) { a } else { b }),
+            0x06 => Self::vloop_binary(emu, d, |a, b| a.max(b)),
+            0x07 => Self::vloop_binary(emu, d, |a, b| if sext(a, bits) > sext(b, bits) { a } else { b }),
+            0x09 => Self::vloop_binary(emu, d, |a, b| a & b),
+            0x0a => Self::vloop_binary(emu, d, |a, b| a | b),
+            0x0b => Self::vloop_binary(emu, d, |a, b| a ^ b),
+            0x0c => Self::vrgather(emu, d, d.sew),
+            0x0e if d.is_vv() => Self::vrgather(emu, d, 2),
+            0x0e => Self::vslide_up(emu, d, offset),
+            0x0f if !d.is_vv() => Self::vslide_down(emu, d, offset),
+            0x10 | 0x12 if !d.vm && d.vd != 0 && (d.funct6 == 0x10 || !d.is_vi()) => {
+                // vadc / vsbc
+                for i in d.vstart..d.vl {
+                    let a = emu.vreg_read(d.vs2, i, d.sew);
+                    let b = emu.vsrc1(d, i);
+                    let carry = emu.vmask_read(0, i) as u64;
+                    let data = if d.funct6 == 0x10 {
+                        a.wrapping_add(b).wrapping_add(carry)
+                    } else {
+                        a.wrapping_sub(b).wrapping_sub(carry)
+                    };
+                    emu.vreg_write(d.vd, i, d.sew, data);
+                }
+                Ok(())
+            }
+            0x11 | 0x13 if d.funct6 == 0x11 || !d.is_vi() => {
+                // vmadc / vmsbc : carry / borrow out, with carry-in from v0 when vm is clear
+                for i in d.vstart..d.vl {
+                    let a = emu.vreg_read(d.vs2, i, d.sew) as u128;
+                    let b = emu.vsrc1(d, i) as u128;
+                    let carry = if d.vm { 0 } else { emu.vmask_read(0, i) as u128 };
+                    let bit = if d.funct6 == 0x11 {
+                        (a + b + carry) >> bits != 0
+                    } else {
+                        a < b + carry
+                    };
+                    emu.vmask_write(d.vd, i, bit);
+                }
+                Ok(())
+            }
+            0x17 => Self::vmerge(emu, d),
+            0x18 => Self::vloop_compare(emu, d, |a, b| a == b),
+            0x19 => Self::vloop_compare(emu, d, |a, b| a != b),
+            0x1a if !d.is_vi() => Self::vloop_compare(emu, d, |a, b| a < b),
+            0x1b if !d.is_vi() => Self::vloop_compare(emu, d, |a, b| sext(a, bits) < sext(b, bits)),
+            0x1c => Self::vloop_compare(emu, d, |a, b| a <= b),
+            0x1d => Self::vloop_compare(emu, d, |a, b| sext(a, bits) <= sext(b, bits)),
+            0x1e if !d.is_vv() => Self::vloop_compare(emu, d, |a, b| a > b),
+            0x1f if !d.is_vv() => Self::vloop_compare(emu, d, |a, b| sext(a, bits) > sext(b, bits)),
+            0x20 => Self::vloop_binary(emu, d, |a, b| {
+                let r = a.wrapping_add(b);
+                if r > umax(bits) || r < a {
+                    sat = true;
+                    umax(bits)
+                } else {
+                    r
+                }
+            }),
+            0x21 => Self::vloop_binary(emu, d, |a, b| {
+                let r = sext(a, bits) as i128 + sext(b, bits) as i128;
+                Self::vx_saturate_signed(r, bits, &mut sat)
+            }),
+            0x22 if !d.is_vi() => Self::vloop_binary(emu, d, |a, b| {
+                if a < b {
+                    sat = true;
+                    0
+                } else {
+                    a - b
+                }
+            }),
+            0x23 if !d.is_vi() => Self::vloop_binary(emu, d, |a, b| {
+                let r = sext(a, bits) as i128 - sext(b, bits) as i128;
+                Self::vx_saturate_signed(r, bits, &mut sat)
+            }),
+            0x25 => Self::vloop_binary(emu, d, |a, b| a << (b as usize & (bits - 1))),
+            0x27 if d.is_vi() => {
+                // vmv<nr>r.v
+                let nr = d.vs1 + 1;
+                if !nr.is_power_of_two() || nr > 8 || d.vd % nr != 0 || d.vs2 % nr != 0 {
+                    return Err(());
+                }
+                for i in d.vstart..(nr * VLENB / d.sew) {
+                    let data = emu.vreg_read(d.vs2, i, d.sew);
+                    emu.vreg_write(d.vd, i, d.sew, data);
+                }
+                Ok(())
+            }
+            0x27 => Self::vloop_binary(emu, d, |a, b| {
+                // vsmul
+                let r = vx_roundoff(sext(a, bits) as i128 * sext(b, bits) as i128, bits - 1, vxrm);
+                Self::vx_saturate_signed(r, bits, &mut sat)
+            }),
+            0x28 => Self::vloop_binary(emu, d, |a, b| a >> (b as usize & (bits - 1))),
+            0x29 => Self::vloop_binary(emu, d, |a, b| (sext(a, bits) >> (b as usize & (bits - 1))) as u64),
+            0x2a => Self::vloop_binary(emu, d, |a, b| vx_roundoff(a as i128, b as usize & (bits - 1), vxrm) as u64),
+            0x2b => Self::vloop_binary(emu, d, |a, b| vx_roundoff(sext(a, bits) as i128, b as usize & (bits - 1), vxrm) as u64),
+            0x2c => Self::vloop_narrow(emu, d, |a, b| a >> (b as usize & (bits * 2 - 1))),
+            0x2d => Self::vloop_narrow(emu, d, |a, b| (sext(a, bits * 2) >> (b as usize & (bits * 2 - 1))) as u64),
+            0x2e => Self::vloop_narrow(emu, d, |a, b| {
+                let r = vx_roundoff(a as i128, b as usize & (bits * 2 - 1), vxrm);
+                if r > umax(bits) as i128 {
+                    sat = true;
+                    umax(bits)
+                } else {
+                    r as u64
+                }
+            }),
+            0x2f => Self::vloop_narrow(emu, d, |a, b| {
+                let r = vx_roundoff(sext(a, bits * 2) as i128, b as usize & (bits * 2 - 1), vxrm);
+                Self::vx_saturate_signed(r, bits, &mut sat)
+            }),
+            0x30 if d.is_vv() => Self::vreduce(emu, d, d.sew * 2, |acc, e| acc.wrapping_add(e)),
+            0x31 if d.is_vv() => Self::vreduce(emu, d, d.sew * 2, |acc, e| acc.wrapping_add(sext(e, bits) as u64)),
+            _ => Err(()),
+        };
+        if sat {
+            emu.m_csr.m_vxsat.m_csr = 1;
+        }
+        result
+    }
+
+    fn vx_saturate_signed(r: i128, bits: usize, sat: &mut bool) -> u64 {
+        if r > smax(bits) as i128 {
+            *sat = true;
+            smax(bits) as u64
+        } else if r < smin(bits) as i128 {
+            *sat = true;
+            smin(bits) as u64
+        } else {
+            r as u64
+        }
+    }
+
+    // Integer multiply/divide, reduction, mask and permutation (OPMVV / OPMVX) instructions.
+    fn vector_opm(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        let bits = d.bits();
+        let wbits = bits * 2;
+        let vxrm = emu.m_csr.m_vxrm.m_csr as u64;
+        let vv = d.is_vv();
+
+        match d.funct6 {
+            0x00 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc.wrapping_add(e)),
+            0x01 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc & e),
+            0x02 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc | e),
+            0x03 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc ^ e),
+            0x04 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc.min(e)),
+            0x05 if vv => Self::vreduce(emu, d, d.sew, |acc, e| if sext(e, bits) < sext(acc, bits) { e } else { acc }),
+            0x06 if vv => Self::vreduce(emu, d, d.sew, |acc, e| acc.max(e)),
+            0x07 if vv => Self::vreduce(emu, d, d.sew, |acc, e| if sext(e, bits) > sext(acc, bits) { e } else { acc }),
+            0x08 => Self::vloop_binary(emu, d, |a, b| vx_roundoff(a as i128 + b as i128, 1, vxrm) as u64),
+            0x09 => Self::vloop_binary(emu, d, |a, b| vx_roundoff(sext(a, bits) as i128 + sext(b, bits) as i128, 1, vxrm) as u64),
+            0x0a => Self::vloop_binary(emu, d, |a, b| vx_roundoff(a as i128 - b as i128, 1, vxrm) as u64),
+            0x0b => Self::vloop_binary(emu, d, |a, b| vx_roundoff(sext(a, bits) as i128 - sext(b, bits) as i128, 1, vxrm) as u64),
+            0x0e if !vv => Self::vslide1_up(emu, d),
+            0x0f if !vv => Self::vslide1_down(emu, d),
+            0x10 if vv => {
+                // VWXUNARY0 : vmv.x.s / vcpop.m / vfirst.m
+                let data = match d.vs1 {
+                    0x00 => sext(emu.vreg_read(d.vs2, 0, d.sew), bits) as u64,
+                    0x10 => (0..d.vl).filter(|&i| emu.vactive(d.vm, i) && emu.vmask_read(d.vs2, i)).count() as u64,
+                    0x11 => match (0..d.vl).find(|&i| emu.vactive(d.vm, i) && emu.vmask_read(d.vs2, i)) {
+                        Some(i) => i as u64,
+                        None => u64::MAX,
+                    },
+                    _ => return Err(()),
+                };
+                if d.vd != 0 {
+                    emu.m_iregs[d.vd] = data;
+                }
+                Ok(())
+            }
+            0x10 if d.vs2 == 0 => {
+                // vmv.s.x
+                if d.vstart < d.vl {
+                    emu.vreg_write(d.vd, 0, d.sew, d.scalar);
+                }
+                Ok(())
+            }
+            0x12 if vv => {
+                // VXUNARY0 : vzext / vsext
+                let frac = match d.vs1 {
+                    2 | 3 => 8,
+                    4 | 5 => 4,
+                    6 | 7 => 2,
+                    _ => return Err(()),
+                };
+                let signed = d.vs1 & 0x1 != 0;
+                if d.sew < frac {
+                    return Err(());
+                }
+                let src_eew = d.sew / frac;
+                for i in d.vstart..d.vl {
+                    if emu.vactive(d.vm, i) {
+                        let data = emu.vreg_read(d.vs2, i, src_eew);
+                        let data = if signed { sext(data, src_eew * 8) as u64 } else { data };
+                        emu.vreg_write(d.vd, i, d.sew, data);
+                    }
+                }
+                Ok(())
+            }
+            0x14 if vv => Self::vector_mask_unary(emu, d),
+            0x17 if vv && d.vm => {
+                // vcompress
+                if d.vstart != 0 || d.vd == d.vs2 || d.vd == d.vs1 {
+                    return Err(());
+                }
+                let mut j = 0;
+                for i in 0..d.vl {
+                    if emu.vmask_read(d.vs1, i) {
+                        let data = emu.vreg_read(d.vs2, i, d.sew);
+                        emu.vreg_write(d.vd, j, d.sew, data);
+                        j += 1;
+                    }
+                }
+                Ok(())
+            }
+            0x18..=0x1f if vv => {
+                for i in d.vstart..d.vl {
+                    let a = emu.vmask_read(d.vs2, i);
+                    let b = emu.vmask_read(d.vs1, i);
+                    let bit = match d.funct6 {
+                        0x18 => a && !b,
+                        0x19 => a && b,
+                        0x1a => a || b,
+                        0x1b => a ^ b,
+                        0x1c => a || !b,
+                        0x1d => !(a && b),
+                        0x1e => !(a || b),
+                        _ => !(a ^ b),
+                    };
+                    emu.vmask_write(d.vd, i, bit);
+                }
+                Ok(())
+            }
+            0x20 => Self::vloop_binary(emu, d, |a, b| if b == 0 { u64::MAX } else { a / b }),
+            0x21 => Self::vloop_binary(emu, d, |a, b| {
+                let (a, b) = (sext(a, bits), sext(b, bits));
+                if b == 0 { u64::MAX } else { a.wrapping_div(b) as u64 }
+            }),
+            0x22 => Self::vloop_binary(emu, d, |a, b| if b == 0 { a } else { a % b }),
+            0x23 => Self::vloop_binary(emu, d, |a, b| {
+                let (a, b) = (sext(a, bits), sext(b, bits));
+                if b == 0 { a as u64 } else { a.wrapping_rem(b) as u64 }
+            }),
+            0x24 => Self::vloop_binary(emu, d, |a, b| ((a as u128 * b as u128) >> bits) as u64),
+            0x25 => Self::vloop_binary(emu, d, |a, b| a.wrapping_mul(b)),
+            0x26 => Self::vloop_binary(emu, d, |a, b| ((sext(a, bits) as i128 * b as i128) >> bits) as u64),
+            0x27 => Self::vloop_binary(emu, d, |a, b| ((sext(a, bits) as i128 * sext(b, bits) as i128) >> bits) as u64),
+            0x29 => Self::vloop_ternary(emu, d, |c, a, b| b.wrapping_mul(c).wrapping_add(a)),
+            0x2b => Self::vloop_ternary(emu, d, |c, a, b| a.wrapping_sub(b.wrapping_mul(c))),
+            0x2d => Self::vloop_ternary(emu, d, |c, a, b| b.wrapping_mul(a).wrapping_add(c)),
+            0x2f => Self::vloop_ternary(emu, d, |c, a, b| c.wrapping_sub(b.wrapping_mul(a))),
+            0x30 => Self::vloop_widen(emu, d, false, |_, a, b| a.wrapping_add(b)),
+            0x31 => Self::vloop_widen(emu, d, false, |_, a, b| sext(a, bits).wrapping_add(sext(b, bits)) as u64),
+            0x32 => Self::vloop_widen(emu, d, false, |_, a, b| a.wrapping_sub(b)),
+            0x33 => Self::vloop_widen(emu, d, false, |_, a, b| sext(a, bits).wrapping_sub(sext(b, bits)) as u64),
+            0x34 => Self::vloop_widen(emu, d, true, |_, a, b| a.wrapping_add(b)),
+            0x35 => Self::vloop_widen(emu, d, true, |_, a, b| sext(a, wbits).wrapping_add(sext(b, bits)) as u64),
+            0x36 => Self::vloop_widen(emu, d, true, |_, a, b| a.wrapping_sub(b)),
+            0x37 => Self::vloop_widen(emu, d, true, |_, a, b| sext(a, wbits).wrapping_sub(sext(b, bits)) as u64),
+            0x38 => Self::vloop_widen(emu, d, false, |_, a, b| a.wrapping_mul(b)),
+            0x3a => Self::vloop_widen(emu, d, false, |_, a, b| sext(a, bits).wrapping_mul(b as i64) as u64),
+            0x3b => Self::vloop_widen(emu, d, false, |_, a, b| sext(a, bits).wrapping_mul(sext(b, bits)) as u64),
+            0x3c => Self::vloop_widen(emu, d, false, |c, a, b| c.wrapping_add(a.wrapping_mul(b))),
+            0x3d => Self::vloop_widen(emu, d, false, |c, a, b| c.wrapping_add(sext(a, bits).wrapping_mul(sext(b, bits)) as u64)),
+            0x3e if !vv => Self::vloop_widen(emu, d, false, |c, a, b| c.wrapping_add(sext(a, bits).wrapping_mul(b as i64) as u64)),
+            0x3f => Self::vloop_widen(emu, d, false, |c, a, b| c.wrapping_add((a as i64).wrapping_mul(sext(b, bits)) as u64)),
+            _ => Err(()),
+        }
+    }
+
+    // VMUNARY0 : vmsbf / vmsof / vmsif / viota / vid
+    fn vector_mask_unary(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        if d.vstart != 0 || d.vd == d.vs2 && d.vs1 != 0x11 {
+            return Err(());
+        }
+        let mut found = false;
+        let mut count = 0;
+        for i in 0..d.vl {
+            if !emu.vactive(d.vm, i) {
+                continue;
+            }
+            let cur = emu.vmask_read(d.vs2, i);
+            match d.vs1 {
+                0x01 => emu.vmask_write(d.vd, i, !found && !cur),
+                0x02 => emu.vmask_write(d.vd, i, !found && cur),
+                0x03 => emu.vmask_write(d.vd, i, !found),
+                0x10 => emu.vreg_write(d.vd, i, d.sew, count),
+                0x11 => emu.vreg_write(d.vd, i, d.sew, i as u64),
+                _ => return Err(()),
+            }
+            found |= cur;
+            count += cur as u64;
+        }
+        Ok(())
+    }
+
+    // Floating point (OPFVV / OPFVF) instructions. Exception flags are
+    // accrued into fflags.
+    fn vector_opf(emu: &mut EmuEnv, d: &VecInst) -> Result<(), ()> {
+        if d.sew == 1 {
+            return Err(());
+        }
+        let mut flags = 0;
+        let mut flag = ExceptionFlags::default();
+        flag.set();
+        let result = Self::vector_opf_body(emu, d, &mut flags);
+        flag.get();
+        let ret_flag = flag.bits() as i64 | flags;
+        emu.m_csr.csrrs(CsrAddr::FFlags, ret_flag);
+        result
+    }
+
+    fn vector_opf_body(emu: &mut EmuEnv, d: &VecInst, flags: &mut i64) -> Result<(), ()> {
+        let sew = d.sew;
+        let wsew = sew * 2;
+        let sign = fp_sign(sew);
+        let vv = d.is_vv();
+        let rm = match Self::decode_rounding_mode(emu.m_csr.csrrs(CsrAddr::Frm, 0) as u64) {
+            Some(rm) => rm,
+            None => return Err(()),
+        };
+
+        match d.funct6 {
+            0x00 => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| x.add(y, rm))),
+            0x01 | 0x03 if vv => Self::vreduce(emu, d, sew, |acc, e| vfp_arith!(sew, acc, e, 0, |x, y, _z| x.add(y, rm))),
+            0x02 => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| x.sub(y, rm))),
+            0x04 => Self::vloop_binary(emu, d, |a, b| fp_minmax(a, b, sew, false)),
+            0x05 if vv => Self::vreduce(emu, d, sew, |acc, e| fp_minmax(acc, e, sew, false)),
+            0x06 => Self::vloop_binary(emu, d, |a, b| fp_minmax(a, b, sew, true)),
+            0x07 if vv => Self::vreduce(emu, d, sew, |acc, e| fp_minmax(acc, e, sew, true)),
+            0x08 => Self::vloop_binary(emu, d, |a, b| (a & !sign) | (b & sign)),
+            0x09 => Self::vloop_binary(emu, d, |a, b| (a & !sign) | (!b & sign)),
+            0x0a => Self::vloop_binary(emu, d, |a, b| a ^ (b & sign)),
+            0x0e if !vv => Self::vslide1_up(emu, d),
+            0x0f if !vv => Self::vslide1_down(emu, d),
+            0x10 if vv && d.vs1 == 0 => {
+                // vfmv.f.s
+                let data = emu.vreg_read(d.vs2, 0, sew);
+                emu.m_fregs[d.vd] = match sew {
+                    2 => Self::nan_boxing_h(data as u16),
+                    4 => Self::nan_boxing_s(data as u32),
+                    _ => data,
+                };
+                Ok(())
+            }
+            0x10 if !vv && d.vs2 == 0 => {
+                // vfmv.s.f
+                if d.vstart < d.vl {
+                    emu.vreg_write(d.vd, 0, sew, d.scalar);
+                }
+                Ok(())
+            }
+            0x12 if vv => Self::vector_fcvt(emu, d, flags),
+            0x13 if vv => match d.vs1 {
+                0x00 => Self::vloop_binary(emu, d, |a, _| vfp_arith!(sew, a, 0, 0, |x, _y, _z| x.sqrt(rm))),
+                0x04 => Self::vloop_binary(emu, d, |a, _| {
+                    let (data, fflags) = fp_rsqrt7(a, sew);
+                    *flags |= fflags;
+                    data
+                }),
+                0x05 => {
+                    let frm = emu.m_csr.csrrs(CsrAddr::Frm, 0) as u64;
+                    Self::vloop_binary(emu, d, |a, _| {
+                        let (data, fflags) = fp_rec7(a, sew, frm);
+                        *flags |= fflags;
+                        data
+                    })
+                }
+                0x10 => Self::vloop_binary(emu, d, |a, _| fp_class(a, sew)),
+                _ => Err(()),
+            },
+            0x17 if !vv => Self::vmerge(emu, d),
+            0x18 => Self::vloop_compare(emu, d, |a, b| vfp_test!(sew, a, b, |x, y| x.eq(y))),
+            0x19 => Self::vloop_compare(emu, d, |a, b| vfp_test!(sew, a, b, |x, y| x.le(y))),
+            0x1b => Self::vloop_compare(emu, d, |a, b| vfp_test!(sew, a, b, |x, y| x.lt(y))),
+            0x1c => Self::vloop_compare(emu, d, |a, b| !vfp_test!(sew, a, b, |x, y| x.eq(y))),
+            0x1d if !vv => Self::vloop_compare(emu, d, |a, b| vfp_test!(sew, a, b, |x, y| y.lt(x))),
+            0x1f if !vv => Self::vloop_compare(emu, d, |a, b| vfp_test!(sew, a, b, |x, y| y.le(x))),
+            0x20 => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| x.div(y, rm))),
+            0x21 if !vv => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| y.div(x, rm))),
+            0x24 => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| x.mul(y, rm))),
+            0x27 if !vv => Self::vloop_binary(emu, d, |a, b| vfp_arith!(sew, a, b, 0, |x, y, _z| y.sub(x, rm))),
+            // c = vd, a = vs2, b = vs1 / rs1
+            0x28 => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, c, a, |x, y, z| x.fused_mul_add(y, z, rm))),
+            0x29 => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, c, a, |x, y, z| x.neg().fused_mul_add(y, z.neg(), rm))),
+            0x2a => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, c, a, |x, y, z| x.fused_mul_add(y, z.neg(), rm))),
+            0x2b => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, c, a, |x, y, z| x.neg().fused_mul_add(y, z, rm))),
+            0x2c => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, a, c, |x, y, z| x.fused_mul_add(y, z, rm))),
+            0x2d => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, a, c, |x, y, z| x.neg().fused_mul_add(y, z.neg(), rm))),
+            0x2e => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, a, c, |x, y, z| x.fused_mul_add(y, z.neg(), rm))),
+            0x2f => Self::vloop_ternary(emu, d, |c, a, b| vfp_arith!(sew, b, a, c, |x, y, z| x.neg().fused_mul_add(y, z, rm))),
+            // Widening : operands are converted to 2*SEW exactly before the operation.
+            0x30 => Self::vloop_widen(emu, d, false, |_, a, b| {
+                let (a, b) = (fp_to_fp(a, sew, wsew, rm), fp_to_fp(b, sew, wsew, rm));
+                vfp_arith!(wsew, a, b, 0, |x, y, _z| x.add(y, rm))
+            }),
+            0x31 | 0x33 if vv => Self::vreduce(emu, d, wsew, |acc, e| {
+                let e = fp_to_fp(e, sew, wsew, rm);
+                vfp_arith!(wsew, acc, e, 0, |x, y, _z| x.add(y, rm))
+            }),
+            0x32 => Self::vloop_widen(emu, d, false, |_, a, b| {
+                let (a, b) = (fp_to_fp(a, sew, wsew, rm), fp_to_fp(b, sew, wsew, rm));
+                vfp_arith!(wsew, a, b, 0, |x, y, _z| x.sub(y, rm))
+            }),
+            0x34 => Self::vloop_widen(emu, d, true, |_, a, b| {
+                let b = fp_to_fp(b, sew, wsew, rm);
+                vfp_arith!(wsew, a, b, 0, |x, y, _z| x.add(y, rm))
+            }),
+            0x36 => Self::vloop_widen(emu, d, true, |_, a, b| {
+                let b = fp_to_fp(b, sew, wsew, rm);
+                vfp_arith!(wsew, a, b, 0, |x, y, _z| x.sub(y, rm))
+            }),
+            0x38 => Self::vloop_widen(emu, d, false, |_, a, b| {
+                let (a, b) = (fp_to_fp(a, sew, wsew, rm), fp_to_fp(b, sew, wsew, rm));
+                vfp_arith!(wsew, a, b, 0, |x, y, _z| x.mul(y, rm))
+            }),
+            0x3c..=0x3f => {
+                let funct6 = d.funct6;
+                Self::vloop_widen(emu, d, false, |c, a, b| {
+                    let (a, b) = (fp_to_fp(a, sew, wsew, rm), fp_to_fp(b, sew, wsew, rm));
+                    match funct6 {
+                        0x3c => vfp_arith!(wsew, b, a, c, |x, y, z| x.fused_mul_add(y, z, rm)),
+                        0x3d => vfp_arith!(wsew, b, a, c, |x, y, z| x.neg().fused_mul_add(y, z.neg(), rm)),
+                        0x3e => vfp_arith!(wsew, b, a, c, |x, y, z| x.fused_mul_add(y, z.neg(), rm)),
+                        _ => vfp_arith!(wsew, b, a, c, |x, y, z| x.neg().fused_mul_add(y, z, rm)),
+                    }
+                })
+            }
+            _ => Err(()),
+        }
+    }
+
+    // VFUNARY0 : single-width, widening and narrowing conversions.
+    fn vector_fcvt(emu: &mut EmuEnv, d: &VecInst, flags: &mut i64) -> Result<(), ()> {
+        let sew = d.sew;
+        let wsew = sew * 2;
+        let frm = match Self::decode_rounding_mode(emu.m_csr.csrrs(CsrAddr::Frm, 0) as u64) {
+            Some(rm) => rm,
+            None => return Err(()),
+        };
+        let rm = if d.vs1 & 0x6 == 0x6 { RoundingMode::TowardZero } else { frm };
+        let mut nv = false;
+        let result = match d.vs1 {
+            0x00 | 0x06 => Self::vloop_binary(emu, d, |a, _| Self::vfcvt_to_int(a, sew, sew, false, rm, &mut nv)),
+            0x01 | 0x07 => Self::vloop_binary(emu, d, |a, _| Self::vfcvt_to_int(a, sew, sew, true, rm, &mut nv)),
+            0x02 => Self::vloop_binary(emu, d, |a, _| int_to_fp(a, sew, sew, false, rm)),
+            0x03 => Self::vloop_binary(emu, d, |a, _| int_to_fp(a, sew, sew, true, rm)),
+            // widening : the destination is 2*SEW
+            0x08 | 0x0e => Self::vloop_widen(emu, d, false, |_, a, _| Self::vfcvt_to_int(a, sew, wsew, false, rm, &mut nv)),
+            0x09 | 0x0f => Self::vloop_widen(emu, d, false, |_, a, _| Self::vfcvt_to_int(a, sew, wsew, true, rm, &mut nv)),
+            0x0a => Self::vloop_widen(emu, d, false, |_, a, _| int_to_fp(a, sew, wsew, false, rm)),
+            0x0b => Self::vloop_widen(emu, d, false, |_, a, _| int_to_fp(a, sew, wsew, true, rm)),
+            0x0c => Self::vloop_widen(emu, d, false, |_, a, _| fp_to_fp(a, sew, wsew, rm)),
+            // narrowing : the source is 2*SEW
+            0x10 | 0x16 => Self::vloop_narrow(emu, d, |a, _| Self::vfcvt_to_int(a, wsew, sew, false, rm, &mut nv)),
+            0x11 | 0x17 => Self::vloop_narrow(emu, d, |a, _| Self::vfcvt_to_int(a, wsew, sew, true, rm, &mut nv)),
+            0x12 => Self::vloop_narrow(emu, d, |a, _| int_to_fp(a, wsew, sew, false, rm)),
+            0x13 => Self::vloop_narrow(emu, d, |a, _| int_to_fp(a, wsew, sew, true, rm)),
+            0x14 => Self::vloop_narrow(emu, d, |a, _| fp_to_fp(a, wsew, sew, rm)),
+            0x15 => Self::vloop_narrow(emu, d, |a, _| fp_to_fp_rod(a, wsew, sew)),
+            _ => Err(()),
+        };
+        if nv {
+            *flags |= FFLAGS_NV;
+        }
+        result
+    }
+
+    fn vfcvt_to_int(a: u64, from: usize, to: usize, signed: bool, rm: RoundingMode, nv: &mut bool) -> u64 {
+        let (data, inv) = fp_to_int(a, from, to, signed, rm);
+        *nv |= inv;
+        data
+    }
+}
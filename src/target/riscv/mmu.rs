@@ -0,0 +1,523 @@
+use crate::emu_env::{EmuEnv, MachineEnum};
+use crate::target::riscv::riscv_csr::{CsrAddr};
+use crate::target::riscv::riscv_csr_def;
+use crate::target::riscv::riscv::{ExceptCode, PrivMode};
+use crate::target::riscv::tlb::TlbEntry;
+
+#[derive(Copy, Clone)]
+pub enum MemAccType {
+    Fetch,
+    Write,
+    Read,
+    ReadExec,   // HLVX : a load which needs execute permission
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum TransStage {
+    Single, // satp
+    VS,     // vsatp : page tables live in guest physical memory
+    G,      // hgatp
+}
+
+#[derive(PartialEq, Eq)]
+#[derive(Debug)]
+#[allow(dead_code)]
+pub enum MemResult {
+    NoExcept = 0,
+    MisAlign = 1 << 0,
+    NotDefined = 1 << 1,
+    NewRegion = 1 << 2,
+    TlbError = 1 << 3,
+    AccessFault = 1 << 4,
+}
+
+#[derive(PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum VMMode {
+    Mbare = 0,
+    Sv32 = 1,
+    Sv39 = 8,
+    Sv48 = 9,
+    Sv57 = 10,
+    Sv64 = 11,
+}
+impl VMMode {
+    pub fn from(x: i64) -> VMMode {
+        match x {
+            0 => VMMode::Mbare,
+            1 => VMMode::Sv32,
+            8 => VMMode::Sv39,
+            9 => VMMode::Sv48,
+            10 => VMMode::Sv57,
+            11 => VMMode::Sv64,
+            _ => panic!("Intelnal Error: Unknown VMMode = {:}", x),
+        }
+    }
+}
+
+
+// PTE bits
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+const PTE_X: u64 = 1 << 3;
+const PTE_U: u64 = 1 << 4;
+const PTE_G: u64 = 1 << 5;
+const PTE_A: u64 = 1 << 6;
+const PTE_D: u64 = 1 << 7;
+
+// Guest RAM, backed by EmuEnv::m_guest_mem
+pub const RAM_BASE: u64 = 0x8000_0000;
+pub const RAM_SIZE: u64 = 0x80000;
+
+// Address space tag of a translated block besides an ASID (see fetch_tag())
+pub const TB_TAG_BARE: u64 = u64::MAX;
+pub const TB_TAG_GLOBAL: u64 = u64::MAX - 1;
+
+// Shape of a page table : number of levels, VPN bits per level and PTE size in bytes
+#[derive(Copy, Clone)]
+struct PageTableFormat {
+    levels: u32,
+    vpn_bits: u32,
+    pte_size: u64,
+}
+
+impl EmuEnv {
+
+    pub fn convert_physical_address(&mut self, guest_pc: u64, virtual_addr: u64, size: u64, acc_type: MemAccType) -> Result<u64, MemResult> {
+        let virtual_addr = self.mask_xlen(virtual_addr);
+        let is_fetch_access = match acc_type {
+            MemAccType::Fetch => true,
+            _ => false,
+        };
+
+        let mstatus: i64 = self.m_csr.csrrs(CsrAddr::Mstatus, PrivMode::Machine as i64);
+        let mprv: u8 =
+            Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPRV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPRV_LSB)
+                as u8;
+        let mpp_u8: u8 =
+            Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPP_MSB, riscv_csr_def::SYSREG_MSTATUS_MPP_LSB) as u8;
+        let mpp: PrivMode = PrivMode::from_u8(mpp_u8);
+        let mpv: bool = !self.is_rv32() &&
+            Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MPV_MSB, riscv_csr_def::SYSREG_MSTATUS_MPV_LSB) != 0;
+
+        let (priv_mode, virt) = if !is_fetch_access && (mprv != 0) {
+            (mpp, mpv && mpp != PrivMode::Machine)
+        } else {
+            (self.m_priv, self.m_virt)
+        };
+
+        return self.convert_guest_address(guest_pc, virtual_addr, size, acc_type, priv_mode, virt);
+    }
+
+    // Translate as priv_mode would. With virt (VS/VU-mode, or HLV/HSV), the
+    // VS-stage walks vsatp and its result is a guest physical address, which
+    // the G-stage translates through hgatp.
+    // A physical address outside RAM and MMIO, or denied by PMP, is an access fault.
+    // size is the access width : PMP checks all of [phy_addr, phy_addr + size).
+    pub fn convert_guest_address(&mut self, guest_pc: u64, virtual_addr: u64, size: u64, acc_type: MemAccType,
+        priv_mode: PrivMode, virt: bool) -> Result<u64, MemResult> {
+        let phy_addr = self.translate_address(guest_pc, virtual_addr, acc_type, priv_mode, virt)?;
+        if !self.is_phy_access_allowed(phy_addr, size, acc_type, priv_mode) {
+            if self.m_arg_config.mmu_debug {
+                println!("<Access Fault : 0x{:016x} --> 0x{:016x} is not accessible>", virtual_addr, phy_addr);
+            }
+            return self.access_fault(guest_pc, virtual_addr, acc_type);
+        }
+        return Ok(phy_addr);
+    }
+
+    fn translate_address(&mut self, guest_pc: u64, virtual_addr: u64, acc_type: MemAccType,
+        priv_mode: PrivMode, virt: bool) -> Result<u64, MemResult> {
+        self.m_gpa_fault = None;
+        self.m_fault_gva = virt;
+
+        if self.m_arg_config.mmu_debug { 
+            println!("<Convert_Virtual_Address. virtual_addr={:016x} : priv_mode = {}, virt = {}>",
+                 virtual_addr, priv_mode as u32, virt);
+        }
+
+        if priv_mode == PrivMode::Machine {
+            return Ok(virtual_addr);
+        }
+        if !virt {
+            let satp = self.m_csr.csrrs(CsrAddr::Satp, 0);
+            return self.translate_stage(guest_pc, virtual_addr, acc_type, priv_mode, satp, TransStage::Single);
+        }
+
+        let vsatp = self.m_csr.csrrs(CsrAddr::Vsatp, 0);
+        let guest_phy_addr = self.translate_stage(guest_pc, virtual_addr, acc_type, priv_mode, vsatp, TransStage::VS)?;
+        return self.translate_g_stage(guest_pc, virtual_addr, guest_phy_addr, acc_type);
+    }
+
+    // G-stage : every access is checked as a U-mode access. On failure the
+    // guest physical address is kept for htval/mtval2, and the caller's page
+    // fault is raised as a guest-page fault (see generate_exception()).
+    fn translate_g_stage(&mut self, guest_pc: u64, virtual_addr: u64, guest_phy_addr: u64, acc_type: MemAccType) -> Result<u64, MemResult> {
+        let hgatp = self.m_csr.csrrs(CsrAddr::Hgatp, 0);
+        let result = self.translate_stage(guest_pc, guest_phy_addr, acc_type, PrivMode::User, hgatp, TransStage::G);
+        if let Err(MemResult::TlbError) = result {
+            self.m_gpa_fault = Some(guest_phy_addr);
+            if let MemAccType::Fetch = acc_type {
+                self.generate_exception(guest_pc, ExceptCode::InstPageFault, virtual_addr as i64);
+            }
+        }
+        return result;
+    }
+
+    fn translate_stage(&mut self, guest_pc: u64, virtual_addr: u64, acc_type: MemAccType,
+        priv_mode: PrivMode, atp: i64, stage: TransStage) -> Result<u64, MemResult> {
+        let (vm_mode, root_ppn) = self.get_vm_mode(atp);
+
+        let format = match vm_mode {
+            VMMode::Sv32 => PageTableFormat { levels: 2, vpn_bits: 10, pte_size: 4 },
+            VMMode::Sv39 => PageTableFormat { levels: 3, vpn_bits: 9, pte_size: 8 },
+            VMMode::Sv48 => PageTableFormat { levels: 4, vpn_bits: 9, pte_size: 8 },
+            VMMode::Sv57 => PageTableFormat { levels: 5, vpn_bits: 9, pte_size: 8 },
+            _ => return Ok(virtual_addr),
+        };
+        // satp translations go through the softmmu TLB. A hit is checked
+        // again as the leaf PTE would be, and anything it cannot decide
+        // (denied, or a store to a page not yet dirty) walks the table.
+        let asid = self.get_asid(atp);
+        if stage == TransStage::Single {
+            if let Some(entry) = self.m_stlb.lookup(virtual_addr >> 12, asid) {
+                let is_write_access = match acc_type {
+                    MemAccType::Write => true,
+                    _ => false,
+                };
+                if (entry.dirty || !is_write_access) && self.is_allowed_access(entry.perm, acc_type, priv_mode, stage) {
+                    return Ok((entry.ppn << 12) | (virtual_addr & 0xfff));
+                }
+            }
+        }
+        return self.walk_page_table(guest_pc, virtual_addr, acc_type, priv_mode, stage, root_ppn, asid, format);
+    }
+
+    // Page table walk of the privileged spec (4.3.2). PTEs are read from
+    // guest physical memory, or through the G-stage for the VS-stage.
+    fn walk_page_table(&mut self, guest_pc: u64, virtual_addr: u64, acc_type: MemAccType, priv_mode: PrivMode,
+        stage: TransStage, root_ppn: u64, asid: u64, format: PageTableFormat) -> Result<u64, MemResult> {
+        let is_write_access = match acc_type {
+            MemAccType::Write => true,
+            _ => false,
+        };
+
+        // The x4 G-stage formats widen the root VPN by 2 bits (a 16KiB root table)
+        let root_extra_bits: u32 = if stage == TransStage::G { 2 } else { 0 };
+        let va_bits: u32 = 12 + format.levels * format.vpn_bits;
+        if format.pte_size == 8 {
+            // Sv39 and up : G-stage addresses are zero-extended, others must be
+            // the sign extension of bit va_bits-1.
+            let canonical = if stage == TransStage::G {
+                (virtual_addr >> (va_bits + root_extra_bits)) == 0
+            } else {
+                let upper = (virtual_addr as i64) >> (va_bits - 1);
+                upper == 0 || upper == -1
+            };
+            if !canonical {
+                if self.m_arg_config.mmu_debug {
+                    println!("<Page Table Error : 0x{:016x} is not a canonical address>", virtual_addr);
+                }
+                return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+            }
+        }
+
+        let mut pte_val: u64 = 0;
+        let mut pte_addr: u64 = 0;
+        let mut pte_phy_addr: u64 = 0;
+        let mut table_addr: u64 = root_ppn << 12;
+        let mut level: u32 = format.levels;
+        // G set at any level makes the mapping global
+        let mut global = false;
+
+        while level > 0 {
+            level -= 1;
+            let vpn_len = format.vpn_bits + if level == format.levels - 1 { root_extra_bits } else { 0 };
+            let vpn_i: u64 = (virtual_addr >> (12 + level * format.vpn_bits)) & ((1 << vpn_len) - 1);
+            pte_addr = table_addr + vpn_i * format.pte_size;
+
+            // VS-stage page tables are read through the G-stage as implicit loads
+            pte_phy_addr = if stage == TransStage::VS {
+                let result = self.translate_g_stage(guest_pc, virtual_addr, pte_addr, MemAccType::Read);
+                if let (Err(error), MemAccType::Fetch) = (&result, acc_type) {
+                    self.raise_fetch_fault(guest_pc, virtual_addr, error);
+                }
+                result?
+            } else {
+                pte_addr
+            };
+            // A PTE outside memory or denied by PMP is an access fault of the
+            // original access type. Page table accesses are S-mode accesses.
+            if !self.is_phy_access_allowed(pte_phy_addr, format.pte_size, MemAccType::Read, PrivMode::Supervisor) {
+                if self.m_arg_config.mmu_debug {
+                    println!("<Access Fault : PTE address 0x{:016x} is not accessible>", pte_phy_addr);
+                }
+                return self.access_fault(guest_pc, virtual_addr, acc_type);
+            }
+            pte_val = if format.pte_size == 8 {
+                self.read_mem_8byte(pte_phy_addr)
+            } else {
+                self.read_mem_4byte(pte_phy_addr) as u64
+            };
+
+            if self.m_arg_config.mmu_debug {
+                println!("<Info: VAddr = 0x{:016x} PTEAddr = 0x{:016x} : PPTE = 0x{:016x}>", virtual_addr, pte_addr, pte_val);
+            }
+
+            // pte.v = 0, pte.r = 0 with pte.w = 1, or reserved bits set. Bits 63:54
+            // are reserved as Svnapot and Svpbmt are not implemented.
+            let reserved = format.pte_size == 8 && (pte_val >> 54) != 0;
+            if (pte_val & PTE_V) == 0 || ((pte_val & PTE_R) == 0 && (pte_val & PTE_W) != 0) || reserved {
+                if self.m_arg_config.mmu_debug {
+                    println!("<Page Table Error : 0x{:016x} = 0x{:016x} is not valid Page Table. Generate Exception>",
+                         pte_addr, pte_val);
+                }
+                return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+            }
+
+            global |= (pte_val & PTE_G) != 0;
+            if (pte_val & (PTE_R | PTE_X)) != 0 {
+                break;
+            }
+            // A pointer to the next level : D, A and U are reserved
+            if level == 0 || (pte_val & (PTE_D | PTE_A | PTE_U)) != 0 {
+                if self.m_arg_config.mmu_debug {
+                    println!("<Page Table Error : 0x{:016x} = 0x{:016x} is not a valid pointer>", pte_addr, pte_val);
+                }
+                return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+            }
+            table_addr = Self::pte_ppn(pte_val, format) << 12;
+        }
+
+        if !self.is_allowed_access(((pte_val >> 1) & 0x0f) as u8, acc_type, priv_mode, stage) {
+            if self.m_arg_config.mmu_debug {
+                println!("<Page Access Failed. Allowed Access Failed PTE_VAL={:016x}>", pte_val);
+            }
+            return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+        }
+
+        // A superpage needs ppn[level-1:0] = 0
+        let pte_ppn = Self::pte_ppn(pte_val, format);
+        let superpage_bits = level * format.vpn_bits;
+        if (pte_ppn & ((1 << superpage_bits) - 1)) != 0 {
+            if self.m_arg_config.mmu_debug {
+                println!("<Page Access Failed. Misaligned superpage PTE_VAL={:016x}>", pte_val);
+            }
+            return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+        }
+
+        // A/D : updated in the PTE with Svadu (xenvcfg.ADUE), otherwise a page fault
+        let need_d = is_write_access && (pte_val & PTE_D) == 0;
+        if (pte_val & PTE_A) == 0 || need_d {
+            if !self.is_hw_ad_update(stage) {
+                if self.m_arg_config.mmu_debug {
+                    println!("<Access Fault : A/D bit is not set PTE_VAL={:016x}>", pte_val);
+                }
+                return self.page_fault(guest_pc, virtual_addr, acc_type, stage);
+            }
+            let new_pte = pte_val | PTE_A | if is_write_access { PTE_D } else { 0 };
+            // For the VS-stage the update is an implicit store through the G-stage
+            let pte_store_addr = if stage == TransStage::VS {
+                let result = self.translate_g_stage(guest_pc, virtual_addr, pte_addr, MemAccType::Write);
+                if let (Err(error), MemAccType::Fetch) = (&result, acc_type) {
+                    self.raise_fetch_fault(guest_pc, virtual_addr, error);
+                }
+                result?
+            } else {
+                pte_phy_addr
+            };
+            if !self.is_phy_access_allowed(pte_store_addr, format.pte_size, MemAccType::Write, PrivMode::Supervisor) {
+                return self.access_fault(guest_pc, virtual_addr, acc_type);
+            }
+            if format.pte_size == 8 {
+                self.write_mem_8byte(pte_store_addr, new_pte);
+            } else {
+                self.write_mem_4byte(pte_store_addr, new_pte as u32);
+            }
+        }
+
+        let page_bits = 12 + superpage_bits;
+        let page_mask: u64 = (1 << page_bits) - 1;
+        let phy_addr = ((pte_ppn << 12) & !page_mask) | (virtual_addr & page_mask);
+
+        if self.m_arg_config.mmu_debug {
+            println!("<Converted Virtual Address = {:016x}>", phy_addr);
+        }
+        if stage == TransStage::Single {
+            self.m_stlb.insert(TlbEntry {
+                valid: true,
+                vpn: virtual_addr >> 12,
+                ppn: phy_addr >> 12,
+                asid: asid,
+                global: global,
+                perm: ((pte_val >> 1) & 0x0f) as u8,
+                dirty: is_write_access || (pte_val & PTE_D) != 0,
+            });
+        }
+        return Ok(phy_addr);
+    }
+
+    // PPN field of a PTE : bits 31:10 (Sv32) or 53:10 (Sv39 and up)
+    fn pte_ppn(pte_val: u64, format: PageTableFormat) -> u64 {
+        let ppn_bits = if format.pte_size == 8 { 44 } else { 22 };
+        (pte_val >> 10) & ((1 << ppn_bits) - 1)
+    }
+
+    // Svadu : menvcfg.ADUE for satp and hgatp, henvcfg.ADUE for vsatp
+    fn is_hw_ad_update(&mut self, stage: TransStage) -> bool {
+        let envcfg = if stage == TransStage::VS {
+            self.m_csr.csrrs(CsrAddr::Henvcfg, 0)
+        } else {
+            self.m_csr.csrrs(CsrAddr::Menvcfg, 0)
+        };
+        Self::extract_bit_field(envcfg, riscv_csr_def::SYSREG_ENVCFG_ADUE_MSB, riscv_csr_def::SYSREG_ENVCFG_ADUE_LSB) != 0
+    }
+
+    // Instruction page faults are raised here. Load and store page faults
+    // are raised by the caller, and the G-stage's by translate_g_stage().
+    fn page_fault(&mut self, guest_pc: u64, virtual_addr: u64, acc_type: MemAccType, stage: TransStage) -> Result<u64, MemResult> {
+        match acc_type {
+            MemAccType::Fetch if stage != TransStage::G => {
+                self.generate_exception(guest_pc, ExceptCode::InstPageFault, virtual_addr as i64);
+            }
+            _ => {}
+        };
+        return Err(MemResult::TlbError);
+    }
+
+    // Instruction access faults are raised here, load and store access
+    // faults by the caller.
+    fn access_fault(&mut self, guest_pc: u64, virtual_addr: u64, acc_type: MemAccType) -> Result<u64, MemResult> {
+        if let MemAccType::Fetch = acc_type {
+            self.generate_exception(guest_pc, ExceptCode::InstAccessFault, virtual_addr as i64);
+        }
+        return Err(MemResult::AccessFault);
+    }
+
+    // A fetch whose VS-stage walk failed on an implicit G-stage access
+    fn raise_fetch_fault(&mut self, guest_pc: u64, virtual_addr: u64, error: &MemResult) {
+        let code = match error {
+            MemResult::AccessFault => ExceptCode::InstAccessFault,
+            _ => ExceptCode::InstPageFault,
+        };
+        self.generate_exception(guest_pc, code, virtual_addr as i64);
+    }
+
+    fn is_phy_access_allowed(&self, phy_addr: u64, size: u64, acc_type: MemAccType, priv_mode: PrivMode) -> bool {
+        self.is_phy_addr_valid(phy_addr) && self.m_pmp.is_allowed(phy_addr, size, acc_type, priv_mode)
+    }
+
+    // Guest RAM, and the UART page on sifive_u
+    pub fn is_phy_addr_valid(&self, phy_addr: u64) -> bool {
+        if phy_addr >= RAM_BASE && phy_addr < RAM_BASE + RAM_SIZE {
+            return true;
+        }
+        self.m_arg_config.machine == MachineEnum::RiscvSiFiveU && (phy_addr & !0xfff) == 0x1001_0000
+    }
+
+    // Leaf PTE permissions (R, W, X and U in bits 0-3 of i_type). U-mode
+    // needs a U page. S-mode may load/store a U page only with SUM, and never
+    // executes one. MXR makes executable pages readable : vsstatus.MXR adds to
+    // mstatus.MXR for the VS-stage, and the G-stage only sees mstatus.MXR.
+    // Every G-stage access is a U-mode access.
+    fn is_allowed_access(&mut self, i_type: u8, acc_type: MemAccType, priv_mode: PrivMode, stage: TransStage) -> bool {
+        let readable = (i_type & 0x01) != 0;
+        let writable = (i_type & 0x02) != 0;
+        let executable = (i_type & 0x04) != 0;
+        let user_page = (i_type & 0x08) != 0;
+
+        let mstatus: i64 = self.m_csr.csrrs(CsrAddr::Mstatus, 0);
+        let status: i64 = if stage == TransStage::VS { self.m_csr.csrrs(CsrAddr::Vsstatus, 0) } else { mstatus };
+        let sum = Self::extract_bit_field(status, riscv_csr_def::SYSREG_MSTATUS_SUM_MSB, riscv_csr_def::SYSREG_MSTATUS_SUM_LSB) != 0;
+        let mxr = Self::extract_bit_field(mstatus, riscv_csr_def::SYSREG_MSTATUS_MXR_MSB, riscv_csr_def::SYSREG_MSTATUS_MXR_LSB) != 0 ||
+            Self::extract_bit_field(status, riscv_csr_def::SYSREG_MSTATUS_MXR_MSB, riscv_csr_def::SYSREG_MSTATUS_MXR_LSB) != 0;
+
+        let is_exec = match acc_type {
+            MemAccType::Fetch => true,
+            _ => false,
+        };
+        let priv_allowed = match priv_mode {
+            PrivMode::User => user_page,
+            _ => !user_page || (sum && !is_exec),
+        };
+        if !priv_allowed {
+            return false;
+        }
+        return match acc_type {
+            MemAccType::Fetch | MemAccType::ReadExec => executable,
+            MemAccType::Write => readable && writable,
+            MemAccType::Read => readable || (mxr && executable),
+        };
+    }
+
+    // Bare and Sv32 on RV32. Bare, Sv39, Sv48 and Sv57 (Sv39x4, Sv48x4 and
+    // Sv57x4 for hgatp) on RV64.
+    pub fn is_atp_mode_supported(&self, atp: i64) -> bool {
+        if self.is_rv32() {
+            return true;
+        }
+        match Self::extract_bit_field(atp, 63, 60) {
+            0 | 8 | 9 | 10 => true,
+            _ => false,
+        }
+    }
+
+    // ASID of satp or vsatp (VMID for hgatp) : bits 30:22 on RV32, 59:44 on RV64
+    pub fn get_asid(&self, atp: i64) -> u64 {
+        if self.is_rv32() {
+            Self::extract_bit_field(atp, 30, 22) as u64
+        } else {
+            Self::extract_bit_field(atp, 59, 44) as u64
+        }
+    }
+
+    // Address space instruction fetch currently translates in : TB_TAG_BARE
+    // without translation, otherwise the ASID of satp (vsatp in VS/VU-mode).
+    fn fetch_asid(&self) -> u64 {
+        if self.m_priv == PrivMode::Machine {
+            return TB_TAG_BARE;
+        }
+        let atp = if self.m_virt { self.m_csr.m_vsatp.m_csr } else { self.m_csr.m_satp.m_csr };
+        match self.get_vm_mode(atp).0 {
+            VMMode::Mbare => TB_TAG_BARE,
+            _ => self.get_asid(atp),
+        }
+    }
+
+    // Tag of a translated block starting at pc : the ASID it was fetched
+    // with, TB_TAG_GLOBAL if its page is a global mapping, so that it
+    // survives address space switches, or TB_TAG_BARE.
+    pub fn fetch_tag(&self, pc: u64) -> u64 {
+        let asid = self.fetch_asid();
+        if asid != TB_TAG_BARE && !self.m_virt {
+            if let Some(entry) = self.m_stlb.lookup(self.mask_xlen(pc) >> 12, asid) {
+                if entry.global {
+                    return TB_TAG_GLOBAL;
+                }
+            }
+        }
+        asid
+    }
+
+    pub fn is_fetch_tag_current(&self, tag: u64) -> bool {
+        let asid = self.fetch_asid();
+        tag == asid || (tag == TB_TAG_GLOBAL && asid != TB_TAG_BARE)
+    }
+
+    // MODE and root PPN of satp, vsatp or hgatp
+    fn get_vm_mode(&self, atp: i64) -> (VMMode, u64) {
+        let (mode, ppn) = if self.is_rv32() {
+            (Self::extract_bit_field(atp, 31, 31), Self::extract_bit_field(atp, 21, 0))
+        } else {
+            (Self::extract_bit_field(atp, 63, 60), Self::extract_bit_field(atp, 43, 0))
+        };
+        let v_mode = VMMode::from(mode);
+        if v_mode == VMMode::Mbare || v_mode == VMMode::Sv32 ||
+            v_mode == VMMode::Sv39 || v_mode == VMMode::Sv48 || v_mode == VMMode::Sv57 || v_mode == VMMode::Sv64 {
+            return (v_mode, ppn as u64)
+        } else {
+            panic!("Error: illegal VM Mode in SATP {:}", mode)
+        }
+    }
+}
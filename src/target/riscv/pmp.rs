@@ -0,0 +1,183 @@
+use crate::target::riscv::mmu::MemAccType;
+use crate::target::riscv::riscv::PrivMode;
+
+// pmpcfg fields, one byte per entry
+const PMPCFG_R: u8 = 1 << 0;
+const PMPCFG_W: u8 = 1 << 1;
+const PMPCFG_X: u8 = 1 << 2;
+const PMPCFG_A_LSB: u8 = 3;
+const PMPCFG_L: u8 = 1 << 7;
+const PMPCFG_WMASK: u8 = PMPCFG_L | (0x3 << PMPCFG_A_LSB) | PMPCFG_X | PMPCFG_W | PMPCFG_R;
+
+// pmpcfg.A
+const PMP_A_OFF: u8 = 0;
+const PMP_A_TOR: u8 = 1;
+const PMP_A_NA4: u8 = 2;
+const PMP_A_NAPOT: u8 = 3;
+
+const PMPCFG_BASE: u64 = 0x3a0;
+const PMPADDR_BASE: u64 = 0x3b0;
+const PMPADDR_LAST: u64 = 0x3ef;
+pub const PMP_MAX_ENTRIES: usize = 64;
+
+// Physical Memory Protection with a granularity of 4 bytes (G = 0).
+pub struct Pmp {
+    m_num_entries: usize,
+    m_cfg: [u8; PMP_MAX_ENTRIES],
+    m_addr: [u64; PMP_MAX_ENTRIES],
+}
+
+impl Pmp {
+    pub fn new(num_entries: usize) -> Pmp {
+        assert!(num_entries <= PMP_MAX_ENTRIES);
+        Pmp {
+            m_num_entries: num_entries,
+            m_cfg: [0; PMP_MAX_ENTRIES],
+            m_addr: [0; PMP_MAX_ENTRIES],
+        }
+    }
+
+    pub fn is_pmp_csr(csr_addr: u64) -> bool {
+        csr_addr >= PMPCFG_BASE && csr_addr <= PMPADDR_LAST
+    }
+
+    // The odd pmpcfg CSRs only exist on RV32
+    pub fn is_csr_implemented(csr_addr: u64, rv32: bool) -> bool {
+        Self::is_pmp_csr(csr_addr) && (csr_addr >= PMPADDR_BASE || rv32 || (csr_addr & 1) == 0)
+    }
+
+    // pmpcfgN holds entries 4N..4N+3 on RV32 and 4N..4N+7 on RV64
+    fn cfg_entries(csr_addr: u64, rv32: bool) -> (usize, usize) {
+        let first = ((csr_addr - PMPCFG_BASE) * 4) as usize;
+        (first, if rv32 { 4 } else { 8 })
+    }
+
+    // Entries above the implemented ones read as zero
+    pub fn read_csr(&self, csr_addr: u64, rv32: bool) -> u64 {
+        if csr_addr >= PMPADDR_BASE {
+            return self.m_addr[(csr_addr - PMPADDR_BASE) as usize];
+        }
+        let (first, count) = Self::cfg_entries(csr_addr, rv32);
+        (0..count).fold(0, |cfg, i| cfg | ((self.m_cfg[first + i] as u64) << (i * 8)))
+    }
+
+    // Writes to locked entries, and to pmpaddr[i] when entry i+1 is a locked
+    // TOR entry, are ignored. R=0 with W=1 is reserved : that entry keeps
+    // its configuration.
+    pub fn write_csr(&mut self, csr_addr: u64, data: u64, rv32: bool) {
+        if csr_addr >= PMPADDR_BASE {
+            let idx = (csr_addr - PMPADDR_BASE) as usize;
+            if idx >= self.m_num_entries || self.is_locked(idx) {
+                return;
+            }
+            if idx + 1 < self.m_num_entries && self.is_locked(idx + 1) && self.mode(idx + 1) == PMP_A_TOR {
+                return;
+            }
+            // pmpaddr holds bits 33:2 (RV32) or 55:2 (RV64) of the address
+            let mask: u64 = if rv32 { 0xffff_ffff } else { (1 << 54) - 1 };
+            self.m_addr[idx] = data & mask;
+            return;
+        }
+        let (first, count) = Self::cfg_entries(csr_addr, rv32);
+        for i in 0..count {
+            let idx = first + i;
+            let cfg = ((data >> (i * 8)) as u8) & PMPCFG_WMASK;
+            if idx >= self.m_num_entries || self.is_locked(idx) {
+                continue;
+            }
+            if (cfg & PMPCFG_R) == 0 && (cfg & PMPCFG_W) != 0 {
+                continue;
+            }
+            self.m_cfg[idx] = cfg;
+        }
+    }
+
+    fn is_locked(&self, idx: usize) -> bool {
+        (self.m_cfg[idx] & PMPCFG_L) != 0
+    }
+
+    fn mode(&self, idx: usize) -> u8 {
+        (self.m_cfg[idx] >> PMPCFG_A_LSB) & 0x3
+    }
+
+    // [start, end) of an entry, None if it is off or empty
+    fn range(&self, idx: usize) -> Option<(u64, u64)> {
+        let addr = self.m_addr[idx];
+        let (start, end) = match self.mode(idx) {
+            PMP_A_OFF => return None,
+            PMP_A_TOR => {
+                let start = if idx == 0 { 0 } else { self.m_addr[idx - 1] << 2 };
+                (start, addr << 2)
+            }
+            PMP_A_NA4 => (addr << 2, (addr << 2) + 4),
+            PMP_A_NAPOT => {
+                // NAPOT : the trailing ones of pmpaddr give a 2^(ones+3) byte region
+                let ones = (!addr).trailing_zeros();
+                if ones >= 61 {
+                    (0, u64::MAX)
+                } else {
+                    let size: u64 = 1 << (ones + 3);
+                    let start = (addr << 2) & !(size - 1);
+                    (start, start.saturating_add(size))
+                }
+            }
+            _ => return None,
+        };
+        if start >= end {
+            return None;
+        }
+        Some((start, end))
+    }
+
+    fn is_active(&self) -> bool {
+        (0..self.m_num_entries).any(|idx| self.mode(idx) != PMP_A_OFF)
+    }
+
+    // The lowest-numbered entry matching any byte of [phy_addr, phy_addr +
+    // size) decides, and the access fails if it does not cover every byte.
+    // Unlocked entries do not apply to M-mode. S/U-mode accesses matching no
+    // entry fail once any entry is enabled; before that the whole memory is
+    // accessible.
+    pub fn is_allowed(&self, phy_addr: u64, size: u64, acc_type: MemAccType, priv_mode: PrivMode) -> bool {
+        let acc_end = phy_addr.saturating_add(size);
+        for idx in 0..self.m_num_entries {
+            let (start, end) = match self.range(idx) {
+                Some(range) => range,
+                None => continue,
+            };
+            if acc_end <= start || phy_addr >= end {
+                continue;
+            }
+            if phy_addr < start || acc_end > end {
+                return false;
+            }
+            let cfg = self.m_cfg[idx];
+            if priv_mode == PrivMode::Machine && (cfg & PMPCFG_L) == 0 {
+                return true;
+            }
+            return match acc_type {
+                MemAccType::Fetch => (cfg & PMPCFG_X) != 0,
+                MemAccType::Write => (cfg & PMPCFG_W) != 0,
+                MemAccType::Read | MemAccType::ReadExec => (cfg & PMPCFG_R) != 0,
+            };
+        }
+        priv_mode == PrivMode::Machine || !self.is_active()
+    }
+
+    // Whether every byte of the 4KiB page matches the same entry (or none),
+    // so that a check of one address holds for the whole page.
+    pub fn is_page_uniform(&self, page_addr: u64) -> bool {
+        let page_end = page_addr + 0x1000;
+        for idx in 0..self.m_num_entries {
+            let (start, end) = match self.range(idx) {
+                Some(range) => range,
+                None => continue,
+            };
+            if end <= page_addr || start >= page_end {
+                continue;
+            }
+            return start <= page_addr && end >= page_end;
+        }
+        true
+    }
+}
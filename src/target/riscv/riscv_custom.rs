@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use super::super::super::emu_env::EmuEnv;
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+
+use super::riscv::TranslateRiscv;
+
+pub type HelperFunc = fn(emu: &mut EmuEnv, arg0: u64, arg1: u64, arg2: u64, arg3: u64) -> usize;
+
+// Helper slots reserved for custom instructions, after the built-in helpers.
+pub const CUSTOM_HELPER_NUM: usize = 16;
+
+// A custom instruction, or a group of them, registered with
+// EmuEnv::register_custom_inst(). It claims the 32-bit encodings where
+// (inst & mask()) == match_bits(), which are tried before the standard
+// decoders; normally these lie in the custom-0..3 opcodes.
+pub trait CustomInst {
+    fn name(&self) -> &str;
+    fn mask(&self) -> u32;
+    fn match_bits(&self) -> u32;
+
+    // Runtime helper. It is given a helper index at registration, which is
+    // passed to translate() for gen_custom_helper_call().
+    fn helper(&self) -> Option<HelperFunc> {
+        None
+    }
+
+    // CSRs owned by the extension. They must be in the custom CSR space,
+    // and read as 0 until written.
+    fn csr_range(&self) -> Option<RangeInclusive<u64>> {
+        None
+    }
+
+    // Value stored when the guest writes `new` to one of its CSRs.
+    fn csr_write(&self, _csr_addr: u64, _old: i64, new: i64) -> i64 {
+        new
+    }
+
+    fn translate(&self, trans: &mut TranslateRiscv, inst: &InstrInfo, helper_idx: Option<usize>) -> Vec<TCGOp>;
+
+    fn disassemble(&self, inst: u32) -> String {
+        format!("{} 0x{:08x}", self.name(), inst)
+    }
+}
+
+// Custom read/write and read-only CSRs : 0x800-0x8ff, and 0x_c0-0x_ff in
+// the S, H and M pages.
+pub fn is_custom_csr(csr_addr: u64) -> bool {
+    let page = (csr_addr >> 8) & 0xf;
+    match page {
+        0x8 => true,
+        0x5 | 0x6 | 0x7 | 0x9 | 0xa | 0xb | 0xc | 0xd | 0xe | 0xf => ((csr_addr >> 6) & 0x3) == 0x3,
+        _ => false,
+    }
+}
+
+impl TranslateRiscv {
+    pub fn find_custom_inst(&self, inst: u32) -> Option<(Rc<dyn CustomInst>, Option<usize>)> {
+        if inst & 0x3 != 0x3 {
+            return None;
+        }
+        self.m_custom_inst
+            .iter()
+            .find(|(ext, _)| inst & ext.mask() == ext.match_bits())
+            .map(|(ext, helper_idx)| (Rc::clone(ext), *helper_idx))
+    }
+
+    pub fn find_custom_csr(&self, csr_addr: u64) -> Option<Rc<dyn CustomInst>> {
+        self.m_custom_inst
+            .iter()
+            .find(|(ext, _)| ext.csr_range().map_or(false, |range| range.contains(&csr_addr)))
+            .map(|(ext, _)| Rc::clone(ext))
+    }
+
+    pub fn translate_custom(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        match self.find_custom_inst(inst.inst) {
+            Some((ext, helper_idx)) => ext.translate(self, inst, helper_idx),
+            None => panic!("Custom instruction {:08x} is not registered.", inst.inst),
+        }
+    }
+
+    // Call a custom helper with (inst, rs1, rs2, guest_pc). The helper
+    // returns non-zero when it raised an exception, which leaves the TB.
+    pub fn gen_custom_helper_call(&mut self, helper_idx: usize, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let rs1 = Box::new(TCGv::new_imm(rs1_addr as u64));
+        let rs2 = Box::new(TCGv::new_imm(rs2_addr as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(helper_idx, *tcg_inst, *rs1, *rs2, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+}
@@ -0,0 +1,456 @@
+use super::riscv_inst_id::RiscvInstId;
+
+pub fn decode_inst_ld_10_f3_110_r3_00000_f2_00_r2_00000_r1_00000_rd_00001_op_00000 (_inst: u32) -> Option<(RiscvInstId, usize)> {
+    // RV32 decodes this encoding as C_FLWSP in decode_inst_rv32().
+    return Some((RiscvInstId::C_LDSP, 2));
+
+}
+
+
+pub fn decode_inst_ld_10_f3_110_r3_00000_f2_00_r2_00000_r1_00001_rd_00000_op_00000 (_inst: u32) -> Option<(RiscvInstId, usize)> {
+    // RV32 decodes this encoding as C_FSWSP in decode_inst_rv32().
+    return Some((RiscvInstId::C_SDSP, 2));
+
+}
+
+
+pub fn decode_inst_ld_00_r3_00000_f2_00_r2_00000_r1_00000_f3_110_rd_00000_op_00000 (_inst: u32) -> Option<(RiscvInstId, usize)> {
+    // RV32 decodes this encoding as C_FLW in decode_inst_rv32().
+    return Some((RiscvInstId::C_LD, 2));
+
+}
+
+
+pub fn decode_inst_ld_00_r3_00000_f2_00_r2_00000_r1_00001_f3_110_rd_00000_op_00000 (_inst: u32) -> Option<(RiscvInstId, usize)> {
+    // RV32 decodes this encoding as C_FSW in decode_inst_rv32().
+    return Some((RiscvInstId::C_SD, 2));
+
+}
+
+
+pub fn decode_inst_ld_01_f3_010_r3_00000_f2_00_r2_00000_r1_00000_rd_00000_op_00000 (_inst: u32) -> Option<(RiscvInstId, usize)> {
+    // RV32 decodes this encoding as C_JAL in decode_inst_rv32().
+    return Some((RiscvInstId::C_ADDIW, 2));
+
+}
+
+
+// Hand-written decoder for extensions which the generated decoder does not cover.
+// Tried before decode_inst().
+pub fn decode_inst_ext(inst: u32) -> Option<(RiscvInstId, usize)> {
+    if inst & 0x3 != 0x3 {
+        return decode_inst_zcb(inst).map(|id| (id, 2));
+    }
+    if let Some(id) = decode_inst_cmo(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_h(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_zb(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_zk(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_zfa(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_zfh(inst) {
+        return Some((id, 4));
+    }
+    if let Some(id) = decode_inst_v(inst) {
+        return Some((id, 4));
+    }
+    return None;
+}
+
+
+// Encodings which mean something else when XLEN=32. Tried first in RV32 mode.
+pub fn decode_inst_rv32(inst: u32) -> Option<(RiscvInstId, usize)> {
+    if inst & 0x3 != 0x3 {
+        return match inst & 0xe003 {
+            0x6000 => Some((RiscvInstId::C_FLW, 2)),
+            0xe000 => Some((RiscvInstId::C_FSW, 2)),
+            0x2001 => Some((RiscvInstId::C_JAL, 2)),
+            0x6002 => Some((RiscvInstId::C_FLWSP, 2)),
+            0xe002 => Some((RiscvInstId::C_FSWSP, 2)),
+            _ => None,
+        };
+    }
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+    let imm12 = (inst >> 20) & 0xfff;
+
+    match (opcode, funct3, imm12) {
+        (0x13, 0b101, 0x698) => Some((RiscvInstId::REV8, 4)),
+        (0x33, 0b100, 0x080) => Some((RiscvInstId::ZEXT_H, 4)),
+        // Zfa
+        (0x53, 0b000, 0xe21) => Some((RiscvInstId::FMVH_X_D, 4)),
+        (0x53, 0b000, _) if imm12 >> 5 == 0b1011001 => Some((RiscvInstId::FMVP_D_X, 4)),
+        // Scalar crypto
+        (0x13, 0b001, 0x08f) => Some((RiscvInstId::ZIP, 4)),
+        (0x13, 0b101, 0x08f) => Some((RiscvInstId::UNZIP, 4)),
+        (0x33, 0b000, _) => match (imm12 >> 5) & 0x1f {
+            0b10001 => Some((RiscvInstId::AES32ESI, 4)),
+            0b10011 => Some((RiscvInstId::AES32ESMI, 4)),
+            0b10101 => Some((RiscvInstId::AES32DSI, 4)),
+            0b10111 => Some((RiscvInstId::AES32DSMI, 4)),
+            _ => match imm12 >> 5 {
+                0b0101000 => Some((RiscvInstId::SHA512SUM0R, 4)),
+                0b0101001 => Some((RiscvInstId::SHA512SUM1R, 4)),
+                0b0101010 => Some((RiscvInstId::SHA512SIG0L, 4)),
+                0b0101011 => Some((RiscvInstId::SHA512SIG1L, 4)),
+                0b0101110 => Some((RiscvInstId::SHA512SIG0H, 4)),
+                0b0101111 => Some((RiscvInstId::SHA512SIG1H, 4)),
+                _ => None,
+            },
+        },
+        _ => None,
+    }
+}
+
+
+// cbo.* use MISC-MEM funct3=010. prefetch.* are ORI hints with rd=x0,
+// so they have to be matched before ORI.
+fn decode_inst_cmo(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let rd = (inst >> 7) & 0x1f;
+    let funct3 = (inst >> 12) & 0x7;
+    let imm12 = (inst >> 20) & 0xfff;
+
+    match (opcode, funct3, rd) {
+        (0x0f, 0b010, 0) => match imm12 {
+            0x000 => Some(RiscvInstId::CBO_INVAL),
+            0x001 => Some(RiscvInstId::CBO_CLEAN),
+            0x002 => Some(RiscvInstId::CBO_FLUSH),
+            0x004 => Some(RiscvInstId::CBO_ZERO),
+            _ => None,
+        },
+        (0x13, 0b110, 0) => match imm12 & 0x1f {
+            0b00000 => Some(RiscvInstId::PREFETCH_I),
+            0b00001 => Some(RiscvInstId::PREFETCH_R),
+            0b00011 => Some(RiscvInstId::PREFETCH_W),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+// Hypervisor loads/stores and fences, in the SYSTEM opcode.
+fn decode_inst_h(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let rd = (inst >> 7) & 0x1f;
+    let funct3 = (inst >> 12) & 0x7;
+    let rs2 = (inst >> 20) & 0x1f;
+    let funct7 = (inst >> 25) & 0x7f;
+
+    match (opcode, funct3) {
+        (0x73, 0b100) => match (funct7, rs2) {
+            (0b0110000, 0) => Some(RiscvInstId::HLV_B),
+            (0b0110000, 1) => Some(RiscvInstId::HLV_BU),
+            (0b0110010, 0) => Some(RiscvInstId::HLV_H),
+            (0b0110010, 1) => Some(RiscvInstId::HLV_HU),
+            (0b0110010, 3) => Some(RiscvInstId::HLVX_HU),
+            (0b0110100, 0) => Some(RiscvInstId::HLV_W),
+            (0b0110100, 1) => Some(RiscvInstId::HLV_WU),
+            (0b0110100, 3) => Some(RiscvInstId::HLVX_WU),
+            (0b0110110, 0) => Some(RiscvInstId::HLV_D),
+            (0b0110001, _) if rd == 0 => Some(RiscvInstId::HSV_B),
+            (0b0110011, _) if rd == 0 => Some(RiscvInstId::HSV_H),
+            (0b0110101, _) if rd == 0 => Some(RiscvInstId::HSV_W),
+            (0b0110111, _) if rd == 0 => Some(RiscvInstId::HSV_D),
+            _ => None,
+        },
+        (0x73, 0b000) if rd == 0 => match funct7 {
+            0b0010001 => Some(RiscvInstId::HFENCE_VVMA),
+            0b0110001 => Some(RiscvInstId::HFENCE_GVMA),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+// Zcb lives in encodings the C extension leaves reserved.
+fn decode_inst_zcb(inst: u32) -> Option<RiscvInstId> {
+    let funct6 = (inst >> 10) & 0x3f;
+    let bit6 = (inst >> 6) & 0x1;
+    let funct2 = (inst >> 5) & 0x3;
+    let funct3_low = (inst >> 2) & 0x7;
+
+    match (inst & 0x3, funct6) {
+        (0b00, 0b100000) => Some(RiscvInstId::C_LBU),
+        (0b00, 0b100001) if bit6 == 0 => Some(RiscvInstId::C_LHU),
+        (0b00, 0b100001) => Some(RiscvInstId::C_LH),
+        (0b00, 0b100010) => Some(RiscvInstId::C_SB),
+        (0b00, 0b100011) if bit6 == 0 => Some(RiscvInstId::C_SH),
+        (0b01, 0b100111) => match (funct2, funct3_low) {
+            (0b11, 0b000) => Some(RiscvInstId::C_ZEXT_B),
+            (0b11, 0b001) => Some(RiscvInstId::C_SEXT_B),
+            (0b11, 0b010) => Some(RiscvInstId::C_ZEXT_H),
+            (0b11, 0b011) => Some(RiscvInstId::C_SEXT_H),
+            (0b11, 0b100) => Some(RiscvInstId::C_ZEXT_W),
+            (0b11, 0b101) => Some(RiscvInstId::C_NOT),
+            (0b10, _) => Some(RiscvInstId::C_MUL),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+fn decode_inst_zb(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+    let funct7 = (inst >> 25) & 0x7f;
+    let funct6 = (inst >> 26) & 0x3f;
+    let rs2 = (inst >> 20) & 0x1f;
+    let imm12 = (inst >> 20) & 0xfff;
+
+    match opcode {
+        // OP
+        0x33 => match (funct7, funct3) {
+            (0b0010000, 0b010) => Some(RiscvInstId::SH1ADD),
+            (0b0010000, 0b100) => Some(RiscvInstId::SH2ADD),
+            (0b0010000, 0b110) => Some(RiscvInstId::SH3ADD),
+            (0b0100000, 0b111) => Some(RiscvInstId::ANDN),
+            (0b0100000, 0b110) => Some(RiscvInstId::ORN),
+            (0b0100000, 0b100) => Some(RiscvInstId::XNOR),
+            (0b0000101, 0b100) => Some(RiscvInstId::MIN),
+            (0b0000101, 0b101) => Some(RiscvInstId::MINU),
+            (0b0000101, 0b110) => Some(RiscvInstId::MAX),
+            (0b0000101, 0b111) => Some(RiscvInstId::MAXU),
+            (0b0110000, 0b001) => Some(RiscvInstId::ROL),
+            (0b0110000, 0b101) => Some(RiscvInstId::ROR),
+            (0b0100100, 0b001) => Some(RiscvInstId::BCLR),
+            (0b0100100, 0b101) => Some(RiscvInstId::BEXT),
+            (0b0110100, 0b001) => Some(RiscvInstId::BINV),
+            (0b0010100, 0b001) => Some(RiscvInstId::BSET),
+            (0b0000111, 0b101) => Some(RiscvInstId::CZERO_EQZ),
+            (0b0000111, 0b111) => Some(RiscvInstId::CZERO_NEZ),
+            _ => None,
+        },
+        // OP-32
+        0x3b => match (funct7, funct3) {
+            (0b0000100, 0b000) => Some(RiscvInstId::ADD_UW),
+            (0b0000100, 0b100) if rs2 == 0 => Some(RiscvInstId::ZEXT_H),
+            (0b0010000, 0b010) => Some(RiscvInstId::SH1ADD_UW),
+            (0b0010000, 0b100) => Some(RiscvInstId::SH2ADD_UW),
+            (0b0010000, 0b110) => Some(RiscvInstId::SH3ADD_UW),
+            (0b0110000, 0b001) => Some(RiscvInstId::ROLW),
+            (0b0110000, 0b101) => Some(RiscvInstId::RORW),
+            _ => None,
+        },
+        // OP-IMM
+        0x13 => match funct3 {
+            0b001 => match imm12 {
+                0x600 => Some(RiscvInstId::CLZ),
+                0x601 => Some(RiscvInstId::CTZ),
+                0x602 => Some(RiscvInstId::CPOP),
+                0x604 => Some(RiscvInstId::SEXT_B),
+                0x605 => Some(RiscvInstId::SEXT_H),
+                _ => match funct6 {
+                    0b010010 => Some(RiscvInstId::BCLRI),
+                    0b011010 => Some(RiscvInstId::BINVI),
+                    0b001010 => Some(RiscvInstId::BSETI),
+                    _ => None,
+                },
+            },
+            0b101 => match imm12 {
+                0x287 => Some(RiscvInstId::ORC_B),
+                0x6b8 => Some(RiscvInstId::REV8),
+                _ => match funct6 {
+                    0b011000 => Some(RiscvInstId::RORI),
+                    0b010010 => Some(RiscvInstId::BEXTI),
+                    _ => None,
+                },
+            },
+            _ => None,
+        },
+        // OP-IMM-32
+        0x1b => match funct3 {
+            0b001 => match imm12 {
+                0x600 => Some(RiscvInstId::CLZW),
+                0x601 => Some(RiscvInstId::CTZW),
+                0x602 => Some(RiscvInstId::CPOPW),
+                _ if funct6 == 0b000010 => Some(RiscvInstId::SLLI_UW),
+                _ => None,
+            },
+            0b101 if funct7 == 0b0110000 => Some(RiscvInstId::RORIW),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+// Zbkb/Zbkc/Zbkx/Zkn/Zks. The RV32-only forms (aes32*, sha512*r/h/l,
+// zip/unzip) are in decode_inst_rv32().
+fn decode_inst_zk(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+    let funct7 = (inst >> 25) & 0x7f;
+    let rs2 = (inst >> 20) & 0x1f;
+    let imm12 = (inst >> 20) & 0xfff;
+
+    match opcode {
+        // OP
+        0x33 => match (funct7, funct3) {
+            (0b0000101, 0b001) => Some(RiscvInstId::CLMUL),
+            (0b0000101, 0b011) => Some(RiscvInstId::CLMULH),
+            (0b0010100, 0b100) => Some(RiscvInstId::XPERM8),
+            (0b0010100, 0b010) => Some(RiscvInstId::XPERM4),
+            (0b0000100, 0b100) => Some(RiscvInstId::PACK),
+            (0b0000100, 0b111) => Some(RiscvInstId::PACKH),
+            (0b0011001, 0b000) => Some(RiscvInstId::AES64ES),
+            (0b0011011, 0b000) => Some(RiscvInstId::AES64ESM),
+            (0b0011101, 0b000) => Some(RiscvInstId::AES64DS),
+            (0b0011111, 0b000) => Some(RiscvInstId::AES64DSM),
+            (0b0111111, 0b000) => Some(RiscvInstId::AES64KS2),
+            // bs is in funct7[6:5]
+            (_, 0b000) if funct7 & 0x1f == 0b11000 => Some(RiscvInstId::SM4ED),
+            (_, 0b000) if funct7 & 0x1f == 0b11010 => Some(RiscvInstId::SM4KS),
+            _ => None,
+        },
+        // OP-32. rs2 == 0 is zext.h.
+        0x3b => match (funct7, funct3) {
+            (0b0000100, 0b100) if rs2 != 0 => Some(RiscvInstId::PACKW),
+            _ => None,
+        },
+        // OP-IMM
+        0x13 => match funct3 {
+            0b001 => match imm12 {
+                0x100 => Some(RiscvInstId::SHA256SUM0),
+                0x101 => Some(RiscvInstId::SHA256SUM1),
+                0x102 => Some(RiscvInstId::SHA256SIG0),
+                0x103 => Some(RiscvInstId::SHA256SIG1),
+                0x104 => Some(RiscvInstId::SHA512SUM0),
+                0x105 => Some(RiscvInstId::SHA512SUM1),
+                0x106 => Some(RiscvInstId::SHA512SIG0),
+                0x107 => Some(RiscvInstId::SHA512SIG1),
+                0x108 => Some(RiscvInstId::SM3P0),
+                0x109 => Some(RiscvInstId::SM3P1),
+                0x300 => Some(RiscvInstId::AES64IM),
+                _ if imm12 >> 4 == 0x31 && imm12 & 0xf <= 0xa => Some(RiscvInstId::AES64KS1I),
+                _ => None,
+            },
+            0b101 if imm12 == 0x687 => Some(RiscvInstId::BREV8),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+// Zfa for single and double precision. fmvh.x.d and fmvp.d.x only exist
+// on RV32, so they are in decode_inst_rv32().
+fn decode_inst_zfa(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+    let funct7 = (inst >> 25) & 0x7f;
+    let rs2 = (inst >> 20) & 0x1f;
+
+    match opcode {
+        // OP-FP
+        0x53 => match (funct7, rs2, funct3) {
+            (0b1111000, 0b00001, 0b000) => Some(RiscvInstId::FLI_S),
+            (0b0010100, _, 0b010) => Some(RiscvInstId::FMINM_S),
+            (0b0010100, _, 0b011) => Some(RiscvInstId::FMAXM_S),
+            (0b0100000, 0b00100, _) => Some(RiscvInstId::FROUND_S),
+            (0b0100000, 0b00101, _) => Some(RiscvInstId::FROUNDNX_S),
+            (0b1010000, _, 0b100) => Some(RiscvInstId::FLEQ_S),
+            (0b1010000, _, 0b101) => Some(RiscvInstId::FLTQ_S),
+            (0b1111001, 0b00001, 0b000) => Some(RiscvInstId::FLI_D),
+            (0b0010101, _, 0b010) => Some(RiscvInstId::FMINM_D),
+            (0b0010101, _, 0b011) => Some(RiscvInstId::FMAXM_D),
+            (0b0100001, 0b00100, _) => Some(RiscvInstId::FROUND_D),
+            (0b0100001, 0b00101, _) => Some(RiscvInstId::FROUNDNX_D),
+            (0b1010001, _, 0b100) => Some(RiscvInstId::FLEQ_D),
+            (0b1010001, _, 0b101) => Some(RiscvInstId::FLTQ_D),
+            (0b1100001, 0b01000, 0b001) => Some(RiscvInstId::FCVTMOD_W_D),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+fn decode_inst_zfh(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+    let funct7 = (inst >> 25) & 0x7f;
+    let fmt = (inst >> 25) & 0x3;
+    let rs2 = (inst >> 20) & 0x1f;
+
+    match opcode {
+        // LOAD-FP / STORE-FP
+        0x07 if funct3 == 0b001 => Some(RiscvInstId::FLH),
+        0x27 if funct3 == 0b001 => Some(RiscvInstId::FSH),
+        // MADD / MSUB / NMSUB / NMADD
+        0x43 if fmt == 0b10 => Some(RiscvInstId::FMADD_H),
+        0x47 if fmt == 0b10 => Some(RiscvInstId::FMSUB_H),
+        0x4b if fmt == 0b10 => Some(RiscvInstId::FNMSUB_H),
+        0x4f if fmt == 0b10 => Some(RiscvInstId::FNMADD_H),
+        // OP-FP
+        0x53 => match (funct7, rs2, funct3) {
+            (0b0000010, _, _) => Some(RiscvInstId::FADD_H),
+            (0b0000110, _, _) => Some(RiscvInstId::FSUB_H),
+            (0b0001010, _, _) => Some(RiscvInstId::FMUL_H),
+            (0b0001110, _, _) => Some(RiscvInstId::FDIV_H),
+            (0b0101110, 0b00000, _) => Some(RiscvInstId::FSQRT_H),
+            (0b0010010, _, 0b000) => Some(RiscvInstId::FSGNJ_H),
+            (0b0010010, _, 0b001) => Some(RiscvInstId::FSGNJN_H),
+            (0b0010010, _, 0b010) => Some(RiscvInstId::FSGNJX_H),
+            (0b0010110, _, 0b000) => Some(RiscvInstId::FMIN_H),
+            (0b0010110, _, 0b001) => Some(RiscvInstId::FMAX_H),
+            (0b0100000, 0b00010, _) => Some(RiscvInstId::FCVT_S_H),
+            (0b0100010, 0b00000, _) => Some(RiscvInstId::FCVT_H_S),
+            (0b0100001, 0b00010, _) => Some(RiscvInstId::FCVT_D_H),
+            (0b0100010, 0b00001, _) => Some(RiscvInstId::FCVT_H_D),
+            (0b1100010, 0b00000, _) => Some(RiscvInstId::FCVT_W_H),
+            (0b1100010, 0b00001, _) => Some(RiscvInstId::FCVT_WU_H),
+            (0b1100010, 0b00010, _) => Some(RiscvInstId::FCVT_L_H),
+            (0b1100010, 0b00011, _) => Some(RiscvInstId::FCVT_LU_H),
+            (0b1101010, 0b00000, _) => Some(RiscvInstId::FCVT_H_W),
+            (0b1101010, 0b00001, _) => Some(RiscvInstId::FCVT_H_WU),
+            (0b1101010, 0b00010, _) => Some(RiscvInstId::FCVT_H_L),
+            (0b1101010, 0b00011, _) => Some(RiscvInstId::FCVT_H_LU),
+            (0b1110010, 0b00000, 0b000) => Some(RiscvInstId::FMV_X_H),
+            (0b1110010, 0b00000, 0b001) => Some(RiscvInstId::FCLASS_H),
+            (0b1111010, 0b00000, 0b000) => Some(RiscvInstId::FMV_H_X),
+            (0b1010010, _, 0b010) => Some(RiscvInstId::FEQ_H),
+            (0b1010010, _, 0b001) => Some(RiscvInstId::FLT_H),
+            (0b1010010, _, 0b000) => Some(RiscvInstId::FLE_H),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+fn decode_inst_v(inst: u32) -> Option<RiscvInstId> {
+    let opcode = inst & 0x7f;
+    let funct3 = (inst >> 12) & 0x7;
+
+    match opcode {
+        // LOAD-FP / STORE-FP with a vector element width
+        0x07 if funct3 == 0b000 || funct3 >= 0b101 => Some(RiscvInstId::VLOAD),
+        0x27 if funct3 == 0b000 || funct3 >= 0b101 => Some(RiscvInstId::VSTORE),
+        // OP-V
+        0x57 if funct3 == 0b111 => {
+            if inst >> 31 == 0 {
+                Some(RiscvInstId::VSETVLI)
+            } else if inst >> 30 == 0b11 {
+                Some(RiscvInstId::VSETIVLI)
+            } else if (inst >> 25) & 0x3f == 0 {
+                Some(RiscvInstId::VSETVL)
+            } else {
+                None
+            }
+        }
+        0x57 => Some(RiscvInstId::VARITH),
+        _ => None,
+    }
+}
@@ -0,0 +1,397 @@
+/* CAUTION! THIS SOURCE CODE IS GENERATED AUTOMATICALLY. DON'T MODIFY BY HAND. */
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[allow(non_camel_case_types)]
+pub enum RiscvInstId {
+    LUI,
+    AUIPC,
+    JAL,
+    JALR,
+    BEQ,
+    BNE,
+    BLT,
+    BGE,
+    BLTU,
+    BGEU,
+    LB,
+    LH,
+    LW,
+    LBU,
+    LHU,
+    SB,
+    SH,
+    SW,
+    ADDI,
+    SLTI,
+    SLTIU,
+    XORI,
+    ORI,
+    ANDI,
+    SLLI,
+    SRLI,
+    SRAI,
+    ADD,
+    SUB,
+    SLL,
+    SLT,
+    SLTU,
+    XOR,
+    SRL,
+    SRA,
+    OR,
+    AND,
+    FENCE,
+    FENCE_I,
+    MUL,
+    MULH,
+    MULHSU,
+    MULHU,
+    DIV,
+    DIVU,
+    REM,
+    REMU,
+    LR_W,
+    SC_W,
+    AMOSWAP_W,
+    AMOADD_W,
+    AMOXOR_W,
+    AMOAND_W,
+    AMOOR_W,
+    AMOMIN_W,
+    AMOMAX_W,
+    AMOMINU_W,
+    AMOMAXU_W,
+    FLW,
+    FSW,
+    FMADD_S,
+    FMSUB_S,
+    FNMSUB_S,
+    FNMADD_S,
+    FADD_S,
+    FSUB_S,
+    FMUL_S,
+    FDIV_S,
+    FSQRT_S,
+    FSGNJ_S,
+    FSGNJN_S,
+    FSGNJX_S,
+    FMIN_S,
+    FMAX_S,
+    FCVT_W_S,
+    FCVT_WU_S,
+    FMV_X_W,
+    FEQ_S,
+    FLT_S,
+    FLE_S,
+    FCLASS_S,
+    FCVT_S_W,
+    FCVT_S_WU,
+    FMV_W_X,
+    FLD,
+    FSD,
+    FMADD_D,
+    FMSUB_D,
+    FNMSUB_D,
+    FNMADD_D,
+    FADD_D,
+    FSUB_D,
+    FMUL_D,
+    FDIV_D,
+    FSQRT_D,
+    FSGNJ_D,
+    FSGNJN_D,
+    FSGNJX_D,
+    FMIN_D,
+    FMAX_D,
+    FCVT_S_D,
+    FCVT_D_S,
+    FEQ_D,
+    FLT_D,
+    FLE_D,
+    FCLASS_D,
+    FCVT_W_D,
+    FCVT_WU_D,
+    FCVT_D_W,
+    FCVT_D_WU,
+    CSRRW,
+    CSRRS,
+    CSRRC,
+    CSRRWI,
+    CSRRSI,
+    CSRRCI,
+    ECALL,
+    EBREAK,
+    URET,
+    SRET,
+    HRET,
+    MRET,
+    MRTS,
+    MRTH,
+    WFI,
+    SFENCE_VMA,
+    LWU,
+    LD,
+    SD,
+    ADDIW,
+    SLLIW,
+    SRLIW,
+    SRAIW,
+    ADDW,
+    SUBW,
+    SLLW,
+    SRLW,
+    SRAW,
+    MULW,
+    DIVW,
+    DIVUW,
+    REMW,
+    REMUW,
+    LR_D,
+    SC_D,
+    AMOSWAP_D,
+    AMOADD_D,
+    AMOXOR_D,
+    AMOAND_D,
+    AMOOR_D,
+    AMOMIN_D,
+    AMOMAX_D,
+    AMOMINU_D,
+    AMOMAXU_D,
+    FCVT_L_S,
+    FCVT_LU_S,
+    FCVT_S_L,
+    FCVT_S_LU,
+    FCVT_L_D,
+    FCVT_LU_D,
+    FMV_X_D,
+    FCVT_D_L,
+    FCVT_D_LU,
+    FMV_D_X,
+    C_ADDI4SPN,
+    C_FLD,
+    C_LW,
+    C_FLW,
+    C_LD,
+    C_FSD,
+    C_SW,
+    C_FSW,
+    C_SD,
+    C_NOP,
+    C_ADDI,
+    C_JAL,
+    C_ADDIW,
+    C_LI,
+    C_ADDI16SP,
+    C_LUI,
+    C_SRLI,
+    C_SRLI64,
+    C_SRAI,
+    C_SRAI64,
+    C_ANDI,
+    C_SUB,
+    C_XOR,
+    C_OR,
+    C_AND,
+    C_SUBW,
+    C_ADDW,
+    C_J,
+    C_BEQZ,
+    C_BNEZ,
+    C_SLLI,
+    C_FLDSP,
+    C_LWSP,
+    C_FLWSP,
+    C_LDSP,
+    C_JR,
+    C_MV,
+    C_EBREAK,
+    C_JALR,
+    C_ADD,
+    C_FSDSP,
+    C_SWSP,
+    C_FSWSP,
+    C_SDSP,
+    // Zcb
+    C_LBU,
+    C_LHU,
+    C_LH,
+    C_SB,
+    C_SH,
+    C_ZEXT_B,
+    C_SEXT_B,
+    C_ZEXT_H,
+    C_SEXT_H,
+    C_ZEXT_W,
+    C_NOT,
+    C_MUL,
+    SH1ADD,
+    SH2ADD,
+    SH3ADD,
+    ADD_UW,
+    SH1ADD_UW,
+    SH2ADD_UW,
+    SH3ADD_UW,
+    SLLI_UW,
+    ANDN,
+    ORN,
+    XNOR,
+    CLZ,
+    CLZW,
+    CTZ,
+    CTZW,
+    CPOP,
+    CPOPW,
+    MAX,
+    MAXU,
+    MIN,
+    MINU,
+    SEXT_B,
+    SEXT_H,
+    ZEXT_H,
+    ROL,
+    ROLW,
+    ROR,
+    RORI,
+    RORIW,
+    RORW,
+    ORC_B,
+    REV8,
+    BCLR,
+    BCLRI,
+    BEXT,
+    BEXTI,
+    BINV,
+    BINVI,
+    BSET,
+    BSETI,
+    FLH,
+    FSH,
+    FMADD_H,
+    FMSUB_H,
+    FNMSUB_H,
+    FNMADD_H,
+    FADD_H,
+    FSUB_H,
+    FMUL_H,
+    FDIV_H,
+    FSQRT_H,
+    FSGNJ_H,
+    FSGNJN_H,
+    FSGNJX_H,
+    FMIN_H,
+    FMAX_H,
+    FCVT_S_H,
+    FCVT_H_S,
+    FCVT_D_H,
+    FCVT_H_D,
+    FCVT_W_H,
+    FCVT_WU_H,
+    FCVT_H_W,
+    FCVT_H_WU,
+    FCVT_L_H,
+    FCVT_LU_H,
+    FCVT_H_L,
+    FCVT_H_LU,
+    FMV_X_H,
+    FMV_H_X,
+    FEQ_H,
+    FLT_H,
+    FLE_H,
+    FCLASS_H,
+    // Zicond
+    CZERO_EQZ,
+    CZERO_NEZ,
+    // Zicbom / Zicboz / Zicbop
+    CBO_INVAL,
+    CBO_CLEAN,
+    CBO_FLUSH,
+    CBO_ZERO,
+    PREFETCH_I,
+    PREFETCH_R,
+    PREFETCH_W,
+    // Hypervisor
+    HLV_B,
+    HLV_BU,
+    HLV_H,
+    HLV_HU,
+    HLVX_HU,
+    HLV_W,
+    HLV_WU,
+    HLVX_WU,
+    HLV_D,
+    HSV_B,
+    HSV_H,
+    HSV_W,
+    HSV_D,
+    HFENCE_VVMA,
+    HFENCE_GVMA,
+    // Zfa
+    FLI_S,
+    FMINM_S,
+    FMAXM_S,
+    FROUND_S,
+    FROUNDNX_S,
+    FLEQ_S,
+    FLTQ_S,
+    FLI_D,
+    FMINM_D,
+    FMAXM_D,
+    FROUND_D,
+    FROUNDNX_D,
+    FLEQ_D,
+    FLTQ_D,
+    FCVTMOD_W_D,
+    FMVH_X_D,
+    FMVP_D_X,
+    // Scalar crypto
+    AES64ES,
+    AES64ESM,
+    AES64DS,
+    AES64DSM,
+    AES64IM,
+    AES64KS1I,
+    AES64KS2,
+    AES32ESI,
+    AES32ESMI,
+    AES32DSI,
+    AES32DSMI,
+    SHA256SIG0,
+    SHA256SIG1,
+    SHA256SUM0,
+    SHA256SUM1,
+    SHA512SIG0,
+    SHA512SIG1,
+    SHA512SUM0,
+    SHA512SUM1,
+    SHA512SIG0H,
+    SHA512SIG0L,
+    SHA512SIG1H,
+    SHA512SIG1L,
+    SHA512SUM0R,
+    SHA512SUM1R,
+    SM3P0,
+    SM3P1,
+    SM4ED,
+    SM4KS,
+    CLMUL,
+    CLMULH,
+    XPERM4,
+    XPERM8,
+    PACK,
+    PACKH,
+    PACKW,
+    BREV8,
+    ZIP,
+    UNZIP,
+    // Registered through riscv_custom::CustomInst
+    CUSTOM,
+    // Vector : loads, stores and OP-V arithmetic are dispatched to one
+    // helper each, which decodes the remaining fields.
+    VSETVLI,
+    VSETIVLI,
+    VSETVL,
+    VLOAD,
+    VSTORE,
+    VARITH,
+}
@@ -0,0 +1,102 @@
+// Softmmu TLB behind the inline TLB : single-stage (satp) translations of
+// 4KiB pages, tagged with the ASID they were walked with. Entries keep the
+// leaf PTE permissions, so a hit is checked again for the access type and
+// privilege at hand, and a store to a page whose D bit is clear walks again.
+
+use crate::target::riscv::riscv::PrivMode;
+
+// Inline TLB : TLB_SIZE entries indexed by VA bits 23:12, one table per
+// privilege mode for loads and another for stores, so that an entry filled
+// by a load never lets a store through a read-only page.
+pub const TLB_SIZE: usize = 4096;
+pub const TLB_SLOTS: usize = 8;
+
+// An inline TLB tag is VA bits 63:24 in bits 39:0 and the ASID the entry was
+// filled with from bit TLB_ASID_SHIFT. Bits 42:40 of the tag a load or store
+// compares hold its misaligned offset bits, which no entry has.
+pub const TLB_ASID_SHIFT: u64 = 44;
+
+pub fn tlb_tag_asid(tag: u64) -> u64 {
+    tag >> TLB_ASID_SHIFT
+}
+
+pub fn tlb_slot(priv_mode: PrivMode, is_store: bool) -> usize {
+    (priv_mode as usize) * 2 + (is_store as usize)
+}
+
+const STLB_SETS: usize = 256;
+const STLB_WAYS: usize = 4;
+
+#[derive(Copy, Clone)]
+pub struct TlbEntry {
+    pub valid: bool,
+    pub vpn: u64,
+    pub ppn: u64,
+    pub asid: u64,
+    pub global: bool,
+    pub perm: u8,   // R, W, X and U of the leaf PTE in bits 0-3
+    pub dirty: bool,
+}
+
+impl TlbEntry {
+    const INVALID: TlbEntry = TlbEntry { valid: false, vpn: 0, ppn: 0, asid: 0, global: false, perm: 0, dirty: false };
+
+    fn is_match(&self, vpn: u64, asid: u64) -> bool {
+        self.valid && self.vpn == vpn && (self.global || self.asid == asid)
+    }
+}
+
+pub struct SoftTlb {
+    m_entries: Vec<TlbEntry>,
+    m_victim: [usize; STLB_SETS],
+}
+
+impl SoftTlb {
+    pub fn new() -> SoftTlb {
+        SoftTlb {
+            m_entries: vec![TlbEntry::INVALID; STLB_SETS * STLB_WAYS],
+            m_victim: [0; STLB_SETS],
+        }
+    }
+
+    fn set_range(vpn: u64) -> std::ops::Range<usize> {
+        let set = (vpn as usize) % STLB_SETS;
+        set * STLB_WAYS..(set + 1) * STLB_WAYS
+    }
+
+    pub fn lookup(&self, vpn: u64, asid: u64) -> Option<TlbEntry> {
+        self.m_entries[Self::set_range(vpn)].iter().find(|e| e.is_match(vpn, asid)).copied()
+    }
+
+    // Replaces the entry of the same page if there is one, otherwise a
+    // free way, otherwise the ways of a set are evicted in turn.
+    pub fn insert(&mut self, entry: TlbEntry) {
+        let range = Self::set_range(entry.vpn);
+        let set = range.start / STLB_WAYS;
+        let ways = &mut self.m_entries[range];
+        let way = match ways.iter().position(|e| e.is_match(entry.vpn, entry.asid) && e.global == entry.global) {
+            Some(way) => way,
+            None => match ways.iter().position(|e| !e.valid) {
+                Some(way) => way,
+                None => {
+                    let way = self.m_victim[set];
+                    self.m_victim[set] = (way + 1) % STLB_WAYS;
+                    way
+                }
+            },
+        };
+        ways[way] = entry;
+    }
+
+    // sfence.vma : by page (vpn), by address space (asid, sparing global
+    // entries), by both, or everything.
+    pub fn flush(&mut self, vpn: Option<u64>, asid: Option<u64>) {
+        for entry in self.m_entries.iter_mut() {
+            let vpn_match = vpn.map_or(true, |vpn| entry.vpn == vpn);
+            let asid_match = asid.map_or(true, |asid| !entry.global && entry.asid == asid);
+            if vpn_match && asid_match {
+                entry.valid = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGvType, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    /* Zba : address generation */
+    fn translate_shadd(&mut self, shamt: u64, uw: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+        if uw {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(32)));
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source1, source1, TCGv::new_imm(32 - shamt)));
+        } else if shamt != 0 {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(shamt)));
+        }
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::ADD_64BIT, source1, source1, source2));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_sh1add(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(1, false, inst) }
+    pub fn translate_sh2add(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(2, false, inst) }
+    pub fn translate_sh3add(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(3, false, inst) }
+    pub fn translate_add_uw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(0, true, inst) }
+    pub fn translate_sh1add_uw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(1, true, inst) }
+    pub fn translate_sh2add_uw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(2, true, inst) }
+    pub fn translate_sh3add_uw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shadd(3, true, inst) }
+
+    pub fn translate_slli_uw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let imm_const: u64 = ((inst.inst >> 20) & 0x3f) as u64;
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(32)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source1, source1, TCGv::new_imm(32)));
+        if imm_const != 0 {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(imm_const)));
+        }
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    /* Zbb : logical with negate */
+    fn translate_logic_not(&mut self, op: TCGOpcode, invert_rs2: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+        if invert_rs2 {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::XOR_64BIT, source2, source2, TCGv::new_imm(u64::MAX)));
+            tcg_list.push(TCGOp::new_3op(op, source1, source1, source2));
+        } else {
+            tcg_list.push(TCGOp::new_3op(op, source1, source1, source2));
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::XOR_64BIT, source1, source1, TCGv::new_imm(u64::MAX)));
+        }
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_andn(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_logic_not(TCGOpcode::AND_64BIT, true, inst) }
+    pub fn translate_orn(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_logic_not(TCGOpcode::OR_64BIT, true, inst) }
+    pub fn translate_xnor(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_logic_not(TCGOpcode::XOR_64BIT, false, inst) }
+
+    /* Zbb : count bits, byte-reverse */
+    fn translate_unary(&mut self, op: TCGOpcode, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::new_2op(op, source1, source1));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_clz(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CLZ_64BIT, inst) }
+    pub fn translate_clzw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CLZ_32BIT, inst) }
+    pub fn translate_ctz(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CTZ_64BIT, inst) }
+    pub fn translate_ctzw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CTZ_32BIT, inst) }
+    pub fn translate_cpop(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CPOP_64BIT, inst) }
+    pub fn translate_cpopw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::CPOP_32BIT, inst) }
+    pub fn translate_rev8(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_unary(TCGOpcode::BSWAP_64BIT, inst) }
+
+    /* Zbb : sign / zero extension */
+    fn translate_ext(&mut self, op: TCGOpcode, shamt: u64, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(shamt)));
+        tcg_list.push(TCGOp::new_3op(op, source1, source1, TCGv::new_imm(shamt)));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_sext_b(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_ext(TCGOpcode::SRA_64BIT, 56, inst) }
+    pub fn translate_sext_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_ext(TCGOpcode::SRA_64BIT, 48, inst) }
+    pub fn translate_zext_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_ext(TCGOpcode::SRL_64BIT, 48, inst) }
+
+    /* Zbb : integer minimum / maximum */
+    fn translate_minmax(&mut self, op: TCGOpcode, is_max: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let source1 = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+        // Keep rs1 when it already wins the comparison.
+        if is_max {
+            tcg_list.push(TCGOp::new_4op(op, source1, source2, TCGv::new_imm(0), Rc::clone(&label)));
+        } else {
+            tcg_list.push(TCGOp::new_4op(op, source2, source1, TCGv::new_imm(0), Rc::clone(&label)));
+        }
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source2));
+        tcg_list.push(TCGOp::new_label(Rc::clone(&label)));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_min(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_minmax(TCGOpcode::GE_64BIT, false, inst) }
+    pub fn translate_minu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_minmax(TCGOpcode::GEU_64BIT, false, inst) }
+    pub fn translate_max(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_minmax(TCGOpcode::GE_64BIT, true, inst) }
+    pub fn translate_maxu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_minmax(TCGOpcode::GEU_64BIT, true, inst) }
+
+    /* Zbb : bitwise rotation */
+    pub fn translate_rol(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_r(TCGOpcode::ROL_64BIT, inst) }
+    pub fn translate_ror(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_r(TCGOpcode::ROR_64BIT, inst) }
+    pub fn translate_rori(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_i(TCGOpcode::ROR_64BIT, inst) }
+    pub fn translate_rolw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_r(TCGOpcode::ROL_32BIT, inst) }
+    pub fn translate_rorw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_r(TCGOpcode::ROR_32BIT, inst) }
+    pub fn translate_roriw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_shift_i(TCGOpcode::ROR_32BIT, inst) }
+
+    /* Zbb : OR-combine, byte granule */
+    pub fn translate_orc_b(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+        let mask = self.tcg_temp_new();
+        let result = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        // Set bit 7 of every non-zero byte, then spread it across the byte.
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(result, rs1_addr));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::MOV_IMM_64BIT, mask, TCGv::new_imm(0x7f7f_7f7f_7f7f_7f7f)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::AND_64BIT, result, result, mask));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::ADD_64BIT, result, result, mask));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::OR_64BIT, result, result, source1));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::MOV_IMM_64BIT, mask, TCGv::new_imm(0x8080_8080_8080_8080)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::AND_64BIT, result, result, mask));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, mask, result, TCGv::new_imm(7)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, result, result, TCGv::new_imm(1)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SUB_64BIT, result, result, mask));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, result));
+
+        self.tcg_temp_free(result);
+        self.tcg_temp_free(mask);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    /* Zbs : single-bit operations */
+    fn translate_bit_r(&mut self, op: TCGOpcode, invert: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        // The shift amount is moved into RCX, so allocate it last.
+        let source1 = self.tcg_temp_new();
+        let mask = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+        if self.m_rv32 {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::AND_64BIT, source2, source2, TCGv::new_imm(0x1f)));
+        }
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::MOV_IMM_64BIT, mask, TCGv::new_imm(1)));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, mask, mask, source2));
+        if invert {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::XOR_64BIT, mask, mask, TCGv::new_imm(u64::MAX)));
+        }
+        tcg_list.push(TCGOp::new_3op(op, source1, source1, mask));
+        if self.m_rv32 {
+            tcg_list.push(TCGOp::new_2op(TCGOpcode::SIGN_EXT_32_64, source1, source1));
+        }
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(mask);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    fn translate_bit_i(&mut self, op: TCGOpcode, invert: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let imm_const: u64 = ((inst.inst >> 20) & 0x3f) as u64;
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let bit = if invert { !(1 << imm_const) } else { 1 << imm_const };
+
+        let source1 = self.tcg_temp_new();
+        let mask = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::MOV_IMM_64BIT, mask, TCGv::new_imm(bit)));
+        tcg_list.push(TCGOp::new_3op(op, source1, source1, mask));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(mask);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_bset(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_r(TCGOpcode::OR_64BIT, false, inst) }
+    pub fn translate_bclr(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_r(TCGOpcode::AND_64BIT, true, inst) }
+    pub fn translate_binv(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_r(TCGOpcode::XOR_64BIT, false, inst) }
+    pub fn translate_bseti(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_i(TCGOpcode::OR_64BIT, false, inst) }
+    pub fn translate_bclri(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_i(TCGOpcode::AND_64BIT, true, inst) }
+    pub fn translate_binvi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_bit_i(TCGOpcode::XOR_64BIT, false, inst) }
+
+    fn translate_bit_extract(&mut self, shamt: TCGv, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        if shamt.t == TCGvType::Immediate {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source1, source1, shamt));
+        } else {
+            tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+            if self.m_rv32 {
+                tcg_list.push(TCGOp::new_3op(TCGOpcode::AND_64BIT, source2, source2, TCGv::new_imm(0x1f)));
+            }
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source1, source1, source2));
+        }
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::AND_64BIT, source1, source1, TCGv::new_imm(1)));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    pub fn translate_bext(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_bit_extract(TCGv::new_reg(0), inst)
+    }
+
+    pub fn translate_bexti(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst >> 20) & 0x3f) as u64;
+        self.translate_bit_extract(TCGv::new_imm(imm_const), inst)
+    }
+}
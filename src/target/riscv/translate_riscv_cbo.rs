@@ -0,0 +1,38 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv::CALL_HELPER_IDX;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    // cbo.* : the helper checks the envcfg enables and the address, and
+    // performs cbo.zero. The TB is left when it raises an exception.
+    pub fn translate_cbo(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let rs1 = Box::new(TCGv::new_reg(rs1_addr as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CBO_IDX as usize, *tcg_inst, *rs1, TCGv::new_imm(0), *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    // prefetch.* are hints; there is no cache to fill.
+    pub fn translate_prefetch(&mut self, _inst: &InstrInfo) -> Vec<TCGOp> {
+        vec![]
+    }
+}
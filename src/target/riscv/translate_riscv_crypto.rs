@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv::{CALL_HELPER_IDX, CallCryptoIdx};
+
+use super::super::super::get_rs1_addr;
+use super::super::super::get_rs2_addr;
+use super::super::super::get_rd_addr;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    fn translate_crypto(&mut self, crypto_idx: CallCryptoIdx, inst: &InstrInfo) -> Vec<TCGOp> {
+        let crypto_helper_idx = TCGv::new_imm(crypto_idx as u64);
+        let tcg_inst = TCGv::new_imm(inst.inst as u64);
+
+        let tcg_call_op = TCGOp::new_helper_call_arg2(CALL_HELPER_IDX::CALL_CRYPTO_IDX as usize, crypto_helper_idx, tcg_inst);
+        vec![tcg_call_op]
+    }
+
+    // The host op skips the helper call when the host has AES-NI/PCLMULQDQ.
+    fn translate_crypto_sse(&mut self, sse_op: TCGOpcode, crypto_idx: CallCryptoIdx, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = TCGv::new_reg(get_rs1_addr!(inst.inst) as u64);
+        let rs2 = TCGv::new_reg(get_rs2_addr!(inst.inst) as u64);
+        let rd = TCGv::new_reg(get_rd_addr!(inst.inst) as u64);
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let sse_op = TCGOp::new_4op(sse_op, rd, rs1, rs2, Rc::clone(&label));
+        let mut tcg_list = vec![sse_op];
+        tcg_list.append(&mut self.translate_crypto(crypto_idx, inst));
+        tcg_list.push(TCGOp::new_label(Rc::clone(&label)));
+        tcg_list
+    }
+
+    /* Zkne / Zknd */
+    pub fn translate_aes64es (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto_sse(TCGOpcode::AES64ES, CallCryptoIdx::AES64ES, inst) }
+    pub fn translate_aes64esm(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto_sse(TCGOpcode::AES64ESM, CallCryptoIdx::AES64ESM, inst) }
+    pub fn translate_aes64ds (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto_sse(TCGOpcode::AES64DS, CallCryptoIdx::AES64DS, inst) }
+    pub fn translate_aes64dsm(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto_sse(TCGOpcode::AES64DSM, CallCryptoIdx::AES64DSM, inst) }
+    pub fn translate_aes64im (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto_sse(TCGOpcode::AES64IM, CallCryptoIdx::AES64IM, inst) }
+    pub fn translate_aes64ks1i(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES64KS1I, inst) }
+    pub fn translate_aes64ks2(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES64KS2, inst) }
+    pub fn translate_aes32esi (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES32ESI, inst) }
+    pub fn translate_aes32esmi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES32ESMI, inst) }
+    pub fn translate_aes32dsi (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES32DSI, inst) }
+    pub fn translate_aes32dsmi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::AES32DSMI, inst) }
+
+    /* Zknh */
+    pub fn translate_sha256sig0(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA256SIG0, inst) }
+    pub fn translate_sha256sig1(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA256SIG1, inst) }
+    pub fn translate_sha256sum0(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA256SUM0, inst) }
+    pub fn translate_sha256sum1(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA256SUM1, inst) }
+    pub fn translate_sha512sig0(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG0, inst) }
+    pub fn translate_sha512sig1(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG1, inst) }
+    pub fn translate_sha512sum0(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SUM0, inst) }
+    pub fn translate_sha512sum1(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SUM1, inst) }
+    pub fn translate_sha512sig0h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG0H, inst) }
+    pub fn translate_sha512sig0l(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG0L, inst) }
+    pub fn translate_sha512sig1h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG1H, inst) }
+    pub fn translate_sha512sig1l(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SIG1L, inst) }
+    pub fn translate_sha512sum0r(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SUM0R, inst) }
+    pub fn translate_sha512sum1r(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SHA512SUM1R, inst) }
+
+    /* Zksh / Zksed */
+    pub fn translate_sm3p0(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SM3P0, inst) }
+    pub fn translate_sm3p1(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SM3P1, inst) }
+    pub fn translate_sm4ed(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SM4ED, inst) }
+    pub fn translate_sm4ks(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::SM4KS, inst) }
+
+    /* Zbkc / Zbkx */
+    pub fn translate_clmul(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        if self.m_rv32 {
+            return self.translate_crypto(CallCryptoIdx::CLMUL, inst);
+        }
+        self.translate_crypto_sse(TCGOpcode::CLMUL_64BIT, CallCryptoIdx::CLMUL, inst)
+    }
+
+    pub fn translate_clmulh(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        if self.m_rv32 {
+            return self.translate_crypto(CallCryptoIdx::CLMULH, inst);
+        }
+        self.translate_crypto_sse(TCGOpcode::CLMULH_64BIT, CallCryptoIdx::CLMULH, inst)
+    }
+
+    pub fn translate_xperm4(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::XPERM4, inst) }
+    pub fn translate_xperm8(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::XPERM8, inst) }
+
+    /* Zbkb */
+    pub fn translate_pack (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::PACK, inst) }
+    pub fn translate_packh(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::PACKH, inst) }
+    pub fn translate_packw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::PACKW, inst) }
+    pub fn translate_brev8(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::BREV8, inst) }
+    pub fn translate_zip  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::ZIP, inst) }
+    pub fn translate_unzip(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_crypto(CallCryptoIdx::UNZIP, inst) }
+}
@@ -0,0 +1,652 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv::{CALL_HELPER_IDX, CallFcvtIdx};
+use super::riscv_inst_id::RiscvInstId;
+
+use super::super::super::get_rs1_addr;
+use super::super::super::get_rs2_addr;
+use super::super::super::get_rs3_addr;
+use super::super::super::get_rd_addr;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    pub fn translate_fld(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst as i32) >> 20) as u64;
+        
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_LOAD64_IDX as usize, *rd, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));        
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+
+        // Self::translate_float_rri(TCGOpcode::LOAD_FLOAT_64BIT, inst)
+    }
+    pub fn translate_flw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst as i32) >> 20) as u64;
+        
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_LOAD32_IDX as usize, *rd, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));        
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+
+        // Self::translate_float_rri(TCGOpcode::LOAD_FLOAT_32BIT, inst)
+    }
+    pub fn translate_fsd(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst as i32) >> 20) as u64;
+        
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_STORE64_IDX as usize, *rd, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));        
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+
+        // Self::translate_store(TCGOpcode::STORE_FLOAT_64BIT, inst)
+    }
+    pub fn translate_fsw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst as i32) >> 20) as u64;
+        
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_STORE32_IDX as usize, *rd, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));        
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+
+        // Self::translate_store(TCGOpcode::STORE_FLOAT_32BIT, inst)
+    }
+
+    pub fn translate_fadd_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FADD_64BIT, CALL_HELPER_IDX::CALL_FADD_D_IDX, inst)
+    }
+
+    pub fn translate_fsub_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FSUB_64BIT, CALL_HELPER_IDX::CALL_FSUB_D_IDX, inst)
+    }
+
+    pub fn translate_fmul_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FMUL_64BIT, CALL_HELPER_IDX::CALL_FMUL_D_IDX, inst)
+    }
+
+    pub fn translate_fmadd_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FMADD_D_IDX, inst)
+    }
+
+    pub fn translate_fmsub_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FMSUB_D_IDX, inst)
+    }
+
+    pub fn translate_fnmsub_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMSUB_D_IDX, inst)
+    }
+
+    pub fn translate_fnmadd_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMADD_D_IDX, inst)
+    }
+
+    pub fn translate_fdiv_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FDIV_64BIT, CALL_HELPER_IDX::CALL_FDIV_D_IDX, inst)
+    }
+
+    pub fn translate_fsqrt_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FSQRT_64BIT, CALL_HELPER_IDX::CALL_FSQRT_D_IDX, inst)
+    }
+
+    pub fn translate_fmv_x_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_d = TCGOp::new_2op(TCGOpcode::MOVE_TO_INT_FROM_FLOAT, *rd, *rs1);
+        vec![mov_x_d]
+    }
+
+
+    pub fn translate_fmv_d_x(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_d = TCGOp::new_2op(TCGOpcode::MOVE_TO_FLOAT_FROM_INT, *rd, *rs1);
+        vec![mov_x_d]
+    }
+
+    pub fn translate_feq_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FEQ_D_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+    pub fn translate_flt_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FLT_D_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+    pub fn translate_fle_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FLE_D_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fclass_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg2(CALL_HELPER_IDX::CALL_FCLASS_D_IDX as usize, *rd, *rs1);
+        vec![op]
+    }
+
+    pub fn translate_fmax_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_minmax_sse(TCGOpcode::FMAX_64BIT, CALL_HELPER_IDX::CALL_FMAX_D_IDX, inst)
+    }
+
+    pub fn translate_fmin_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_minmax_sse(TCGOpcode::FMIN_64BIT, CALL_HELPER_IDX::CALL_FMIN_D_IDX, inst)
+    }
+
+    pub fn translate_fsgnj_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_3op(TCGOpcode::SGNJ_64BIT, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fsgnjn_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_3op(TCGOpcode::SGNJN_64BIT, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fsgnjx_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_3op(TCGOpcode::SGNJX_64BIT, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fadd_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FADD_32BIT, CALL_HELPER_IDX::CALL_FADD_S_IDX, inst)
+    }
+
+    pub fn translate_fsub_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FSUB_32BIT, CALL_HELPER_IDX::CALL_FSUB_S_IDX, inst)
+    }
+
+    pub fn translate_fmul_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FMUL_32BIT, CALL_HELPER_IDX::CALL_FMUL_S_IDX, inst)
+    }
+
+    pub fn translate_fmadd_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FMADD_S_IDX, inst)
+    }
+
+    pub fn translate_fmsub_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FMSUB_S_IDX, inst)
+    }
+
+    pub fn translate_fnmsub_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMSUB_S_IDX, inst)
+    }
+
+    pub fn translate_fnmadd_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMADD_S_IDX, inst)
+    }
+
+    pub fn translate_fdiv_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FDIV_32BIT, CALL_HELPER_IDX::CALL_FDIV_S_IDX, inst)
+    }
+
+    pub fn translate_fsqrt_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_sse(TCGOpcode::FSQRT_32BIT, CALL_HELPER_IDX::CALL_FSQRT_S_IDX, inst)
+    }
+
+    pub fn translate_fmv_x_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_s = TCGOp::new_2op(TCGOpcode::MOVE_TO_INT_FROM_FLOAT, *rd, *rs1);
+        vec![mov_x_s]
+    }
+
+
+    pub fn translate_fmv_s_x(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_s = TCGOp::new_2op(TCGOpcode::MOVE_TO_FLOAT_FROM_INT, *rd, *rs1);
+        vec![mov_x_s]
+    }
+
+    pub fn translate_feq_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FEQ_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+    pub fn translate_flt_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FLT_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+    pub fn translate_fle_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FLE_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fclass_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg2(CALL_HELPER_IDX::CALL_FCLASS_S_IDX as usize, *rd, *rs1);
+        vec![op]
+    }
+
+    pub fn translate_fmv_x_w(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_d = TCGOp::new_2op(TCGOpcode::MOVE_TO_INT_FROM_FLOAT_32BIT, *rd, *rs1);
+        vec![mov_x_d]
+    }
+
+
+    pub fn translate_fmv_w_x(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let mov_x_d = TCGOp::new_2op(TCGOpcode::MOVE_TO_FLOAT_FROM_INT_32BIT, *rd, *rs1);
+        vec![mov_x_d]
+    }
+
+    pub fn translate_fmax_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_minmax_sse(TCGOpcode::FMAX_32BIT, CALL_HELPER_IDX::CALL_FMAX_S_IDX, inst)
+    }
+
+    pub fn translate_fmin_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        self.translate_float_minmax_sse(TCGOpcode::FMIN_32BIT, CALL_HELPER_IDX::CALL_FMIN_S_IDX, inst)
+    }
+
+    pub fn translate_fsgnj_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FSGNJ_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fsgnjn_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FSGNJN_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fsgnjx_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let op = TCGOp::new_helper_call_arg3(CALL_HELPER_IDX::CALL_FSGNJX_S_IDX as usize, *rd, *rs1, *rs2);
+        vec![op]
+    }
+
+    pub fn translate_fcvt_w_s (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::W_S, inst) }
+
+    pub fn translate_fcvt_wu_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::WU_S, inst) }
+
+    pub fn translate_fcvt_s_w (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_W, inst) }
+
+    pub fn translate_fcvt_s_wu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_WU, inst) }
+
+    pub fn translate_fcvt_s_d (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_D, inst) }
+
+    pub fn translate_fcvt_d_s (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_S, inst) }
+
+    pub fn translate_fcvt_w_d (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::W_D, inst) }
+
+    pub fn translate_fcvt_wu_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::WU_D, inst) }
+
+    pub fn translate_fcvt_d_w (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_W, inst) }
+
+    pub fn translate_fcvt_d_wu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_WU, inst) }
+
+    pub fn translate_fcvt_l_s (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::L_S, inst) }
+
+    pub fn translate_fcvt_lu_s(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::LU_S, inst) }
+
+    pub fn translate_fcvt_s_l (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_L, inst) }
+
+    pub fn translate_fcvt_s_lu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_LU, inst) }
+
+    pub fn translate_fcvt_l_d (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::L_D, inst) }
+
+    pub fn translate_fcvt_lu_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::LU_D, inst) }
+
+    pub fn translate_fcvt_d_l (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_L, inst) }
+
+    pub fn translate_fcvt_d_lu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_LU, inst) }
+
+    /* Zfh : half-precision */
+    pub fn translate_flh(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = ((inst.inst as i32) >> 20) as u64;
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_LOAD16_IDX as usize, *rd, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    pub fn translate_fsh(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let imm_const: u64 = (((inst.inst >> 25) & 0x7f) << 5 | ((inst.inst >> 7) & 0x1f)) as u64;
+        let imm_const = ((imm_const as i64) << (64 - 12)) >> (64 - 12);
+
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let imm = Box::new(TCGv::new_imm(imm_const as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst) as u64));
+
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FLOAT_STORE16_IDX as usize, *rs2, *rs1, *imm, *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *rs1, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    fn translate_float_h_rrr(&mut self, helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = TCGv::new_reg(get_rs1_addr!(inst.inst) as u64);
+        let rs2 = TCGv::new_reg(get_rs2_addr!(inst.inst) as u64);
+        let rd  = TCGv::new_reg(get_rd_addr!(inst.inst) as u64);
+
+        vec![TCGOp::new_helper_call_arg3(helper_idx as usize, rd, rs1, rs2)]
+    }
+
+    fn translate_float_h_rr(&mut self, helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = TCGv::new_reg(get_rs1_addr!(inst.inst) as u64);
+        let rd  = TCGv::new_reg(get_rd_addr!(inst.inst) as u64);
+
+        vec![TCGOp::new_helper_call_arg2(helper_idx as usize, rd, rs1)]
+    }
+
+    // FP operations which round : the helper decodes the registers and rm
+    // from the instruction, and leaves the TB if rm is reserved.
+    fn translate_float_rm(&mut self, helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let tcg_inst = TCGv::new_imm(inst.inst as u64);
+        let tcg_inst_addr = TCGv::new_imm(inst.addr);
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(helper_idx as usize, tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), tcg_inst_addr);
+        Self::gen_float_exit_check(tcg_call_op)
+    }
+
+    // Inline SSE2 operation, with the helper as the slow path. The inline
+    // code jumps over the helper call unless the result needs softfloat.
+    fn translate_float_sse(&mut self, sse_op: TCGOpcode, helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = TCGv::new_reg(get_rs1_addr!(inst.inst) as u64);
+        let rs2 = TCGv::new_reg(get_rs2_addr!(inst.inst) as u64);
+        let rd = TCGv::new_reg(get_rd_addr!(inst.inst) as u64);
+        let rm = TCGv::new_imm(((inst.inst >> 12) & 0x7) as u64);
+        let tcg_inst = TCGv::new_imm(inst.inst as u64);
+        let tcg_inst_addr = TCGv::new_imm(inst.addr);
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let sse_op = TCGOp::new_4op_with_label(sse_op, rd, rs1, rs2, rm, Rc::clone(&label));
+        let tcg_call_op = TCGOp::new_helper_call_arg4(helper_idx as usize, tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![sse_op, tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    fn translate_float_minmax_sse(&mut self, sse_op: TCGOpcode, helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst)as u64));
+        let rd = Box::new(TCGv::new_reg(get_rd_addr!(inst.inst) as u64));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let sse_op = TCGOp::new_4op(sse_op, *rd, *rs1, *rs2, Rc::clone(&label));
+        let op = TCGOp::new_helper_call_arg3(helper_idx as usize, *rd, *rs1, *rs2);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![sse_op, op, tcg_set_label]
+    }
+
+    fn translate_fcvt(&mut self, fcvt_idx: CallFcvtIdx, inst: &InstrInfo) -> Vec<TCGOp> {
+        let fcvt_helper_idx = TCGv::new_imm(fcvt_idx as u64);
+        let tcg_inst = TCGv::new_imm(inst.inst as u64);
+        let tcg_inst_addr = TCGv::new_imm(inst.addr);
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FCVT_IDX as usize, fcvt_helper_idx, tcg_inst, TCGv::new_imm(0), tcg_inst_addr);
+        Self::gen_float_exit_check(tcg_call_op)
+    }
+
+    fn gen_float_exit_check(helper_op: TCGOp) -> Vec<TCGOp> {
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![helper_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    pub fn translate_fadd_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FADD_H_IDX, inst) }
+    pub fn translate_fsub_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FSUB_H_IDX, inst) }
+    pub fn translate_fmul_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FMUL_H_IDX, inst) }
+    pub fn translate_fdiv_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FDIV_H_IDX, inst) }
+    pub fn translate_fmadd_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FMADD_H_IDX, inst) }
+    pub fn translate_fmsub_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FMSUB_H_IDX, inst) }
+    pub fn translate_fnmsub_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMSUB_H_IDX, inst) }
+    pub fn translate_fnmadd_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FNMADD_H_IDX, inst) }
+    pub fn translate_fsqrt_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FSQRT_H_IDX, inst) }
+    pub fn translate_fsgnj_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FSGNJ_H_IDX, inst) }
+    pub fn translate_fsgnjn_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FSGNJN_H_IDX, inst) }
+    pub fn translate_fsgnjx_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FSGNJX_H_IDX, inst) }
+    pub fn translate_fmin_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMIN_H_IDX, inst) }
+    pub fn translate_fmax_h  (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMAX_H_IDX, inst) }
+    pub fn translate_feq_h   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FEQ_H_IDX, inst) }
+    pub fn translate_flt_h   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLT_H_IDX, inst) }
+    pub fn translate_fle_h   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLE_H_IDX, inst) }
+    pub fn translate_fclass_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FCLASS_H_IDX, inst) }
+    pub fn translate_fmv_x_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FMV_X_H_IDX, inst) }
+    pub fn translate_fmv_h_x (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FMV_H_X_IDX, inst) }
+
+    pub fn translate_fcvt_s_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::S_H , inst) }
+    pub fn translate_fcvt_h_s (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_S , inst) }
+    pub fn translate_fcvt_d_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::D_H , inst) }
+    pub fn translate_fcvt_h_d (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_D , inst) }
+    pub fn translate_fcvt_w_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::W_H , inst) }
+    pub fn translate_fcvt_wu_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::WU_H, inst) }
+    pub fn translate_fcvt_h_w (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_W , inst) }
+    pub fn translate_fcvt_h_wu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_WU, inst) }
+    pub fn translate_fcvt_l_h (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::L_H , inst) }
+    pub fn translate_fcvt_lu_h(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::LU_H, inst) }
+    pub fn translate_fcvt_h_l (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_L , inst) }
+    pub fn translate_fcvt_h_lu(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_fcvt(CallFcvtIdx::H_LU, inst) }
+
+    /* Zfa : fli passes the constant index in rs1 */
+    pub fn translate_fli_s      (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FLI_S_IDX, inst) }
+    pub fn translate_fminm_s    (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMINM_S_IDX, inst) }
+    pub fn translate_fmaxm_s    (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMAXM_S_IDX, inst) }
+    pub fn translate_fround_s   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FROUND_S_IDX, inst) }
+    pub fn translate_froundnx_s (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FROUNDNX_S_IDX, inst) }
+    pub fn translate_fleq_s     (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLEQ_S_IDX, inst) }
+    pub fn translate_fltq_s     (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLTQ_S_IDX, inst) }
+    pub fn translate_fli_d      (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FLI_D_IDX, inst) }
+    pub fn translate_fminm_d    (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMINM_D_IDX, inst) }
+    pub fn translate_fmaxm_d    (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMAXM_D_IDX, inst) }
+    pub fn translate_fround_d   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FROUND_D_IDX, inst) }
+    pub fn translate_froundnx_d (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_rm(CALL_HELPER_IDX::CALL_FROUNDNX_D_IDX, inst) }
+    pub fn translate_fleq_d     (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLEQ_D_IDX, inst) }
+    pub fn translate_fltq_d     (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FLTQ_D_IDX, inst) }
+    pub fn translate_fcvtmod_w_d(&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FCVTMOD_W_D_IDX, inst) }
+    pub fn translate_fmvh_x_d   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rr(CALL_HELPER_IDX::CALL_FMVH_X_D_IDX, inst) }
+    pub fn translate_fmvp_d_x   (&mut self, inst: &InstrInfo) -> Vec<TCGOp> { self.translate_float_h_rrr(CALL_HELPER_IDX::CALL_FMVP_D_X_IDX, inst) }
+
+    // F, D, Zfh, Zfa and C.F* instructions, which need mstatus.FS != Off.
+    pub fn is_fp_inst(id: RiscvInstId) -> bool {
+        match id {
+            RiscvInstId::FLW | RiscvInstId::FSW | RiscvInstId::FLD | RiscvInstId::FSD | RiscvInstId::FLH | RiscvInstId::FSH |
+            RiscvInstId::C_FLD | RiscvInstId::C_FLW | RiscvInstId::C_FSD | RiscvInstId::C_FSW |
+            RiscvInstId::C_FLDSP | RiscvInstId::C_FLWSP | RiscvInstId::C_FSDSP | RiscvInstId::C_FSWSP |
+            RiscvInstId::FMADD_S | RiscvInstId::FMSUB_S | RiscvInstId::FNMSUB_S | RiscvInstId::FNMADD_S |
+            RiscvInstId::FADD_S | RiscvInstId::FSUB_S | RiscvInstId::FMUL_S | RiscvInstId::FDIV_S | RiscvInstId::FSQRT_S |
+            RiscvInstId::FSGNJ_S | RiscvInstId::FSGNJN_S | RiscvInstId::FSGNJX_S | RiscvInstId::FMIN_S | RiscvInstId::FMAX_S |
+            RiscvInstId::FCVT_W_S | RiscvInstId::FCVT_WU_S | RiscvInstId::FCVT_S_W | RiscvInstId::FCVT_S_WU |
+            RiscvInstId::FCVT_L_S | RiscvInstId::FCVT_LU_S | RiscvInstId::FCVT_S_L | RiscvInstId::FCVT_S_LU |
+            RiscvInstId::FMV_X_W | RiscvInstId::FMV_W_X | RiscvInstId::FEQ_S | RiscvInstId::FLT_S | RiscvInstId::FLE_S | RiscvInstId::FCLASS_S |
+            RiscvInstId::FMADD_D | RiscvInstId::FMSUB_D | RiscvInstId::FNMSUB_D | RiscvInstId::FNMADD_D |
+            RiscvInstId::FADD_D | RiscvInstId::FSUB_D | RiscvInstId::FMUL_D | RiscvInstId::FDIV_D | RiscvInstId::FSQRT_D |
+            RiscvInstId::FSGNJ_D | RiscvInstId::FSGNJN_D | RiscvInstId::FSGNJX_D | RiscvInstId::FMIN_D | RiscvInstId::FMAX_D |
+            RiscvInstId::FCVT_S_D | RiscvInstId::FCVT_D_S |
+            RiscvInstId::FCVT_W_D | RiscvInstId::FCVT_WU_D | RiscvInstId::FCVT_D_W | RiscvInstId::FCVT_D_WU |
+            RiscvInstId::FCVT_L_D | RiscvInstId::FCVT_LU_D | RiscvInstId::FCVT_D_L | RiscvInstId::FCVT_D_LU |
+            RiscvInstId::FMV_X_D | RiscvInstId::FMV_D_X | RiscvInstId::FEQ_D | RiscvInstId::FLT_D | RiscvInstId::FLE_D | RiscvInstId::FCLASS_D |
+            RiscvInstId::FMADD_H | RiscvInstId::FMSUB_H | RiscvInstId::FNMSUB_H | RiscvInstId::FNMADD_H |
+            RiscvInstId::FADD_H | RiscvInstId::FSUB_H | RiscvInstId::FMUL_H | RiscvInstId::FDIV_H | RiscvInstId::FSQRT_H |
+            RiscvInstId::FSGNJ_H | RiscvInstId::FSGNJN_H | RiscvInstId::FSGNJX_H | RiscvInstId::FMIN_H | RiscvInstId::FMAX_H |
+            RiscvInstId::FCVT_S_H | RiscvInstId::FCVT_H_S | RiscvInstId::FCVT_D_H | RiscvInstId::FCVT_H_D |
+            RiscvInstId::FCVT_W_H | RiscvInstId::FCVT_WU_H | RiscvInstId::FCVT_H_W | RiscvInstId::FCVT_H_WU |
+            RiscvInstId::FCVT_L_H | RiscvInstId::FCVT_LU_H | RiscvInstId::FCVT_H_L | RiscvInstId::FCVT_H_LU |
+            RiscvInstId::FMV_X_H | RiscvInstId::FMV_H_X | RiscvInstId::FEQ_H | RiscvInstId::FLT_H | RiscvInstId::FLE_H | RiscvInstId::FCLASS_H |
+            RiscvInstId::FLI_S | RiscvInstId::FMINM_S | RiscvInstId::FMAXM_S | RiscvInstId::FROUND_S | RiscvInstId::FROUNDNX_S | RiscvInstId::FLEQ_S | RiscvInstId::FLTQ_S |
+            RiscvInstId::FLI_D | RiscvInstId::FMINM_D | RiscvInstId::FMAXM_D | RiscvInstId::FROUND_D | RiscvInstId::FROUNDNX_D | RiscvInstId::FLEQ_D | RiscvInstId::FLTQ_D |
+            RiscvInstId::FCVTMOD_W_D | RiscvInstId::FMVH_X_D | RiscvInstId::FMVP_D_X => true,
+            _ => false,
+        }
+    }
+
+    // FP instructions that may write an f register or fflags. Stores, FMV.X.*
+    // and FCLASS leave the FP state untouched.
+    pub fn is_fp_write_inst(id: RiscvInstId) -> bool {
+        match id {
+            RiscvInstId::FSW | RiscvInstId::FSD | RiscvInstId::FSH |
+            RiscvInstId::C_FSD | RiscvInstId::C_FSW | RiscvInstId::C_FSDSP | RiscvInstId::C_FSWSP |
+            RiscvInstId::FMV_X_W | RiscvInstId::FMV_X_D | RiscvInstId::FMV_X_H | RiscvInstId::FMVH_X_D |
+            RiscvInstId::FCLASS_S | RiscvInstId::FCLASS_D | RiscvInstId::FCLASS_H => false,
+            id => Self::is_fp_inst(id),
+        }
+    }
+
+    pub fn gen_fs_dirty() -> TCGOp {
+        TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_FS_DIRTY_IDX as usize,
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0))
+    }
+}
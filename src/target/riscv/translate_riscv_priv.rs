@@ -0,0 +1,172 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv::CALL_HELPER_IDX;
+
+use super::super::super::get_rs1_addr;
+use super::super::super::get_rd_addr;
+use super::super::super::get_rs2_addr;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    pub fn translate_csrrw(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_reg(rs1_addr as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRW_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+        Self::gen_csr_exit_check(csr_op)
+    }
+    pub fn translate_csrrs(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_reg(rs1_addr as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRS_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+
+        Self::gen_csr_exit_check(csr_op)
+    }
+    pub fn translate_csrrc(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_reg(rs1_addr as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRC_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+        Self::gen_csr_exit_check(csr_op)
+    }
+    pub fn translate_csrrwi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_imm: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_imm(rs1_imm as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRWI_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+
+        Self::gen_csr_exit_check(csr_op)
+    }
+    pub fn translate_csrrsi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_imm: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_imm(rs1_imm as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRSI_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+        Self::gen_csr_exit_check(csr_op)
+    }
+    pub fn translate_csrrci(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_imm: usize = get_rs1_addr!(inst.inst) as usize;
+        let rd_addr: usize = get_rd_addr!(inst.inst) as usize;
+        let csr_const: u64 = get_imm12!(inst.inst);
+
+        let rs1 = Box::new(TCGv::new_imm(rs1_imm as u64));
+        let rd = Box::new(TCGv::new_reg(rd_addr as u64));
+        let csr = Box::new(TCGv::new_imm(csr_const));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let csr_op =
+            TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_CSRRCI_IDX as usize, *rd, *rs1, *csr, *tcg_inst_addr);
+        Self::gen_csr_exit_check(csr_op)
+    }
+
+    pub fn translate_fence(&mut self, _inst: &InstrInfo) -> Vec<TCGOp> {
+        vec![]
+    }
+    pub fn translate_fence_i(&mut self, _inst: &InstrInfo) -> Vec<TCGOp> {
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        vec![exit_tb]
+    }
+    pub fn translate_sfence_vma(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1 = Box::new(TCGv::new_reg(get_rs1_addr!(inst.inst) as u64));
+        let rs2 = Box::new(TCGv::new_reg(get_rs2_addr!(inst.inst) as u64));
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+        let op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_SFENCE_VMA_IDX as usize, *rs1, *rs2, *tcg_inst, *tcg_inst_addr);
+        Self::gen_csr_exit_check(op)
+    }
+    pub fn translate_wfi(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::gen_hyp_helper(CALL_HELPER_IDX::CALL_WFI_IDX, inst)
+    }
+    pub fn translate_mret(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+        let mret_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_MRET_IDX as usize, *tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), *tcg_inst_addr);
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        vec![mret_op, exit_tb]
+    }
+
+    pub fn translate_ecall(&mut self, _inst: &InstrInfo) -> Vec<TCGOp> {
+        let ecall_op = TCGOp::new_helper_call_arg0(CALL_HELPER_IDX::CALL_ECALL_IDX as usize);
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        vec![ecall_op, exit_tb]
+    }
+
+    pub fn translate_sret(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+        let mret_op = TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_SRET_IDX as usize, *tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), *tcg_inst_addr);
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        vec![mret_op, exit_tb]
+    }
+
+    // hlv.* / hlvx.* / hsv.* : the helper decodes the access from the instruction.
+    pub fn translate_hlv_hsv(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::gen_hyp_helper(CALL_HELPER_IDX::CALL_HLV_HSV_IDX, inst)
+    }
+
+    pub fn translate_hfence(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::gen_hyp_helper(CALL_HELPER_IDX::CALL_HFENCE_IDX, inst)
+    }
+
+    fn gen_hyp_helper(helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+        let tcg_call_op = TCGOp::new_helper_call_arg4(helper_idx as usize, *tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), *tcg_inst_addr);
+        Self::gen_csr_exit_check(tcg_call_op)
+    }
+
+    // The helper returns non-zero when it raised an exception: leave the TB.
+    fn gen_csr_exit_check(helper_op: TCGOp) -> Vec<TCGOp> {
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![helper_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+}
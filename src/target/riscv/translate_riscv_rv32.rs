@@ -0,0 +1,185 @@
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv_inst_id::RiscvInstId;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    // On RV32 the guest registers are kept sign-extended from 32 bits.
+    // Instructions whose 64-bit lowering would break that are redirected
+    // here; None means the common lowering is already correct.
+    pub fn translate_rv32(&mut self, id: RiscvInstId, inst: &InstrInfo) -> Option<Vec<TCGOp>> {
+        let shamt_5 = (inst.inst >> 25) & 0x1 != 0;   // shamt[5] is reserved on RV32
+        let c_shamt_5 = (inst.inst >> 12) & 0x1 != 0;
+        let c_rd = (inst.inst >> 7) & 0x1f;
+        let c_rd_prime = ((inst.inst >> 2) & 0x7) + 8;
+        let c_rs1_prime = ((inst.inst >> 7) & 0x7) + 8;
+
+        let tcg_list = match id {
+            RiscvInstId::ADD => self.translate_addw(inst),
+            RiscvInstId::SUB => self.translate_subw(inst),
+            RiscvInstId::ADDI => self.translate_addiw(inst),
+            RiscvInstId::SLL => self.translate_sllw(inst),
+            RiscvInstId::SRL => self.translate_srlw(inst),
+            RiscvInstId::SRA => self.translate_sraw(inst),
+
+            RiscvInstId::SLLI | RiscvInstId::SRLI | RiscvInstId::SRAI |
+            RiscvInstId::RORI | RiscvInstId::BCLRI | RiscvInstId::BSETI |
+            RiscvInstId::BINVI | RiscvInstId::BEXTI if shamt_5 => Self::gen_illegal_inst(inst),
+            RiscvInstId::SLLI => self.translate_slliw(inst),
+            RiscvInstId::SRLI => self.translate_srliw(inst),
+            RiscvInstId::SRAI => self.translate_sraiw(inst),
+
+            RiscvInstId::MUL    => self.translate_mulw(inst),
+            RiscvInstId::MULH   => self.translate_mulh_rv32(true, true, inst),
+            RiscvInstId::MULHU  => self.translate_mulh_rv32(false, false, inst),
+            RiscvInstId::MULHSU => self.translate_mulh_rv32(true, false, inst),
+            RiscvInstId::DIV    => self.translate_divw(inst),
+            RiscvInstId::DIVU   => self.translate_divuw(inst),
+            RiscvInstId::REM    => self.translate_remw(inst),
+            RiscvInstId::REMU   => self.translate_remuw(inst),
+
+            RiscvInstId::SH1ADD => { let l = self.translate_sh1add(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::SH2ADD => { let l = self.translate_sh2add(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::SH3ADD => { let l = self.translate_sh3add(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::CLZ => self.translate_clzw(inst),
+            RiscvInstId::CTZ => self.translate_ctzw(inst),
+            RiscvInstId::CPOP => self.translate_cpopw(inst),
+            RiscvInstId::ROL => self.translate_rolw(inst),
+            RiscvInstId::ROR => self.translate_rorw(inst),
+            RiscvInstId::RORI => self.translate_roriw(inst),
+            RiscvInstId::REV8 => self.translate_rev8_rv32(inst),
+            RiscvInstId::ORC_B => { let l = self.translate_orc_b(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::BCLRI => { let l = self.translate_bclri(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::BSETI => { let l = self.translate_bseti(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+            RiscvInstId::BINVI => { let l = self.translate_binvi(inst); self.sext_rd(l, get_rd_addr!(inst.inst)) }
+
+            RiscvInstId::C_SLLI if c_shamt_5 => Self::gen_illegal_inst(inst),
+            RiscvInstId::C_SRLI64 | RiscvInstId::C_SRAI64 => Self::gen_illegal_inst(inst),
+            RiscvInstId::C_ADDI     => { let l = self.translate_c_addi(inst); self.sext_rd(l, c_rd) }
+            RiscvInstId::C_ADDI16SP => { let l = self.translate_c_addi16sp(inst); self.sext_rd(l, 2) }
+            RiscvInstId::C_ADDI4SPN => { let l = self.translate_c_addi4spn(inst); self.sext_rd(l, c_rd_prime) }
+            RiscvInstId::C_ADD      => { let l = self.translate_c_add(inst); self.sext_rd(l, c_rd) }
+            RiscvInstId::C_SUB      => { let l = self.translate_c_sub(inst); self.sext_rd(l, c_rs1_prime) }
+            RiscvInstId::C_SLLI     => { let l = self.translate_c_slli(inst); self.sext_rd(l, c_rd) }
+            RiscvInstId::C_SRLI     => self.translate_c_srli_rv32(inst),
+            RiscvInstId::C_MUL      => { let l = self.translate_c_mul(inst); self.sext_rd(l, c_rs1_prime) }
+
+            // RV64 only
+            RiscvInstId::LD | RiscvInstId::SD | RiscvInstId::LWU |
+            RiscvInstId::ADDIW | RiscvInstId::SLLIW | RiscvInstId::SRLIW | RiscvInstId::SRAIW |
+            RiscvInstId::ADDW | RiscvInstId::SUBW | RiscvInstId::SLLW | RiscvInstId::SRLW | RiscvInstId::SRAW |
+            RiscvInstId::MULW | RiscvInstId::DIVW | RiscvInstId::DIVUW | RiscvInstId::REMW | RiscvInstId::REMUW |
+            RiscvInstId::C_LD | RiscvInstId::C_SD | RiscvInstId::C_LDSP | RiscvInstId::C_SDSP |
+            RiscvInstId::C_ADDIW | RiscvInstId::C_ADDW | RiscvInstId::C_SUBW | RiscvInstId::C_ZEXT_W |
+            RiscvInstId::FMV_X_D | RiscvInstId::FMV_D_X |
+            RiscvInstId::FCVT_L_S | RiscvInstId::FCVT_LU_S | RiscvInstId::FCVT_S_L | RiscvInstId::FCVT_S_LU |
+            RiscvInstId::FCVT_L_D | RiscvInstId::FCVT_LU_D | RiscvInstId::FCVT_D_L | RiscvInstId::FCVT_D_LU |
+            RiscvInstId::FCVT_L_H | RiscvInstId::FCVT_LU_H | RiscvInstId::FCVT_H_L | RiscvInstId::FCVT_H_LU |
+            RiscvInstId::ADD_UW | RiscvInstId::SH1ADD_UW | RiscvInstId::SH2ADD_UW | RiscvInstId::SH3ADD_UW |
+            RiscvInstId::SLLI_UW | RiscvInstId::CLZW | RiscvInstId::CTZW | RiscvInstId::CPOPW |
+            RiscvInstId::ROLW | RiscvInstId::RORW | RiscvInstId::RORIW |
+            RiscvInstId::HLV_WU | RiscvInstId::HLV_D | RiscvInstId::HSV_D |
+            RiscvInstId::AES64ES | RiscvInstId::AES64ESM | RiscvInstId::AES64DS | RiscvInstId::AES64DSM |
+            RiscvInstId::AES64IM | RiscvInstId::AES64KS1I | RiscvInstId::AES64KS2 |
+            RiscvInstId::SHA512SIG0 | RiscvInstId::SHA512SIG1 | RiscvInstId::SHA512SUM0 | RiscvInstId::SHA512SUM1 |
+            RiscvInstId::PACKW => Self::gen_illegal_inst(inst),
+
+            _ => return None,
+        };
+        Some(tcg_list)
+    }
+
+    pub fn sext_xlen(&self, data: u64) -> u64 {
+        if self.m_rv32 { data as i32 as i64 as u64 } else { data }
+    }
+
+    fn sext_rd(&mut self, mut tcg_list: Vec<TCGOp>, rd_addr: u32) -> Vec<TCGOp> {
+        if rd_addr == 0 || tcg_list.is_empty() {
+            return tcg_list;
+        }
+        let dest = self.tcg_temp_new();
+        tcg_list.push(TCGOp::tcg_get_gpr(dest, rd_addr));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::SIGN_EXT_32_64, dest, dest));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, dest));
+        self.tcg_temp_free(dest);
+
+        tcg_list
+    }
+
+    // The 64-bit product of two 32-bit values is exact, so MULH* is the
+    // upper half of it. Unsigned operands are zero-extended first.
+    fn translate_mulh_rv32(&mut self, rs1_signed: bool, rs2_signed: bool, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rs2_addr = get_rs2_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+        let source2 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::tcg_get_gpr(source2, rs2_addr));
+        if !rs1_signed {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source1, source1, TCGv::new_imm(32)));
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source1, source1, TCGv::new_imm(32)));
+        }
+        if !rs2_signed {
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SLL_64BIT, source2, source2, TCGv::new_imm(32)));
+            tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_64BIT, source2, source2, TCGv::new_imm(32)));
+        }
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::MUL_64BIT, source1, source1, source2));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SRA_64BIT, source1, source1, TCGv::new_imm(32)));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source2);
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    fn translate_rev8_rv32(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rs1_addr = get_rs1_addr!(inst.inst);
+        let rd_addr = get_rd_addr!(inst.inst);
+
+        if rd_addr == 0 {
+            return vec![];
+        }
+
+        let source1 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rs1_addr));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::BSWAP_64BIT, source1, source1));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SRA_64BIT, source1, source1, TCGv::new_imm(32)));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+
+    fn translate_c_srli_rv32(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        let rd_addr = ((inst.inst >> 7) & 0x7) + 8;
+        let shamt = ((inst.inst >> 2) & 0x1f) as u64;
+
+        let source1 = self.tcg_temp_new();
+
+        let mut tcg_list = vec![];
+
+        tcg_list.push(TCGOp::tcg_get_gpr(source1, rd_addr));
+        tcg_list.push(TCGOp::new_3op(TCGOpcode::SRL_32BIT, source1, source1, TCGv::new_imm(shamt)));
+        tcg_list.push(TCGOp::new_2op(TCGOpcode::SIGN_EXT_32_64, source1, source1));
+        tcg_list.push(TCGOp::tcg_set_gpr(rd_addr, source1));
+
+        self.tcg_temp_free(source1);
+
+        tcg_list
+    }
+}
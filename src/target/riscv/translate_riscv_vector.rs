@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::super::tcg::tcg::{TCGOp, TCGOpcode, TCGv, TCGLabel};
+use super::super::super::instr_info::InstrInfo;
+use super::riscv::CALL_HELPER_IDX;
+use super::riscv_inst_id::RiscvInstId;
+
+use super::riscv::TranslateRiscv;
+
+impl TranslateRiscv {
+    // Vector instructions are executed by helpers which take the raw
+    // instruction word, and leave the TB when they raise an exception.
+    fn translate_vector_helper(helper_idx: CALL_HELPER_IDX, inst: &InstrInfo) -> Vec<TCGOp> {
+        let tcg_inst = Box::new(TCGv::new_imm(inst.inst as u64));
+        let tcg_inst_addr = Box::new(TCGv::new_imm(inst.addr));
+
+        let label = Rc::new(RefCell::new(TCGLabel::new()));
+
+        let tcg_call_op = TCGOp::new_helper_call_arg4(helper_idx as usize, *tcg_inst, TCGv::new_imm(0), TCGv::new_imm(0), *tcg_inst_addr);
+
+        let zero = Box::new(TCGv::new_reg(0 as u64));
+        let dummy_addr = Box::new(TCGv::new_imm(0));
+
+        let result_cmp_op = TCGOp::new_4op(TCGOpcode::EQ_EAX_64BIT, *zero, *zero, *dummy_addr, Rc::clone(&label));
+        let exit_tb = TCGOp::new_0op(TCGOpcode::EXIT_TB, None);
+        let tcg_set_label = TCGOp::new_label(Rc::clone(&label));
+
+        vec![tcg_call_op, result_cmp_op, exit_tb, tcg_set_label]
+    }
+
+    pub fn translate_vsetvl(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::translate_vector_helper(CALL_HELPER_IDX::CALL_VSETVL_IDX, inst)
+    }
+
+    pub fn translate_vload(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::translate_vector_helper(CALL_HELPER_IDX::CALL_VECTOR_LOAD_IDX, inst)
+    }
+
+    pub fn translate_vstore(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::translate_vector_helper(CALL_HELPER_IDX::CALL_VECTOR_STORE_IDX, inst)
+    }
+
+    pub fn translate_varith(&mut self, inst: &InstrInfo) -> Vec<TCGOp> {
+        Self::translate_vector_helper(CALL_HELPER_IDX::CALL_VECTOR_ARITH_IDX, inst)
+    }
+
+    // Instructions which need misa.V and mstatus.VS != Off.
+    pub fn is_vector_inst(id: RiscvInstId) -> bool {
+        match id {
+            RiscvInstId::VSETVLI | RiscvInstId::VSETIVLI | RiscvInstId::VSETVL |
+            RiscvInstId::VLOAD | RiscvInstId::VSTORE | RiscvInstId::VARITH => true,
+            _ => false,
+        }
+    }
+
+    pub fn gen_vs_dirty() -> TCGOp {
+        TCGOp::new_helper_call_arg4(CALL_HELPER_IDX::CALL_VS_DIRTY_IDX as usize,
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0),
+                                    TCGv::new_imm(0))
+    }
+}
@@ -15,6 +15,7 @@ pub enum TCGOpcode {
     MEM_STORE,
     ADD_TLBIDX_OFFSET,
     ADD_TLBADDR_OFFSET,
+    ADD_TLBASID_OFFSET,
     ADD_MEM_OFFSET,
 
     HELPER_CALL_ARG0,
@@ -83,6 +84,30 @@ pub enum TCGOpcode {
     SGNJN_32BIT,
     SGNJX_32BIT,   
 
+    FADD_64BIT,
+    FSUB_64BIT,
+    FMUL_64BIT,
+    FDIV_64BIT,
+    FSQRT_64BIT,
+    FMIN_64BIT,
+    FMAX_64BIT,
+
+    FADD_32BIT,
+    FSUB_32BIT,
+    FMUL_32BIT,
+    FDIV_32BIT,
+    FSQRT_32BIT,
+    FMIN_32BIT,
+    FMAX_32BIT,
+
+    AES64ES,
+    AES64ESM,
+    AES64DS,
+    AES64DSM,
+    AES64IM,
+    CLMUL_64BIT,
+    CLMULH_64BIT,
+
     MUL_64BIT,
     MULH_64BIT,
     MULHU_64BIT,
@@ -99,6 +124,22 @@ pub enum TCGOpcode {
     REM_32BIT,
     REMU_32BIT,
 
+    CLZ_64BIT,
+    CTZ_64BIT,
+    CPOP_64BIT,
+    CLZ_32BIT,
+    CTZ_32BIT,
+    CPOP_32BIT,
+
+    ROL_64BIT,
+    ROR_64BIT,
+    ROL_32BIT,
+    ROR_32BIT,
+
+    BSWAP_64BIT,
+    CZERO_EQZ_64BIT,
+    CZERO_NEZ_64BIT,
+
     TLB_MATCH_CHECK,
     CMP_EQ,
     EXIT_TB,
@@ -244,6 +285,18 @@ impl TCGOp {
         }
     }
 
+    pub fn new_4op_with_label(opcode: TCGOpcode, a1: TCGv, a2: TCGv, a3: TCGv, a4: TCGv, label: Rc<RefCell<TCGLabel>>) -> TCGOp {
+        TCGOp {
+            op: Some(opcode),
+            arg0: Some(a1),
+            arg1: Some(a2),
+            arg2: Some(a3),
+            arg3: Some(a4),
+            label: Some(label),
+            helper_idx: 0,
+        }
+    }
+
     pub fn new_helper_call_arg0(helper_idx: usize) -> TCGOp {
         TCGOp {
             op: Some(TCGOpcode::HELPER_CALL_ARG0),
@@ -434,6 +487,17 @@ pub trait TCG {
     fn tcg_gen_sll_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
     fn tcg_gen_sra_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
 
+    fn tcg_gen_rol_64bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_ror_64bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_rol_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_ror_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+
+    fn tcg_gen_clz(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_ctz(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_cpop(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_bswap_64bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+    fn tcg_gen_czero(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize;
+
     /* Memory Access */
     fn tcg_gen_load(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>, mem_size: MemOpType, target_reg: RegisterType) -> usize;
 
@@ -36,6 +36,10 @@ enum X86Opcode {
     SLL_GV_IMM = 0x20_c1,
     SRL_GV_IMM = 0x28_c1,
     SRA_GV_IMM = 0x38_c1,
+    ROL_GV_CL = 0x00_d3,
+    ROR_GV_CL = 0x08_d3,
+    ROL_GV_IMM = 0x00_c1,
+    ROR_GV_IMM = 0x08_c1,
     SIGN_EXT_A = 0x98,
     RETN = 0xc3,
     JMP_JZ = 0xe9,
@@ -66,7 +70,7 @@ enum X86Opcode {
     // JNS_rel16_32 = 0x89_0f,  // JNS_rel16_32	SF=0	符号がない場合ニアジャンプします
     // JNZ_rel16_32 = 0x85_0f,  // JNZ_rel16_32	ZF=0	ゼロでない場合ニアジャンプします
     // JO_rel16_32 = 0x80_0f,   // JO_rel16_32	OF=1	オーバーフローがある場合ニアジャンプします
-    // JP_rel16_32 = 0x8A_0f,   // JP_rel16_32	PF=1	パリティがある場合ニアジャンプします
+    JP_rel16_32 = 0x8A_0f,   // JP_rel16_32	PF=1	パリティがある場合ニアジャンプします
     // JPE_rel16_32 = 0x8A_0f,  // JPE_rel16_32	PF=1	パリティが偶数の場合ニアジャンプします
     // JPO_rel16_32 = 0x8B_0f,  // JPO_rel16_32	PF=0	パリティが奇数の場合ニアジャンプします
     // JS_rel16_32 = 0x88_0f,   // JS_rel16_32	SF=1	符号がある場合ニアジャンプします
@@ -95,6 +99,18 @@ enum X86Opcode {
     SETB = 0x92_0f, // より下の場合バイトを設定します
     SETL = 0x9c_0f, // より小さい場合バイトを設定します
 
+    BSR_GV_EV = 0xbd_0f,    // F3 prefix : LZCNT
+    BSF_GV_EV = 0xbc_0f,    // F3 prefix : TZCNT
+    POPCNT_GV_EV = 0xb8_0f, // with F3 prefix
+    CMOVZ_GV_EV = 0x44_0f,
+    CMOVNZ_GV_EV = 0x45_0f,
+    BSWAP_R = 0xc8_0f,
+    TEST_EV_GV = 0x85,
+    AND_EV_GV = 0x21,
+    ADD_EV_IB = 0x00_83,
+    SUB_EV_IB = 0x28_83,
+    XOR_EV_IB = 0x30_83,
+
     PUSH = 0x50,
     POP = 0x58,
 }
@@ -137,6 +153,39 @@ pub enum X86TargetRM {
     SENTINEL = 0b1001,
 }
 
+// Second byte of the scalar SSE instructions, after the F2 (double) or F3
+// (single) prefix and 0F.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[allow(non_camel_case_types, dead_code)]
+enum X86SseOpcode {
+    MOVS_XMM_EV = 0x10,
+    MOVS_EV_XMM = 0x11,
+    UCOMIS = 0x2e, // 66 for double, no prefix for single
+    SQRTS = 0x51,
+    ADDS = 0x58,
+    MULS = 0x59,
+    SUBS = 0x5c,
+    MINS = 0x5d,
+    DIVS = 0x5e,
+    MAXS = 0x5f,
+    MOVHPS_XMM_EV = 0x16,
+    MOVHPS_EV_XMM = 0x17,
+    MOVD_EV_XMM = 0x7e, // 66 prefix
+    PXOR = 0xef,        // 66 prefix
+    MXCSR = 0xae,       // /2 : LDMXCSR, /3 : STMXCSR
+}
+
+// 66 0F 38 xx, except PCLMULQDQ which is 66 0F 3A 44 ib
+#[derive(PartialEq, Clone, Copy)]
+enum X86CryptoOpcode {
+    AESIMC = 0xdb,
+    AESENC = 0xdc,
+    AESENCLAST = 0xdd,
+    AESDEC = 0xde,
+    AESDECLAST = 0xdf,
+    PCLMULQDQ = 0x44,
+}
+
 pub struct TCGX86;
 
 impl TCGX86 {
@@ -158,6 +207,15 @@ impl TCGX86 {
         return 3;
     }
 
+    // Register to register form that also reaches R8 : REX.B extends rm
+    // and REX.R extends reg.
+    fn tcg_modrm_64bit_ext_out(op: X86Opcode, rm: X86TargetRM, reg: X86TargetRM, mc: &mut Vec<u8>) -> usize {
+        let rex = 0x48 | (((reg as u64) >> 3) << 2) | ((rm as u64) >> 3);
+        let modrm = X86ModRM::MOD_11_DISP_RAX as u64 | (((reg as u64) & 0x7) << 3) | ((rm as u64) & 0x7);
+        Self::tcg_out((modrm << 16) | (op as u64) << 8 | rex, 3, mc);
+        return 3;
+    }
+
     fn tcg_64bit_out(op: X86Opcode, mc: &mut Vec<u8>) -> usize {
         Self::tcg_out(((op as u64) << 8) | 0x48 << 0, 2, mc);
         return 2;
@@ -358,6 +416,21 @@ impl TCGX86 {
         return gen_size;
     }
 
+    // Load a single-precision value, replacing an improperly NaN-boxed one
+    // with the canonical NaN. Only the lower 32 bits of dest are valid.
+    fn tcg_gen_load_fregs_unboxed_32bit(emu: &EmuEnv, dest: X86TargetRM, scratch: X86TargetRM, source: u64, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size = 0;
+        gen_size += Self::tcg_gen_load_fregs_64bit(emu, dest, source, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + dest as u8, scratch as u8, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::SRA_GV_IMM, X86ModRM::MOD_11_DISP_RAX as u8 + scratch as u8, 0, mc);
+        gen_size += Self::tcg_out(32, 1, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::ADD_EV_IB, X86ModRM::MOD_11_DISP_RAX as u8 + scratch as u8, 0, mc);
+        gen_size += Self::tcg_out(1, 1, mc);
+        gen_size += Self::tcg_gen_imm_u64(scratch, 0x7fc00000, mc);
+        gen_size += Self::tcg_modrm_2byte_64bit_raw_out(X86Opcode::CMOVNZ_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + scratch as u8, dest as u8, mc);
+        return gen_size;
+    }
+
     #[allow(dead_code)]
     fn tcg_gen_rrr_64bit(emu: &EmuEnv, op: X86Opcode, tcg: &tcg::TCGOp, mc: &mut Vec<u8>) -> usize {
         let arg0 = tcg.arg0.unwrap();
@@ -544,6 +617,47 @@ impl TCGX86 {
         return gen_size;
     }
 
+    // Emit "op dest, src" for a 0F-prefixed bit-count instruction.
+    // f3_prefix selects LZCNT/TZCNT/POPCNT over BSR/BSF.
+    fn tcg_gen_bitcount_op(op: X86Opcode, f3_prefix: bool, is_64bit: bool, dest: X86TargetRM, src: X86TargetRM, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size: usize = 0;
+        if f3_prefix {
+            gen_size += Self::tcg_out(0xf3, 1, mc);
+        }
+        if is_64bit {
+            gen_size += Self::tcg_modrm_2byte_64bit_raw_out(op, X86ModRM::MOD_11_DISP_RAX as u8 + src as u8, dest as u8, mc);
+        } else {
+            gen_size += Self::tcg_modrm_2byte_32bit_raw_out(op, X86ModRM::MOD_11_DISP_RAX as u8 + src as u8, dest as u8, mc);
+        }
+        return gen_size;
+    }
+
+    // BSR/BSF leave the destination undefined for a zero source.
+    // Select the zero value through RAX with CMOVZ.
+    fn tcg_gen_cmovz_imm(dest: X86TargetRM, imm: u64, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size: usize = 0;
+        gen_size += Self::tcg_out(X86Opcode::MOV_EAX_IV as u64, 1, mc);
+        gen_size += Self::tcg_out(imm, 4, mc);
+        gen_size += Self::tcg_modrm_2byte_64bit_raw_out(X86Opcode::CMOVZ_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8, dest as u8, mc);
+        return gen_size;
+    }
+
+    fn tcg_gen_rotate(emu: &EmuEnv, op_r: X86Opcode, op_i: X86Opcode, is_64bit: bool, tcg: &tcg::TCGOp, mc: &mut Vec<u8>) -> usize {
+        let arg0 = tcg.arg0.unwrap();
+        let arg1 = tcg.arg1.unwrap();
+        let arg2 = tcg.arg2.unwrap();
+
+        assert_eq!(arg0.t, TCGvType::TCGTemp);
+        assert_eq!(arg1.t, TCGvType::TCGTemp);
+
+        return match (arg2.t == tcg::TCGvType::Immediate, is_64bit) {
+            (true, true) => Self::tcg_gen_shift_i_64bit(emu, op_i, tcg, mc),
+            (false, true) => Self::tcg_gen_shift_r_64bit(emu, op_r, tcg, mc),
+            (true, false) => Self::tcg_gen_shift_i_32bit(emu, op_i, tcg, mc),
+            (false, false) => Self::tcg_gen_shift_r_32bit(emu, op_r, tcg, mc),
+        };
+    }
+
     fn tcg_out(inst: u64, byte_len: usize, v: &mut Vec<u8>) -> usize {
         for (i, be) in inst.to_le_bytes().iter().enumerate() {
             if i < byte_len {
@@ -762,7 +876,8 @@ impl TCG for TCGX86 {
                     TCGOpcode::MEM_STORE => TCGX86::tcg_gen_mem_store(emu, pc_address, tcg, mc, MemOpType::STORE_64BIT),
 
                     TCGOpcode::ADD_TLBIDX_OFFSET => TCGX86::tcg_gen_tlbidx_offset(emu, pc_address, tcg, mc),             
-                    TCGOpcode::ADD_TLBADDR_OFFSET => TCGX86::tcg_gen_tlbaddr_offset(emu, pc_address, tcg, mc),                    
+                    TCGOpcode::ADD_TLBADDR_OFFSET => TCGX86::tcg_gen_tlbaddr_offset(emu, pc_address, tcg, mc),
+                    TCGOpcode::ADD_TLBASID_OFFSET => TCGX86::tcg_gen_tlbasid_offset(emu, pc_address, tcg, mc),
 
                     TCGOpcode::ADD_MEM_OFFSET => TCGX86::tcg_gen_mem_offset(emu, pc_address, tcg, mc),
 
@@ -800,6 +915,21 @@ impl TCG for TCGX86 {
                     TCGOpcode::SLL_32BIT => TCGX86::tcg_gen_sll_32bit(emu, pc_address, tcg, mc),
                     TCGOpcode::SRA_32BIT => TCGX86::tcg_gen_sra_32bit(emu, pc_address, tcg, mc),
 
+                    TCGOpcode::ROL_64BIT => TCGX86::tcg_gen_rol_64bit(emu, pc_address, tcg, mc),
+                    TCGOpcode::ROR_64BIT => TCGX86::tcg_gen_ror_64bit(emu, pc_address, tcg, mc),
+                    TCGOpcode::ROL_32BIT => TCGX86::tcg_gen_rol_32bit(emu, pc_address, tcg, mc),
+                    TCGOpcode::ROR_32BIT => TCGX86::tcg_gen_ror_32bit(emu, pc_address, tcg, mc),
+
+                    TCGOpcode::CLZ_64BIT => TCGX86::tcg_gen_clz(emu, pc_address, tcg, mc),
+                    TCGOpcode::CLZ_32BIT => TCGX86::tcg_gen_clz(emu, pc_address, tcg, mc),
+                    TCGOpcode::CTZ_64BIT => TCGX86::tcg_gen_ctz(emu, pc_address, tcg, mc),
+                    TCGOpcode::CTZ_32BIT => TCGX86::tcg_gen_ctz(emu, pc_address, tcg, mc),
+                    TCGOpcode::CPOP_64BIT => TCGX86::tcg_gen_cpop(emu, pc_address, tcg, mc),
+                    TCGOpcode::CPOP_32BIT => TCGX86::tcg_gen_cpop(emu, pc_address, tcg, mc),
+                    TCGOpcode::BSWAP_64BIT => TCGX86::tcg_gen_bswap_64bit(emu, pc_address, tcg, mc),
+                    TCGOpcode::CZERO_EQZ_64BIT => TCGX86::tcg_gen_czero(emu, pc_address, tcg, mc),
+                    TCGOpcode::CZERO_NEZ_64BIT => TCGX86::tcg_gen_czero(emu, pc_address, tcg, mc),
+
                     TCGOpcode::JMPR => TCGX86::tcg_gen_jmpr(emu, pc_address, tcg, mc),
                     TCGOpcode::JMPIM => TCGX86::tcg_gen_jmpim(emu, pc_address, tcg, mc),
                     TCGOpcode::EQ_64BIT => TCGX86::tcg_gen_eq_64bit(emu, pc_address, tcg, mc),
@@ -850,6 +980,30 @@ impl TCG for TCGX86 {
                     TCGOpcode::SGNJN_32BIT => TCGX86::tcg_gen_sgnjn_32bit(emu, pc_address, tcg, mc),
                     TCGOpcode::SGNJX_32BIT => TCGX86::tcg_gen_sgnjx_32bit(emu, pc_address, tcg, mc),
 
+                    TCGOpcode::FADD_64BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::ADDS, true),
+                    TCGOpcode::FSUB_64BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::SUBS, true),
+                    TCGOpcode::FMUL_64BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::MULS, true),
+                    TCGOpcode::FDIV_64BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::DIVS, true),
+                    TCGOpcode::FSQRT_64BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::SQRTS, true),
+                    TCGOpcode::FMIN_64BIT => TCGX86::tcg_gen_float_minmax_sse(emu, pc_address, tcg, mc, X86SseOpcode::MINS, true),
+                    TCGOpcode::FMAX_64BIT => TCGX86::tcg_gen_float_minmax_sse(emu, pc_address, tcg, mc, X86SseOpcode::MAXS, true),
+
+                    TCGOpcode::FADD_32BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::ADDS, false),
+                    TCGOpcode::FSUB_32BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::SUBS, false),
+                    TCGOpcode::FMUL_32BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::MULS, false),
+                    TCGOpcode::FDIV_32BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::DIVS, false),
+                    TCGOpcode::FSQRT_32BIT => TCGX86::tcg_gen_float_sse(emu, pc_address, tcg, mc, X86SseOpcode::SQRTS, false),
+                    TCGOpcode::FMIN_32BIT => TCGX86::tcg_gen_float_minmax_sse(emu, pc_address, tcg, mc, X86SseOpcode::MINS, false),
+                    TCGOpcode::FMAX_32BIT => TCGX86::tcg_gen_float_minmax_sse(emu, pc_address, tcg, mc, X86SseOpcode::MAXS, false),
+
+                    TCGOpcode::AES64ES => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::AESENCLAST),
+                    TCGOpcode::AES64ESM => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::AESENC),
+                    TCGOpcode::AES64DS => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::AESDECLAST),
+                    TCGOpcode::AES64DSM => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::AESDEC),
+                    TCGOpcode::AES64IM => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::AESIMC),
+                    TCGOpcode::CLMUL_64BIT => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::PCLMULQDQ),
+                    TCGOpcode::CLMULH_64BIT => TCGX86::tcg_gen_crypto_sse(emu, pc_address, tcg, mc, X86CryptoOpcode::PCLMULQDQ),
+
                     TCGOpcode::HELPER_CALL_ARG0 => { TCGX86::tcg_gen_helper_call(emu, 0, pc_address, tcg, mc) }
                     TCGOpcode::HELPER_CALL_ARG1 => { TCGX86::tcg_gen_helper_call(emu, 1, pc_address, tcg, mc) }
                     TCGOpcode::HELPER_CALL_ARG2 => { TCGX86::tcg_gen_helper_call(emu, 2, pc_address, tcg, mc) }
@@ -1092,6 +1246,26 @@ impl TCG for TCGX86 {
         return gen_size;
     }
 
+    fn tcg_gen_tlbasid_offset(emu: &EmuEnv, pc_address: u64, tcg: &tcg::TCGOp, mc: &mut Vec<u8>) -> usize {
+        let dest_reg = tcg.arg0.unwrap();
+        let src_reg = tcg.arg1.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src_reg.t, TCGvType::TCGTemp);
+
+        let target_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let source_x86reg = Self::convert_x86_reg(src_reg.value);
+
+        let mut gen_size = pc_address as usize;
+
+        if dest_reg.value != src_reg.value {
+            gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + source_x86reg as u8, target_x86reg as u8, mc);    
+        }
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::ADD_GV_IMM, X86ModRM::MOD_11_DISP_RAX as u8 + target_x86reg as u8, 0, mc);
+        gen_size += Self::tcg_out(emu.calc_tlb_asid_relat_address() as u64, 4, mc);
+        return gen_size;
+    }
+
     fn tcg_gen_mem_offset(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
         let dest_reg = tcg.arg0.unwrap();
         let offset = tcg.arg1.unwrap();
@@ -1291,6 +1465,176 @@ impl TCG for TCGX86 {
         gen_size
     }
 
+    fn tcg_gen_rol_64bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let gen_size: usize = pc_address as usize;
+        return gen_size + Self::tcg_gen_rotate(emu, X86Opcode::ROL_GV_CL, X86Opcode::ROL_GV_IMM, true, tcg, mc);
+    }
+
+    fn tcg_gen_ror_64bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let gen_size: usize = pc_address as usize;
+        return gen_size + Self::tcg_gen_rotate(emu, X86Opcode::ROR_GV_CL, X86Opcode::ROR_GV_IMM, true, tcg, mc);
+    }
+
+    fn tcg_gen_rol_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let gen_size: usize = pc_address as usize;
+        return gen_size + Self::tcg_gen_rotate(emu, X86Opcode::ROL_GV_CL, X86Opcode::ROL_GV_IMM, false, tcg, mc);
+    }
+
+    fn tcg_gen_ror_32bit(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let gen_size: usize = pc_address as usize;
+        return gen_size + Self::tcg_gen_rotate(emu, X86Opcode::ROR_GV_CL, X86Opcode::ROR_GV_IMM, false, tcg, mc);
+    }
+
+    fn tcg_gen_clz(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let op = tcg.op.unwrap();
+        let dest_reg = tcg.arg0.unwrap();
+        let src_reg = tcg.arg1.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src_reg.t, TCGvType::TCGTemp);
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let is_64bit = op == TCGOpcode::CLZ_64BIT;
+        let dest_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let src_x86reg = Self::convert_x86_reg(src_reg.value);
+
+        if is_x86_feature_detected!("lzcnt") {
+            gen_size += Self::tcg_gen_bitcount_op(X86Opcode::BSR_GV_EV, true, is_64bit, dest_x86reg, src_x86reg, mc);
+        } else {
+            // bsr dest, src ; cmovz dest, 127 ; xor dest, 63
+            let width: u64 = if is_64bit { 64 } else { 32 };
+            gen_size += Self::tcg_gen_bitcount_op(X86Opcode::BSR_GV_EV, false, is_64bit, dest_x86reg, src_x86reg, mc);
+            gen_size += Self::tcg_gen_cmovz_imm(dest_x86reg, width * 2 - 1, mc);
+            gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::XOR_EV_IB, X86ModRM::MOD_11_DISP_RAX as u8 + dest_x86reg as u8, 0, mc);
+            gen_size += Self::tcg_out(width - 1, 1, mc);
+        }
+        return gen_size;
+    }
+
+    fn tcg_gen_ctz(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let op = tcg.op.unwrap();
+        let dest_reg = tcg.arg0.unwrap();
+        let src_reg = tcg.arg1.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src_reg.t, TCGvType::TCGTemp);
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let is_64bit = op == TCGOpcode::CTZ_64BIT;
+        let dest_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let src_x86reg = Self::convert_x86_reg(src_reg.value);
+
+        if is_x86_feature_detected!("bmi1") {
+            gen_size += Self::tcg_gen_bitcount_op(X86Opcode::BSF_GV_EV, true, is_64bit, dest_x86reg, src_x86reg, mc);
+        } else {
+            // bsf dest, src ; cmovz dest, width
+            let width: u64 = if is_64bit { 64 } else { 32 };
+            gen_size += Self::tcg_gen_bitcount_op(X86Opcode::BSF_GV_EV, false, is_64bit, dest_x86reg, src_x86reg, mc);
+            gen_size += Self::tcg_gen_cmovz_imm(dest_x86reg, width, mc);
+        }
+        return gen_size;
+    }
+
+    fn tcg_gen_cpop(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let op = tcg.op.unwrap();
+        let dest_reg = tcg.arg0.unwrap();
+        let src_reg = tcg.arg1.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src_reg.t, TCGvType::TCGTemp);
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let is_64bit = op == TCGOpcode::CPOP_64BIT;
+        let dest_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let src_x86reg = Self::convert_x86_reg(src_reg.value);
+
+        if is_x86_feature_detected!("popcnt") {
+            gen_size += Self::tcg_gen_bitcount_op(X86Opcode::POPCNT_GV_EV, true, is_64bit, dest_x86reg, src_x86reg, mc);
+            return gen_size;
+        }
+
+        // Clear the lowest set bit until the value becomes zero, counting in RAX.
+        if is_64bit {
+            gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + src_x86reg as u8, dest_x86reg as u8, mc);
+        } else {
+            gen_size += Self::tcg_modrm_32bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + src_x86reg as u8, dest_x86reg as u8, mc);
+        }
+        gen_size += Self::tcg_modrm_32bit_raw_out(X86Opcode::XOR_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8, X86TargetRM::RAX as u8, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::TEST_EV_GV, X86ModRM::MOD_11_DISP_RAX as u8 + dest_x86reg as u8, dest_x86reg as u8, mc);
+        let mut done = vec![];
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JE_rel16_32, mc, &mut done);
+        let loop_start = mc.len();
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::ADD_EV_IB, X86ModRM::MOD_11_DISP_RAX as u8, 0, mc);
+        gen_size += Self::tcg_out(1, 1, mc);
+        // dest &= dest - 1, through R8
+        gen_size += Self::tcg_modrm_64bit_ext_out(X86Opcode::MOV_EV_GV, X86TargetRM::R8, dest_x86reg, mc);
+        gen_size += Self::tcg_modrm_64bit_ext_out(X86Opcode::SUB_EV_IB, X86TargetRM::R8, X86TargetRM::RAX, mc);
+        gen_size += Self::tcg_out(1, 1, mc);
+        gen_size += Self::tcg_modrm_64bit_ext_out(X86Opcode::AND_EV_GV, dest_x86reg, X86TargetRM::R8, mc);
+        gen_size += Self::tcg_gen_bwd_jcc(X86Opcode::JNE_rel16_32, loop_start, mc);
+        Self::tcg_set_fwd_jcc(mc, &done);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_EV_GV, X86ModRM::MOD_11_DISP_RAX as u8 + dest_x86reg as u8, X86TargetRM::RAX as u8, mc);
+
+        return gen_size;
+    }
+
+    fn tcg_gen_bswap_64bit(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let dest_reg = tcg.arg0.unwrap();
+        let src_reg = tcg.arg1.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src_reg.t, TCGvType::TCGTemp);
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let dest_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let src_x86reg = Self::convert_x86_reg(src_reg.value);
+
+        if dest_reg.value != src_reg.value {
+            gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + src_x86reg as u8, dest_x86reg as u8, mc);
+        }
+        gen_size += Self::tcg_out(0x48, 1, mc);
+        gen_size += Self::tcg_out(X86Opcode::BSWAP_R as u64 + ((dest_x86reg as u64) << 8), 2, mc);
+
+        return gen_size;
+    }
+
+    // dest = (src2 == 0) ? 0 : src1 for EQZ, the inverse for NEZ.
+    fn tcg_gen_czero(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
+        let dest_reg = tcg.arg0.unwrap();
+        let src1_reg = tcg.arg1.unwrap();
+        let src2_reg = tcg.arg2.unwrap();
+
+        assert_eq!(dest_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src1_reg.t, TCGvType::TCGTemp);
+        assert_eq!(src2_reg.t, TCGvType::TCGTemp);
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let dest_x86reg = Self::convert_x86_reg(dest_reg.value);
+        let src1_x86reg = Self::convert_x86_reg(src1_reg.value);
+        let src2_x86reg = Self::convert_x86_reg(src2_reg.value);
+
+        let cmov_op = match tcg.op.unwrap() {
+            TCGOpcode::CZERO_EQZ_64BIT => X86Opcode::CMOVZ_GV_EV,
+            TCGOpcode::CZERO_NEZ_64BIT => X86Opcode::CMOVNZ_GV_EV,
+            _ => panic!("Unknown czero opcode"),
+        };
+
+        if dest_reg.value != src1_reg.value {
+            gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + src1_x86reg as u8, dest_x86reg as u8, mc);
+        }
+        // xor clobbers ZF, so clear eax before testing src2.
+        gen_size += Self::tcg_modrm_32bit_raw_out(X86Opcode::XOR_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8, X86TargetRM::RAX as u8, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::TEST_EV_GV, X86ModRM::MOD_11_DISP_RAX as u8 + src2_x86reg as u8, src2_x86reg as u8, mc);
+        gen_size += Self::tcg_modrm_2byte_64bit_raw_out(cmov_op, X86ModRM::MOD_11_DISP_RAX as u8, dest_x86reg as u8, mc);
+
+        return gen_size;
+    }
+
     fn tcg_gen_sign_ext_32_64(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
         let dest_reg = tcg.arg0.unwrap();
         let source1_reg = tcg.arg1.unwrap();
@@ -1384,6 +1728,10 @@ impl TCG for TCGX86 {
         gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8 + src1_x86reg as u8, X86TargetRM::RAX as u8, mc);
         gen_size += Self::tcg_64bit_out(X86Opcode::ADD_EAX_IV, mc);
         gen_size += Self::tcg_out(src2.value as u64, 4, mc);
+        if emu.is_rv32() {
+            // mov eax, eax : the RV32 PC is zero-extended
+            gen_size += Self::tcg_modrm_32bit_out(X86Opcode::MOV_EV_GV, X86ModRM::MOD_11_DISP_RAX, X86TargetRM::RAX, mc);
+        }
         // RAX --> PC
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::MOV_EV_GV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RAX, mc);
         gen_size += Self::tcg_out(emu.calc_pc_address() as u64, 4, mc); // Set Program Counter
@@ -2196,13 +2544,13 @@ impl TCG for TCGX86 {
 
         let mut gen_size: usize = pc_address as usize;
 
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RCX, 0x7fffffff, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RCX, mc);
-        gen_size += Self::tcg_out(emu.calc_fregs_relat_address(arg1.value) as u64, 4, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg2.value, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RCX, 0x80000000, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RAX, X86TargetRM::RCX, mc);
 
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RAX, 0x80000000, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RAX, mc);
-        gen_size += Self::tcg_out(emu.calc_fregs_relat_address(arg2.value) as u64, 4, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg1.value, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RDX, 0x7fffffff, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RDX, X86TargetRM::RAX, mc);
 
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::OR_GV_EV, X86ModRM::MOD_11_DISP_RCX, X86TargetRM::RAX, mc);
         gen_size += Self::tcg_gen_store_fregs_32bit(emu, X86TargetRM::RAX, arg0.value, mc);
@@ -2223,13 +2571,13 @@ impl TCG for TCGX86 {
 
         let mut gen_size: usize = pc_address as usize;
 
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RCX, 0x7fffffff, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RCX, mc);
-        gen_size += Self::tcg_out(emu.calc_fregs_relat_address(arg1.value) as u64, 4, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg2.value, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::NEG_GV, X86ModRM::MOD_11_DISP_RAX, X86TargetRM::RDX, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RCX, 0x80000000, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RAX, X86TargetRM::RCX, mc);
 
-        gen_size += Self::tcg_gen_load_fregs_64bit(emu, X86TargetRM::RDX, arg2.value, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::NEG_GV, X86ModRM::MOD_11_DISP_RDX, X86TargetRM::RDX, mc);
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RAX, 0x80000000, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg1.value, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RDX, 0x7fffffff, mc);
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RDX, X86TargetRM::RAX, mc);
 
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::OR_GV_EV, X86ModRM::MOD_11_DISP_RCX, X86TargetRM::RAX, mc);
@@ -2252,14 +2600,14 @@ impl TCG for TCGX86 {
 
         let mut gen_size: usize = pc_address as usize;
 
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RCX, 0x7fffffff, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RCX, mc);
-        gen_size += Self::tcg_out(emu.calc_fregs_relat_address(arg1.value) as u64, 4, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg2.value, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_11_DISP_RAX as u8, X86TargetRM::RCX as u8, mc);
+        gen_size += Self::tcg_gen_load_fregs_unboxed_32bit(emu, X86TargetRM::RAX, X86TargetRM::RDX, arg1.value, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::XOR_GV_EV, X86ModRM::MOD_11_DISP_RAX, X86TargetRM::RCX, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RDX, 0x80000000, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RDX, X86TargetRM::RCX, mc);
 
-        gen_size += Self::tcg_gen_load_fregs_64bit(emu, X86TargetRM::RDX, arg1.value, mc);
-        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::XOR_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RDX, mc);
-        gen_size += Self::tcg_out(emu.calc_fregs_relat_address(arg2.value) as u64, 4, mc);
-        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RAX, 0x80000000, mc);
+        gen_size += Self::tcg_gen_imm_u64(X86TargetRM::RDX, 0x7fffffff, mc);
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::AND_GV_EV, X86ModRM::MOD_11_DISP_RDX, X86TargetRM::RAX, mc);
 
         gen_size += Self::tcg_modrm_64bit_out(X86Opcode::OR_GV_EV, X86ModRM::MOD_11_DISP_RCX, X86TargetRM::RAX, mc);
@@ -2268,6 +2616,275 @@ impl TCG for TCGX86 {
         return gen_size;
     }
 
+    // Scalar SSE instruction with an [rbp + disp32] operand. reg is the xmm
+    // register, or the /digit of LDMXCSR and STMXCSR.
+    fn tcg_gen_sse_rbp(prefix: Option<u8>, op: X86SseOpcode, reg: u8, disp: isize, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size = 0;
+        if let Some(prefix) = prefix {
+            gen_size += Self::tcg_out(prefix as u64, 1, mc);
+        }
+        gen_size += Self::tcg_out(0x0f, 1, mc);
+        gen_size += Self::tcg_out(op as u64, 1, mc);
+        gen_size += Self::tcg_out(X86ModRM::MOD_10_DISP_RBP as u64 | ((reg as u64) << 3), 1, mc);
+        gen_size += Self::tcg_out(disp as u64, 4, mc);
+        return gen_size;
+    }
+
+    // Forward jump inside the code of one TCGOp. The position of its rel32
+    // is recorded in fixups, and tcg_set_fwd_jcc() points it at the end of mc.
+    fn tcg_gen_fwd_jcc(x86_op: X86Opcode, mc: &mut Vec<u8>, fixups: &mut Vec<usize>) -> usize {
+        let mut gen_size = 0;
+        gen_size += Self::tcg_out(x86_op as u64, 2, mc);
+        fixups.push(mc.len());
+        gen_size += Self::tcg_out(0, 4, mc);
+        return gen_size;
+    }
+
+    fn tcg_set_fwd_jcc(mc: &mut Vec<u8>, fixups: &[usize]) {
+        for off in fixups {
+            let diff = (mc.len() - off - 4) as u32;
+            mc[*off..*off + 4].copy_from_slice(&diff.to_le_bytes());
+        }
+    }
+
+    // Backward jump to an earlier position in the code of the same TCGOp.
+    fn tcg_gen_bwd_jcc(x86_op: X86Opcode, target: usize, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size = 0;
+        gen_size += Self::tcg_out(x86_op as u64, 2, mc);
+        let diff = target as i64 - (mc.len() + 4) as i64;
+        gen_size += Self::tcg_out(diff as u32 as u64, 4, mc);
+        return gen_size;
+    }
+
+    // Leave the inline path unless the single-precision operand is NaN-boxed.
+    fn tcg_gen_check_boxed_32bit(emu: &EmuEnv, source: u64, mc: &mut Vec<u8>, fixups: &mut Vec<usize>) -> usize {
+        let mut gen_size = 0;
+        gen_size += Self::tcg_gen_load_fregs_64bit(emu, X86TargetRM::RAX, source, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::SRA_GV_IMM, X86ModRM::MOD_11_DISP_RAX as u8, 0, mc);
+        gen_size += Self::tcg_out(32, 1, mc);
+        gen_size += Self::tcg_modrm_64bit_raw_out(X86Opcode::ADD_EV_IB, X86ModRM::MOD_11_DISP_RAX as u8, 0, mc);
+        gen_size += Self::tcg_out(1, 1, mc);
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JNE_rel16_32, mc, fixups);
+        return gen_size;
+    }
+
+    // Write xmm0 to the destination register, NaN-boxing single-precision.
+    fn tcg_gen_store_fregs_xmm0(emu: &EmuEnv, dest: u64, is_64bit: bool, mc: &mut Vec<u8>) -> usize {
+        let mut gen_size = 0;
+        if is_64bit {
+            gen_size += Self::tcg_gen_sse_rbp(Some(0xf2), X86SseOpcode::MOVS_EV_XMM, 0, emu.calc_fregs_relat_address(dest), mc);
+        } else {
+            // movd eax, xmm0
+            gen_size += Self::tcg_out(0x66, 1, mc);
+            gen_size += Self::tcg_out(0x0f, 1, mc);
+            gen_size += Self::tcg_out(X86SseOpcode::MOVD_EV_XMM as u64, 1, mc);
+            gen_size += Self::tcg_out(X86ModRM::MOD_11_DISP_RAX as u64, 1, mc);
+            gen_size += Self::tcg_gen_store_fregs_32bit(emu, X86TargetRM::RAX, dest, mc);
+        }
+        return gen_size;
+    }
+
+    // jmp label, over the softfloat helper call.
+    fn tcg_gen_jmp_label(gen_size: usize, mc: &mut Vec<u8>, label: &Rc<RefCell<tcg::TCGLabel>>) -> usize {
+        let mut gen_size = gen_size;
+
+        gen_size += Self::tcg_out(X86Opcode::JMP_JZ as u64, 1, mc);
+        gen_size += Self::tcg_out(0, 4, mc);
+        gen_size += Self::tcg_out_reloc(gen_size - 4, label);
+
+        return gen_size;
+    }
+
+    // FADD/FSUB/FMUL/FDIV/FSQRT on SSE2, with arg3 = rm selecting the host
+    // MXCSR. A host exception other than inexact, a NaN result or an operand
+    // that is not NaN-boxed falls through to the softfloat helper following
+    // this op, which recomputes the result and fflags. Otherwise NX is
+    // accrued from the host flags and the helper is skipped.
+    fn tcg_gen_float_sse(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>, sse_op: X86SseOpcode, is_64bit: bool) -> usize {
+        let arg0 = tcg.arg0.unwrap();
+        let arg1 = tcg.arg1.unwrap();
+        let arg2 = tcg.arg2.unwrap();
+        let arg3 = tcg.arg3.unwrap();
+
+        assert_eq!(arg0.t, TCGvType::Register);
+        assert_eq!(arg1.t, TCGvType::Register);
+        assert_eq!(arg2.t, TCGvType::Register);
+        assert_eq!(arg3.t, TCGvType::Immediate);
+
+        let label = match &tcg.label {
+            Some(l) => l,
+            None => panic!("Label is not defined."),
+        };
+
+        let mut gen_size: usize = pc_address as usize;
+        let mut slow_path = vec![];
+
+        let prefix = if is_64bit { 0xf2 } else { 0xf3 };
+        let is_sqrt = sse_op == X86SseOpcode::SQRTS;
+
+        if !is_64bit {
+            gen_size += Self::tcg_gen_check_boxed_32bit(emu, arg1.value, mc, &mut slow_path);
+            if !is_sqrt {
+                gen_size += Self::tcg_gen_check_boxed_32bit(emu, arg2.value, mc, &mut slow_path);
+            }
+        }
+
+        // ldmxcsr [host_mxcsr[rm]]
+        gen_size += Self::tcg_gen_sse_rbp(None, X86SseOpcode::MXCSR, 2, emu.calc_host_mxcsr_relat_address(arg3.value), mc);
+        if is_sqrt {
+            gen_size += Self::tcg_gen_sse_rbp(Some(prefix), sse_op, 0, emu.calc_fregs_relat_address(arg1.value), mc);
+        } else {
+            gen_size += Self::tcg_gen_sse_rbp(Some(prefix), X86SseOpcode::MOVS_XMM_EV, 0, emu.calc_fregs_relat_address(arg1.value), mc);
+            gen_size += Self::tcg_gen_sse_rbp(Some(prefix), sse_op, 0, emu.calc_fregs_relat_address(arg2.value), mc);
+        }
+        // stmxcsr [host_mxcsr_tmp], then back to the default MXCSR
+        gen_size += Self::tcg_gen_sse_rbp(None, X86SseOpcode::MXCSR, 3, emu.calc_host_mxcsr_tmp_relat_address(), mc);
+        gen_size += Self::tcg_gen_sse_rbp(None, X86SseOpcode::MXCSR, 2, emu.calc_host_mxcsr_relat_address(0), mc);
+
+        // IE, DE, ZE, OE or UE : take the slow path
+        gen_size += Self::tcg_modrm_32bit_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RAX, mc);
+        gen_size += Self::tcg_out(emu.calc_host_mxcsr_tmp_relat_address() as u64, 4, mc);
+        gen_size += Self::tcg_out(X86Opcode::AND_EAX_IV as u64, 1, mc);
+        gen_size += Self::tcg_out(0x1f, 4, mc);
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JNE_rel16_32, mc, &mut slow_path);
+
+        // ucomis xmm0, xmm0 : a NaN result needs canonicalization
+        if is_64bit {
+            gen_size += Self::tcg_out(0x66, 1, mc);
+        }
+        gen_size += Self::tcg_out(0x0f, 1, mc);
+        gen_size += Self::tcg_out(X86SseOpcode::UCOMIS as u64, 1, mc);
+        gen_size += Self::tcg_out(X86ModRM::MOD_11_DISP_RAX as u64, 1, mc);
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JP_rel16_32, mc, &mut slow_path);
+
+        gen_size += Self::tcg_gen_store_fregs_xmm0(emu, arg0.value, is_64bit, mc);
+
+        // fcsr |= PE (MXCSR bit 5) >> 5
+        gen_size += Self::tcg_modrm_32bit_out(X86Opcode::MOV_GV_EV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RAX, mc);
+        gen_size += Self::tcg_out(emu.calc_host_mxcsr_tmp_relat_address() as u64, 4, mc);
+        gen_size += Self::tcg_modrm_32bit_raw_out(X86Opcode::SRL_GV_IMM, X86ModRM::MOD_11_DISP_RAX as u8, 0, mc);
+        gen_size += Self::tcg_out(5, 1, mc);
+        gen_size += Self::tcg_out(X86Opcode::AND_EAX_IV as u64, 1, mc);
+        gen_size += Self::tcg_out(1, 4, mc);
+        gen_size += Self::tcg_modrm_64bit_out(X86Opcode::OR_EV_GV, X86ModRM::MOD_10_DISP_RBP, X86TargetRM::RAX, mc);
+        gen_size += Self::tcg_out(emu.calc_fcsr_relat_address() as u64, 4, mc);
+
+        gen_size = Self::tcg_gen_jmp_label(gen_size, mc, label);
+        Self::tcg_set_fwd_jcc(mc, &slow_path);
+
+        return gen_size;
+    }
+
+    // aes64es/esm/ds/dsm/im with AES-NI and RV64 clmul/clmulh with
+    // PCLMULQDQ. The state rs2:rs1 is loaded into xmm0 and the round key is
+    // zero. The result is stored to rd and the helper following this op is
+    // skipped. Without the host feature, or with host_crypto off, nothing
+    // is emitted and the helper computes the result.
+    fn tcg_gen_crypto_sse(emu: &mut EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>, crypto_op: X86CryptoOpcode) -> usize {
+        let arg0 = tcg.arg0.unwrap();
+        let arg1 = tcg.arg1.unwrap();
+        let arg2 = tcg.arg2.unwrap();
+
+        assert_eq!(arg0.t, TCGvType::Register);
+        assert_eq!(arg1.t, TCGvType::Register);
+        assert_eq!(arg2.t, TCGvType::Register);
+
+        let label = match &tcg.label {
+            Some(l) => l,
+            None => panic!("Label is not defined."),
+        };
+
+        let mut gen_size: usize = pc_address as usize;
+
+        let is_clmul = crypto_op == X86CryptoOpcode::PCLMULQDQ;
+        let supported = if is_clmul { is_x86_feature_detected!("pclmulqdq") } else { is_x86_feature_detected!("aes") };
+        if !supported || !emu.m_arg_config.host_crypto || arg0.value == 0 {
+            return gen_size;
+        }
+
+        // movsd xmm0, [rs1]
+        gen_size += Self::tcg_gen_sse_rbp(Some(0xf2), X86SseOpcode::MOVS_XMM_EV, 0, emu.calc_gpr_relat_address(arg1.value), mc);
+        if is_clmul {
+            // movsd xmm1, [rs2]
+            gen_size += Self::tcg_gen_sse_rbp(Some(0xf2), X86SseOpcode::MOVS_XMM_EV, 1, emu.calc_gpr_relat_address(arg2.value), mc);
+            // pclmulqdq xmm0, xmm1, 0
+            gen_size += Self::tcg_out(0x66, 1, mc);
+            gen_size += Self::tcg_out(0x0f, 1, mc);
+            gen_size += Self::tcg_out(0x3a, 1, mc);
+            gen_size += Self::tcg_out(crypto_op as u64, 1, mc);
+            gen_size += Self::tcg_out(X86ModRM::MOD_11_DISP_RCX as u64, 1, mc);
+            gen_size += Self::tcg_out(0, 1, mc);
+        } else {
+            // movhps xmm0, [rs2] ; pxor xmm1, xmm1
+            gen_size += Self::tcg_gen_sse_rbp(None, X86SseOpcode::MOVHPS_XMM_EV, 0, emu.calc_gpr_relat_address(arg2.value), mc);
+            gen_size += Self::tcg_out(0x66, 1, mc);
+            gen_size += Self::tcg_out(0x0f, 1, mc);
+            gen_size += Self::tcg_out(X86SseOpcode::PXOR as u64, 1, mc);
+            gen_size += Self::tcg_out(X86ModRM::MOD_11_DISP_RCX as u64 | (1 << 3), 1, mc);
+            // aesxxx xmm0, xmm1 / aesimc xmm0, xmm0
+            let modrm = if crypto_op == X86CryptoOpcode::AESIMC { X86ModRM::MOD_11_DISP_RAX } else { X86ModRM::MOD_11_DISP_RCX };
+            gen_size += Self::tcg_out(0x66, 1, mc);
+            gen_size += Self::tcg_out(0x0f, 1, mc);
+            gen_size += Self::tcg_out(0x38, 1, mc);
+            gen_size += Self::tcg_out(crypto_op as u64, 1, mc);
+            gen_size += Self::tcg_out(modrm as u64, 1, mc);
+        }
+        if tcg.op == Some(TCGOpcode::CLMULH_64BIT) {
+            gen_size += Self::tcg_gen_sse_rbp(None, X86SseOpcode::MOVHPS_EV_XMM, 0, emu.calc_gpr_relat_address(arg0.value), mc);
+        } else {
+            gen_size += Self::tcg_gen_sse_rbp(Some(0xf2), X86SseOpcode::MOVS_EV_XMM, 0, emu.calc_gpr_relat_address(arg0.value), mc);
+        }
+
+        // rd was written behind the register cache
+        emu.m_gpr_usage_list = [None; 32];
+        emu.m_x86reg_usage_list = [None; X86TargetRM::SENTINEL as usize];
+
+        gen_size = Self::tcg_gen_jmp_label(gen_size, mc, label);
+        return gen_size;
+    }
+
+    // FMIN/FMAX on SSE2. MINS/MAXS differ from RISC-V for NaNs and signed
+    // zeros, so unordered or equal operands go to the softfloat helper
+    // following this op. Ordered, unequal operands raise no flags.
+    fn tcg_gen_float_minmax_sse(emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>, sse_op: X86SseOpcode, is_64bit: bool) -> usize {
+        let arg0 = tcg.arg0.unwrap();
+        let arg1 = tcg.arg1.unwrap();
+        let arg2 = tcg.arg2.unwrap();
+
+        assert_eq!(arg0.t, TCGvType::Register);
+        assert_eq!(arg1.t, TCGvType::Register);
+        assert_eq!(arg2.t, TCGvType::Register);
+
+        let label = match &tcg.label {
+            Some(l) => l,
+            None => panic!("Label is not defined."),
+        };
+
+        let mut gen_size: usize = pc_address as usize;
+        let mut slow_path = vec![];
+
+        let prefix = if is_64bit { 0xf2 } else { 0xf3 };
+        let ucomis_prefix = if is_64bit { Some(0x66) } else { None };
+
+        if !is_64bit {
+            gen_size += Self::tcg_gen_check_boxed_32bit(emu, arg1.value, mc, &mut slow_path);
+            gen_size += Self::tcg_gen_check_boxed_32bit(emu, arg2.value, mc, &mut slow_path);
+        }
+
+        gen_size += Self::tcg_gen_sse_rbp(Some(prefix), X86SseOpcode::MOVS_XMM_EV, 0, emu.calc_fregs_relat_address(arg1.value), mc);
+        gen_size += Self::tcg_gen_sse_rbp(ucomis_prefix, X86SseOpcode::UCOMIS, 0, emu.calc_fregs_relat_address(arg2.value), mc);
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JP_rel16_32, mc, &mut slow_path);
+        gen_size += Self::tcg_gen_fwd_jcc(X86Opcode::JE_rel16_32, mc, &mut slow_path);
+
+        gen_size += Self::tcg_gen_sse_rbp(Some(prefix), sse_op, 0, emu.calc_fregs_relat_address(arg2.value), mc);
+        gen_size += Self::tcg_gen_store_fregs_xmm0(emu, arg0.value, is_64bit, mc);
+
+        gen_size = Self::tcg_gen_jmp_label(gen_size, mc, label);
+        Self::tcg_set_fwd_jcc(mc, &slow_path);
+
+        return gen_size;
+    }
+
     fn tcg_gen_cmp_eq(_emu: &EmuEnv, pc_address: u64, tcg: &TCGOp, mc: &mut Vec<u8>) -> usize {
         let mut gen_size: usize = pc_address as usize;
 
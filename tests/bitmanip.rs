@@ -0,0 +1,125 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::{EmuEnv, XlenEnum};
+
+const OP: u32 = 0x33;
+const OP_IMM: u32 = 0x13;
+const OP_32: u32 = 0x3b;
+const OP_IMM_32: u32 = 0x1b;
+
+// rd = x7, rs1 = x5, rs2 = x6
+fn exec(emu: &mut EmuEnv, inst: u32, rs1: u64, rs2: u64) -> u64 {
+    emu.m_iregs[5] = rs1;
+    emu.m_iregs[6] = rs2;
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    emu.m_iregs[7]
+}
+
+fn rr(funct7: u32, funct3: u32, opcode: u32) -> u32 {
+    r_type(funct7, 6, 5, funct3, 7, opcode)
+}
+
+fn ri(imm: i32, funct3: u32, opcode: u32) -> u32 {
+    i_type(imm, 5, funct3, 7, opcode)
+}
+
+// clz, ctz and cpop are encoded as funct7 0x30 with the operation in rs2
+fn unary(op: u32, opcode: u32) -> u32 {
+    r_type(0x30, op, 5, 0b001, 7, opcode)
+}
+
+#[test]
+fn clz() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM), 0x0000_0000_0001_0000, 0), 47);
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM), 0, 0), 64);
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM), u64::MAX, 0), 0);
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM_32), 0x0001_0000, 0), 15);
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM_32), 0xffff_ffff_0000_0000, 0), 32);
+    assert_eq!(exec(&mut emu, unary(0, OP_IMM_32), 0xffff_ffff_0000_0001, 0), 31);
+}
+
+#[test]
+fn ctz() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(1, OP_IMM), 0x100, 0), 8);
+    assert_eq!(exec(&mut emu, unary(1, OP_IMM), 0, 0), 64);
+    assert_eq!(exec(&mut emu, unary(1, OP_IMM), 1 << 63, 0), 63);
+    assert_eq!(exec(&mut emu, unary(1, OP_IMM_32), 0xffff_0000_0000_0000, 0), 32);
+    assert_eq!(exec(&mut emu, unary(1, OP_IMM_32), 0x8000_0000, 0), 31);
+}
+
+#[test]
+fn cpop() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(2, OP_IMM), 0xf0f0_0000_0000_0001, 0), 9);
+    assert_eq!(exec(&mut emu, unary(2, OP_IMM), 0, 0), 0);
+    assert_eq!(exec(&mut emu, unary(2, OP_IMM), u64::MAX, 0), 64);
+    assert_eq!(exec(&mut emu, unary(2, OP_IMM_32), 0xffff_ffff_0000_0007, 0), 3);
+    assert_eq!(exec(&mut emu, unary(2, OP_IMM_32), 0xffff_ffff, 0), 32);
+}
+
+#[test]
+fn rev8_orc_b() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, ri(0x6b8, 0b101, OP_IMM), 0x0102_0304_0506_0708, 0), 0x0807_0605_0403_0201);
+    assert_eq!(exec(&mut emu, ri(0x287, 0b101, OP_IMM), 0x0001_0000_8000_0010, 0), 0x00ff_0000_ff00_00ff);
+    assert_eq!(exec(&mut emu, ri(0x287, 0b101, OP_IMM), 0, 0), 0);
+}
+
+#[test]
+fn rev8_orc_b_rv32() {
+    let mut emu = new_emu();
+    emu.m_xlen = XlenEnum::Rv32;
+    // Results are sign-extended from bit 31
+    assert_eq!(exec(&mut emu, ri(0x287, 0b101, OP_IMM), 0x0100_0000, 0), 0xffff_ffff_ff00_0000);
+    assert_eq!(exec(&mut emu, ri(0x287, 0b101, OP_IMM), 0x0000_0100, 0), 0x0000_ff00);
+    assert_eq!(exec(&mut emu, ri(0x698, 0b101, OP_IMM), 0x0000_0080, 0), 0xffff_ffff_8000_0000);
+}
+
+#[test]
+fn rotate() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, rr(0x30, 0b001, OP), 0x8000_0000_0000_0001, 4), 0x18);
+    assert_eq!(exec(&mut emu, rr(0x30, 0b101, OP), 1, 4), 1 << 60);
+    // Only the low 6 bits of the amount are used
+    assert_eq!(exec(&mut emu, rr(0x30, 0b101, OP), 1, 68), 1 << 60);
+    assert_eq!(exec(&mut emu, ri(0x600 | 4, 0b101, OP_IMM), 1, 0), 1 << 60);
+    // The word forms rotate the low 32 bits and sign-extend
+    assert_eq!(exec(&mut emu, rr(0x30, 0b001, OP_32), 0xffff_ffff_8000_0001, 4), 0x18);
+    assert_eq!(exec(&mut emu, rr(0x30, 0b101, OP_32), 1, 1), 0xffff_ffff_8000_0000);
+    assert_eq!(exec(&mut emu, ri(0x600 | 1, 0b101, OP_IMM_32), 1, 0), 0xffff_ffff_8000_0000);
+}
+
+#[test]
+fn zba() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, rr(0x10, 0b010, OP), 3, 100), 106);
+    assert_eq!(exec(&mut emu, rr(0x10, 0b100, OP), 3, 100), 112);
+    assert_eq!(exec(&mut emu, rr(0x10, 0b110, OP), 3, 100), 124);
+    // The .uw forms zero-extend rs1 from 32 bits
+    assert_eq!(exec(&mut emu, rr(0x04, 0b000, OP_32), 0xffff_ffff_8000_0000, 1), 0x8000_0001);
+    assert_eq!(exec(&mut emu, rr(0x10, 0b010, OP_32), 0xffff_ffff_8000_0000, 0), 0x1_0000_0000);
+    assert_eq!(exec(&mut emu, rr(0x10, 0b100, OP_32), 0xffff_ffff_8000_0000, 0), 0x2_0000_0000);
+    assert_eq!(exec(&mut emu, rr(0x10, 0b110, OP_32), 0xffff_ffff_8000_0000, 8), 0x4_0000_0008);
+    assert_eq!(exec(&mut emu, ri(0x080 | 4, 0b001, OP_IMM_32), 0xffff_ffff_8000_0001, 0), 0x8_0000_0010);
+}
+
+#[test]
+fn zbs() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, rr(0x24, 0b001, OP), 0xff, 3), 0xf7);
+    assert_eq!(exec(&mut emu, rr(0x14, 0b001, OP), 0, 63), 1 << 63);
+    assert_eq!(exec(&mut emu, rr(0x34, 0b001, OP), 0xff, 0), 0xfe);
+    assert_eq!(exec(&mut emu, rr(0x24, 0b101, OP), 0x10, 4), 1);
+    assert_eq!(exec(&mut emu, rr(0x24, 0b101, OP), 0x10, 68), 1);
+    assert_eq!(exec(&mut emu, rr(0x24, 0b101, OP), 0x10, 5), 0);
+    assert_eq!(exec(&mut emu, ri(0x480 | 3, 0b001, OP_IMM), 0xff, 0), 0xf7);
+    assert_eq!(exec(&mut emu, ri(0x280 | 63, 0b001, OP_IMM), 0, 0), 1 << 63);
+    assert_eq!(exec(&mut emu, ri(0x680, 0b001, OP_IMM), 0xff, 0), 0xfe);
+    assert_eq!(exec(&mut emu, ri(0x480 | 40, 0b101, OP_IMM), 1 << 40, 0), 1);
+}
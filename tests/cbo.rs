@@ -0,0 +1,84 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+// cbo.zero (x5)
+const CBO_ZERO: u64 = 0x0042_a00f;
+const CBZE: i64 = 1 << 7;
+
+// Sv39 tables : root at 0x8001_0000, VPN[1] at 0x8001_1000, VPN[0] at 0x8001_2000.
+// VA 0x4000_1000 maps read-only to 0x8003_0000.
+fn enable_sv39_read_only(emu: &mut EmuEnv) {
+    emu.write_mem_8byte(0x8001_0000 + 8, (0x80011 << 10) | 1);
+    emu.write_mem_8byte(0x8001_1000, (0x80012 << 10) | 1);
+    // V, R, A
+    emu.write_mem_8byte(0x8001_2000 + 8, (0x80030 << 10) | 0x43);
+    emu.m_csr.csrrw(CsrAddr::Satp, (8 << 60) | 0x80010);
+}
+
+fn cbo_zero(emu: &mut EmuEnv, rs1_data: u64) -> usize {
+    emu.m_iregs[5] = rs1_data;
+    EmuEnv::helper_func_cbo(emu, CBO_ZERO, 5, 0, CODE_BASE)
+}
+
+#[test]
+fn cbo_zero_clears_the_aligned_block() {
+    let mut emu = new_emu();
+    for offset in (0..0x100).step_by(8) {
+        emu.write_mem_8byte(0x8000_2000 + offset, u64::MAX);
+    }
+    emu.m_iregs[5] = 0x8000_2047;
+    run(&mut emu, &[CBO_ZERO as u32]);
+    for offset in (0x40..0x80).step_by(8) {
+        assert_eq!(emu.read_mem_8byte(0x8000_2000 + offset), 0);
+    }
+    assert_eq!(emu.read_mem_8byte(0x8000_2038), u64::MAX);
+    assert_eq!(emu.read_mem_8byte(0x8000_2080), u64::MAX);
+}
+
+#[test]
+fn cbo_zero_store_page_fault() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Menvcfg, CBZE);
+    enable_sv39_read_only(&mut emu);
+    emu.m_priv = PrivMode::Supervisor;
+    assert_ne!(cbo_zero(&mut emu, 0x4000_1010), 0);
+    assert_eq!(mcause(&mut emu), 15);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x4000_1010);
+    assert_eq!(emu.read_mem_8byte(0x8003_0000), 0);
+}
+
+#[test]
+fn cbo_denied_by_envcfg() {
+    let mut emu = new_emu();
+    // menvcfg denies : illegal instruction below M-mode
+    emu.m_priv = PrivMode::Supervisor;
+    assert_ne!(cbo_zero(&mut emu, 0x8000_2000), 0);
+    assert_eq!(mcause(&mut emu), 2);
+    // senvcfg denies in U-mode
+    emu.m_csr.csrrw(CsrAddr::Menvcfg, CBZE);
+    emu.m_priv = PrivMode::User;
+    assert_ne!(cbo_zero(&mut emu, 0x8000_2000), 0);
+    assert_eq!(mcause(&mut emu), 2);
+    // henvcfg denies in VS-mode : virtual instruction
+    emu.m_priv = PrivMode::Supervisor;
+    emu.set_virt(true);
+    assert_ne!(cbo_zero(&mut emu, 0x8000_2000), 0);
+    assert_eq!(mcause(&mut emu), 22);
+    // senvcfg denies in VU-mode : virtual instruction
+    emu.m_csr.csrrw(CsrAddr::Henvcfg, CBZE);
+    emu.m_priv = PrivMode::User;
+    emu.set_virt(true);
+    assert_ne!(cbo_zero(&mut emu, 0x8000_2000), 0);
+    assert_eq!(mcause(&mut emu), 22);
+    // Enabled at every level
+    emu.m_csr.csrrw(CsrAddr::Senvcfg, CBZE);
+    emu.m_priv = PrivMode::User;
+    emu.set_virt(true);
+    assert_eq!(cbo_zero(&mut emu, 0x8000_2000), 0);
+}
@@ -0,0 +1,140 @@
+// Shared by the integration tests that run guest code without an ELF image.
+#![allow(dead_code)]
+
+use dydra::emu_env::{ArgConfig, EmuEnv};
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+pub const CODE_BASE: u64 = 0x8000_0000;
+pub const MTVEC: u64 = 0x8000_1000;
+pub const STVEC: u64 = 0x8000_2000;
+pub const VSTVEC: u64 = 0x8000_3000;
+
+// Single-step so that every instruction is its own translated block
+pub fn new_emu() -> EmuEnv {
+    new_emu_with(ArgConfig { step: true, ..Default::default() })
+}
+
+pub fn new_emu_with(arg_config: ArgConfig) -> EmuEnv {
+    let mut emu = EmuEnv::new(arg_config);
+    emu.m_priv = PrivMode::Machine;
+    emu.m_csr.csrrw(CsrAddr::Mtvec, MTVEC as i64);
+    emu.m_csr.csrrw(CsrAddr::Stvec, STVEC as i64);
+    emu.m_csr.csrrw(CsrAddr::Vstvec, VSTVEC as i64);
+    emu
+}
+
+// Load code at CODE_BASE and run one block per instruction word
+pub fn run(emu: &mut EmuEnv, code: &[u32]) {
+    emu.load_code(CODE_BASE, code);
+    emu.run_blocks(code.len());
+}
+
+pub fn mcause(emu: &mut EmuEnv) -> i64 {
+    emu.m_csr.csrrs(CsrAddr::Mcause, 0)
+}
+
+pub fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+pub fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (((imm as u32) & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+pub fn s_type(imm: i32, rs2: u32, rs1: u32, funct3: u32, opcode: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 5) & 0x7f) << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | ((imm & 0x1f) << 7) | opcode
+}
+
+pub fn b_type(imm: i32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 12) & 1) << 31) | (((imm >> 5) & 0x3f) << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12)
+        | (((imm >> 1) & 0xf) << 8) | (((imm >> 11) & 1) << 7) | 0x63
+}
+
+pub fn j_type(imm: i32, rd: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 20) & 1) << 31) | (((imm >> 1) & 0x3ff) << 21) | (((imm >> 11) & 1) << 20) | (((imm >> 12) & 0xff) << 12)
+        | (rd << 7) | 0x6f
+}
+
+pub fn csrrw(rd: u32, csr: u32, rs1: u32) -> u32 {
+    i_type(csr as i32, rs1, 0b001, rd, 0x73)
+}
+
+pub fn csrrs(rd: u32, csr: u32, rs1: u32) -> u32 {
+    i_type(csr as i32, rs1, 0b010, rd, 0x73)
+}
+
+pub fn csrrc(rd: u32, csr: u32, rs1: u32) -> u32 {
+    i_type(csr as i32, rs1, 0b011, rd, 0x73)
+}
+
+pub fn csrrwi(rd: u32, csr: u32, uimm: u32) -> u32 {
+    i_type(csr as i32, uimm, 0b101, rd, 0x73)
+}
+
+pub fn csrrci(rd: u32, csr: u32, uimm: u32) -> u32 {
+    i_type(csr as i32, uimm, 0b111, rd, 0x73)
+}
+
+// mstatus.FS = Initial
+pub fn enable_fp(emu: &mut EmuEnv) {
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 1 << 13);
+}
+
+pub fn new_fp_emu() -> EmuEnv {
+    let mut emu = new_emu();
+    enable_fp(&mut emu);
+    emu
+}
+
+pub fn fp_r(funct7: u32, rs2: u32, rs1: u32, rm: u32, rd: u32) -> u32 {
+    r_type(funct7, rs2, rs1, rm, rd, 0x53)
+}
+
+pub const BOX_S: u64 = 0xffff_ffff_0000_0000;
+
+pub fn fflags(emu: &mut EmuEnv) -> i64 {
+    emu.m_csr.csrrs(CsrAddr::FFlags, 0)
+}
+
+// Sv39 tables in guest RAM : root (VPN[2]) at 0x8001_0000, one VPN[1]
+// table at 0x8001_1000 and one VPN[0] table at 0x8001_2000.
+pub const ROOT_PPN: u64 = 0x80010;
+pub const L1_PPN: u64 = 0x80011;
+pub const L0_PPN: u64 = 0x80012;
+
+pub const V: u64 = 1 << 0;
+pub const R: u64 = 1 << 1;
+pub const W: u64 = 1 << 2;
+pub const X: u64 = 1 << 3;
+pub const U: u64 = 1 << 4;
+pub const G: u64 = 1 << 5;
+pub const A: u64 = 1 << 6;
+pub const D: u64 = 1 << 7;
+
+pub fn set_pte(emu: &EmuEnv, table_ppn: u64, idx: u64, pte: u64) {
+    emu.write_mem_8byte((table_ppn << 12) + idx * 8, pte);
+}
+
+pub fn pointer(ppn: u64) -> u64 {
+    (ppn << 10) | V
+}
+
+pub fn leaf(ppn: u64, flags: u64) -> u64 {
+    (ppn << 10) | V | flags
+}
+
+// VA 0x4000_0000 - 0x401f_ffff goes through the VPN[0] table
+pub fn setup_tables(emu: &EmuEnv) {
+    set_pte(emu, ROOT_PPN, 1, pointer(L1_PPN));
+    set_pte(emu, L1_PPN, 0, pointer(L0_PPN));
+}
+
+// satp = Sv39 with the root above, running in S-mode
+pub fn enable_sv39(emu: &mut EmuEnv) {
+    emu.m_csr.csrrw(CsrAddr::Satp, (8 << 60) | ROOT_PPN as i64);
+    emu.m_priv = PrivMode::Supervisor;
+}
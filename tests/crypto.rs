@@ -0,0 +1,132 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+
+const A: u64 = 0x0123_4567_89ab_cdef;
+const B: u64 = 0xfedc_ba98_7654_3210;
+
+// rd = x7, rs1 = x5, rs2 = x6
+fn exec(emu: &mut EmuEnv, inst: u32, rs1: u64, rs2: u64) -> u64 {
+    emu.m_iregs[5] = rs1;
+    emu.m_iregs[6] = rs2;
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    emu.m_iregs[7]
+}
+
+fn op(funct7: u32, funct3: u32) -> u32 {
+    r_type(funct7, 6, 5, funct3, 7, 0x33)
+}
+
+fn unary(imm12: i32) -> u32 {
+    i_type(imm12, 5, 0b001, 7, 0x13)
+}
+
+// Each vector runs with the host AES-NI/PCLMULQDQ path, where the host has
+// it, and with the helpers only.
+fn for_each_config<F: Fn(&mut EmuEnv)>(f: F) {
+    for host_crypto in [true, false].iter() {
+        let mut emu = new_emu();
+        emu.m_arg_config.host_crypto = *host_crypto;
+        f(&mut emu);
+    }
+}
+
+#[test]
+fn aes64() {
+    for_each_config(|emu| {
+        assert_eq!(exec(emu, op(0x19, 0), A, B), 0xa786_2385_bb20_6edf);
+        assert_eq!(exec(emu, op(0x1b, 0), A, B), 0x6443_f555_5927_d88c);
+        assert_eq!(exec(emu, op(0x1d, 0), A, B), 0x0f93_800a_09fd_c061);
+        assert_eq!(exec(emu, op(0x1f, 0), A, B), 0xa944_0af1_1b83_78b5);
+        assert_eq!(exec(emu, unary(0x300), A, 0), 0xc66c_8228_4ee4_0aa0);
+        // rd = x0 is not written
+        emu.m_iregs[5] = A;
+        run(emu, &[r_type(0x19, 6, 5, 0, 0, 0x33)]);
+        assert_eq!(emu.m_iregs[0], 0);
+    });
+}
+
+#[test]
+fn aes64_key_schedule() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(0x310), B, 0), 0x46bb_86f5_46bb_86f5);
+    assert_eq!(exec(&mut emu, unary(0x31a), B, 0), 0xbb86_f446_bb86_f446);
+    assert_eq!(exec(&mut emu, op(0x3f, 0), A, B), 0x89ab_cdef_7777_7777);
+}
+
+// FIPS-197 AES-128 : round 1 key and the ciphertext of the example block,
+// from aes64ks1i/ks2 and aes64esm/es.
+#[test]
+fn aes128_encrypt_block() {
+    for_each_config(|emu| {
+        let mut rk = vec![(0x0706_0504_0302_0100u64, 0x0f0e_0d0c_0b0a_0908u64)];
+        for rnum in 0..10 {
+            let (k0, k1) = rk[rnum];
+            let t = exec(emu, unary(0x310 | rnum as i32), k1, 0);
+            let k0 = exec(emu, op(0x3f, 0), t, k0);
+            let k1 = exec(emu, op(0x3f, 0), k0, k1);
+            rk.push((k0, k1));
+        }
+        assert_eq!(rk[1].0 & 0xffff_ffff, 0xfd74_aad6);
+        let (mut s0, mut s1) = (0x7766_5544_3322_1100 ^ rk[0].0, 0xffee_ddcc_bbaa_9988 ^ rk[0].1);
+        for round in 1..11 {
+            let funct7 = if round == 10 { 0x19 } else { 0x1b };
+            let n0 = exec(emu, op(funct7, 0), s0, s1);
+            let n1 = exec(emu, op(funct7, 0), s1, s0);
+            s0 = n0 ^ rk[round].0;
+            s1 = n1 ^ rk[round].1;
+        }
+        assert_eq!((s0, s1), (0x3004_7b6a_d8e0_c469, 0x5ac5_b470_80b7_cdd8));
+    });
+}
+
+#[test]
+fn sha2() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(0x102), 0x6a09_e667, 0), 0xffff_ffff_ba0c_f582);
+    assert_eq!(exec(&mut emu, unary(0x103), 0x6a09_e667, 0), 0xffff_ffff_cfe5_da3c);
+    assert_eq!(exec(&mut emu, unary(0x100), 0x6a09_e667, 0), 0xffff_ffff_ce20_b47e);
+    assert_eq!(exec(&mut emu, unary(0x101), 0x510e_527f, 0), 0x3587_272b);
+    let h0 = 0x6a09_e667_f3bc_c908;
+    assert_eq!(exec(&mut emu, unary(0x106), h0, 0), 0x3dba_e919_51ca_a1df);
+    assert_eq!(exec(&mut emu, unary(0x107), h0, 0), 0xc8c6_19e7_3ee4_4510);
+    assert_eq!(exec(&mut emu, unary(0x104), h0, 0), 0x08c4_db56_aac8_0c2a);
+    assert_eq!(exec(&mut emu, unary(0x105), h0, 0), 0x259a_6cc1_6433_36ef);
+}
+
+#[test]
+fn sm3_sm4() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, unary(0x108), 0x6a09_e667, 0), 0xffff_ffff_b50b_fca0);
+    assert_eq!(exec(&mut emu, unary(0x109), 0x6a09_e667, 0), 0xffff_ffff_aa8f_5790);
+    // sm4ed / sm4ks with bs = 0 and 3 : sbox[0x00] = 0xd6, sbox[0x01] = 0x90, sbox[0xff] = 0x48
+    assert_eq!(exec(&mut emu, op(0x18, 0), 0x0123_4567, 0x00), 0x5a78_90e9);
+    assert_eq!(exec(&mut emu, op(0x78, 0), 0, 0xff00_0000), 0x6821_2149);
+    assert_eq!(exec(&mut emu, op(0x1a, 0), 0, 0x01), 0x0012_4890);
+    assert_eq!(exec(&mut emu, op(0x7a, 0), 0x89ab_cdef, 0xff00_0000), 0xffff_ffff_c1ab_c4cb);
+}
+
+#[test]
+fn clmul() {
+    for_each_config(|emu| {
+        assert_eq!(exec(emu, op(0x05, 0b001), A, B), 0x40a0_7898_28c8_10f0);
+        assert_eq!(exec(emu, op(0x05, 0b011), A, B), 0x00e0_38d8_6888_50b0);
+        assert_eq!(exec(emu, op(0x05, 0b001), u64::MAX, 1 << 63), 1 << 63);
+        assert_eq!(exec(emu, op(0x05, 0b011), u64::MAX, 1 << 63), u64::MAX >> 1);
+    });
+}
+
+#[test]
+fn zbkb_zbkx() {
+    let mut emu = new_emu();
+    assert_eq!(exec(&mut emu, i_type(0x687, 5, 0b101, 7, 0x13), A, 0), 0x80c4_a2e6_91d5_b3f7);
+    assert_eq!(exec(&mut emu, op(0x04, 0b100), A, B), 0x7654_3210_89ab_cdef);
+    assert_eq!(exec(&mut emu, op(0x04, 0b111), A, B), 0x10ef);
+    assert_eq!(exec(&mut emu, r_type(0x04, 6, 5, 0b100, 7, 0x3b), A, B), 0x3210_cdef);
+    assert_eq!(exec(&mut emu, op(0x14, 0b100), A, 0x0801_0203_0405_0607), 0x00cd_ab89_6745_2301);
+    assert_eq!(exec(&mut emu, op(0x14, 0b010), A, 0x0f1e_2d3c_4b5a_6978), 0xf0e1_d2c3_b4a5_9687);
+}
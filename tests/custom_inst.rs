@@ -0,0 +1,163 @@
+extern crate dydra;
+
+mod common;
+
+use std::cell::Cell;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use common::*;
+use dydra::emu_env::{ArgConfig, EmuEnv};
+use dydra::instr_info::InstrInfo;
+use dydra::target::riscv::riscv::{PrivMode, TranslateRiscv};
+use dydra::target::riscv::riscv_csr::CsrAddr;
+use dydra::target::riscv::riscv_custom::{CustomInst, HelperFunc};
+use dydra::tcg::tcg::TCGOp;
+
+// acc rd, rs1, rs2 in custom-0 : rd = rs1 + rs2 + the first CSR of the range.
+// funct3 selects the instance so that several can be registered.
+struct Acc {
+    funct3: u32,
+    csrs: RangeInclusive<u64>,
+    csr_writes: Cell<u32>,
+    disassembled: Cell<u32>,
+}
+
+impl Acc {
+    fn new(funct3: u32, csrs: RangeInclusive<u64>) -> Rc<Acc> {
+        Rc::new(Acc { funct3, csrs, csr_writes: Cell::new(0), disassembled: Cell::new(0) })
+    }
+
+    fn helper_func_acc(emu: &mut EmuEnv, inst: u64, rs1: u64, rs2: u64, _guest_pc: u64) -> usize {
+        let rd = ((inst >> 7) & 0x1f) as usize;
+        let acc = *emu.m_custom_csr.get(&0x800).unwrap_or(&0) as u64;
+        if rd != 0 {
+            emu.m_iregs[rd] = emu.m_iregs[rs1 as usize].wrapping_add(emu.m_iregs[rs2 as usize]).wrapping_add(acc);
+        }
+        0
+    }
+}
+
+impl CustomInst for Acc {
+    fn name(&self) -> &str {
+        "acc"
+    }
+
+    fn mask(&self) -> u32 {
+        0x0000_707f
+    }
+
+    fn match_bits(&self) -> u32 {
+        (self.funct3 << 12) | 0x0b
+    }
+
+    fn helper(&self) -> Option<HelperFunc> {
+        Some(Acc::helper_func_acc)
+    }
+
+    fn csr_range(&self) -> Option<RangeInclusive<u64>> {
+        Some(self.csrs.clone())
+    }
+
+    // The accumulator is 32 bits wide
+    fn csr_write(&self, _csr_addr: u64, _old: i64, new: i64) -> i64 {
+        self.csr_writes.set(self.csr_writes.get() + 1);
+        new & 0xffff_ffff
+    }
+
+    fn translate(&self, trans: &mut TranslateRiscv, inst: &InstrInfo, helper_idx: Option<usize>) -> Vec<TCGOp> {
+        trans.gen_custom_helper_call(helper_idx.unwrap(), inst)
+    }
+
+    fn disassemble(&self, inst: u32) -> String {
+        self.disassembled.set(self.disassembled.get() + 1);
+        format!("acc x{}, x{}, x{}", (inst >> 7) & 0x1f, (inst >> 15) & 0x1f, (inst >> 20) & 0x1f)
+    }
+}
+
+fn acc_inst(funct3: u32) -> u32 {
+    r_type(0, 6, 5, funct3, 7, 0x0b)
+}
+
+// csrrs with rs1 = x0 : read only
+fn csr_read(emu: &mut EmuEnv, csr: u64) -> usize {
+    EmuEnv::helper_func_csrrs(emu, 7, 0, csr, CODE_BASE)
+}
+
+fn csr_write(emu: &mut EmuEnv, csr: u64) -> usize {
+    emu.m_iregs[5] = 1;
+    EmuEnv::helper_func_csrrw(emu, 7, 5, csr, CODE_BASE)
+}
+
+#[test]
+fn custom_inst_decode_translate_run() {
+    let mut emu = EmuEnv::new(ArgConfig { step: true, dump_guest: true, ..Default::default() });
+    emu.m_priv = PrivMode::Machine;
+    emu.m_csr.csrrw(CsrAddr::Mtvec, MTVEC as i64);
+    let acc = Acc::new(0, 0x800..=0x801);
+    emu.register_custom_inst(acc.clone());
+
+    emu.m_iregs[5] = 0x1_0000_0005;
+    emu.m_iregs[6] = 0x10;
+    // csrrw x0, 0x800, x5 ; acc x7, x5, x6
+    run(&mut emu, &[csrrw(0, 0x800, 5), acc_inst(0)]);
+    assert_eq!(*emu.m_custom_csr.get(&0x800).unwrap(), 5);
+    assert_eq!(emu.m_iregs[7], 0x1_0000_0005 + 0x10 + 5);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+    assert_eq!(acc.csr_writes.get(), 1);
+    assert!(acc.disassembled.get() > 0);
+    assert_eq!(acc.disassemble(acc_inst(0)), "acc x7, x5, x6");
+}
+
+#[test]
+fn custom_csr_set_clear_with_x0_does_not_write() {
+    let mut emu = new_emu();
+    let acc = Acc::new(0, 0x800..=0x801);
+    emu.register_custom_inst(acc.clone());
+    assert_eq!(csr_read(&mut emu, 0x800), 0);
+    assert_eq!(EmuEnv::helper_func_csrrc(&mut emu, 7, 0, 0x800, CODE_BASE), 0);
+    assert_eq!(EmuEnv::helper_func_csrrsi(&mut emu, 7, 0, 0x800, CODE_BASE), 0);
+    assert_eq!(acc.csr_writes.get(), 0);
+    assert_eq!(csr_write(&mut emu, 0x800), 0);
+    assert_eq!(acc.csr_writes.get(), 1);
+}
+
+#[test]
+fn custom_csr_privilege() {
+    let mut emu = new_emu();
+    // 0x7c0 : M-mode, 0x9c0 : S-mode
+    emu.register_custom_inst(Acc::new(0, 0x7c0..=0x7c1));
+    emu.register_custom_inst(Acc::new(1, 0x9c0..=0x9c1));
+    assert_eq!(csr_write(&mut emu, 0x7c0), 0);
+    emu.m_priv = PrivMode::Supervisor;
+    assert_ne!(csr_read(&mut emu, 0x7c0), 0);
+    assert_eq!(mcause(&mut emu), 2);
+    emu.m_priv = PrivMode::Supervisor;
+    assert_eq!(csr_write(&mut emu, 0x9c0), 0);
+    emu.m_priv = PrivMode::User;
+    assert_ne!(csr_read(&mut emu, 0x9c0), 0);
+    assert_eq!(mcause(&mut emu), 2);
+    // From VU-mode an S-level CSR is a virtual instruction exception, an
+    // M-level one is still illegal
+    emu.m_priv = PrivMode::User;
+    emu.set_virt(true);
+    assert_ne!(csr_read(&mut emu, 0x9c0), 0);
+    assert_eq!(mcause(&mut emu), 22);
+    emu.m_priv = PrivMode::User;
+    emu.set_virt(true);
+    assert_ne!(csr_read(&mut emu, 0x7c0), 0);
+    assert_eq!(mcause(&mut emu), 2);
+}
+
+#[test]
+fn custom_csr_read_only() {
+    let mut emu = new_emu();
+    // 0xcc0 : read-only, U-mode
+    let acc = Acc::new(0, 0xcc0..=0xcc1);
+    emu.register_custom_inst(acc.clone());
+    emu.m_priv = PrivMode::User;
+    assert_eq!(csr_read(&mut emu, 0xcc0), 0);
+    assert_ne!(csr_write(&mut emu, 0xcc0), 0);
+    assert_eq!(mcause(&mut emu), 2);
+    assert_eq!(acc.csr_writes.get(), 0);
+}
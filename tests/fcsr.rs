@@ -0,0 +1,39 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+
+const FFLAGS: u32 = 0x001;
+const FRM: u32 = 0x002;
+const FCSR: u32 = 0x003;
+
+const ONE: u64 = 0x3f80_0000;
+
+#[test]
+fn fflags_accrue() {
+    let mut emu = new_fp_emu();
+    emu.m_fregs[1] = BOX_S | ONE;
+    // 2^-24 : inexact when added to 1.0
+    emu.m_fregs[2] = BOX_S | 0x3380_0000;
+    emu.m_fregs[3] = BOX_S;
+    // fadd.s f4, f1, f2 ; fdiv.s f5, f1, f3 ; fadd.s f6, f1, f1
+    run(&mut emu, &[fp_r(0x00, 2, 1, 0, 4), fp_r(0x0c, 3, 1, 0, 5), fp_r(0x00, 1, 1, 0, 6)]);
+    assert_eq!(fflags(&mut emu), 0x09);
+    assert_eq!(emu.m_fregs[5], BOX_S | 0x7f80_0000);
+}
+
+#[test]
+fn fcsr_frm_fflags_views() {
+    let mut emu = new_fp_emu();
+    // csrrwi frm, 3 ; csrrwi fflags, 0x11 ; csrrs x7, fcsr, x0
+    run(&mut emu, &[csrrwi(0, FRM, 3), csrrwi(0, FFLAGS, 0x11), csrrs(7, FCSR, 0)]);
+    assert_eq!(emu.m_iregs[7], 0x71);
+    // fcsr is 8 bits wide
+    emu.m_iregs[5] = 0x1ff;
+    run(&mut emu, &[csrrw(0, FCSR, 5), csrrs(7, FRM, 0), csrrs(8, FFLAGS, 0), csrrs(9, FCSR, 0)]);
+    assert_eq!([emu.m_iregs[7], emu.m_iregs[8], emu.m_iregs[9]], [0x7, 0x1f, 0xff]);
+    // Clearing fflags leaves frm alone
+    run(&mut emu, &[csrrwi(0, FFLAGS, 0), csrrs(7, FCSR, 0)]);
+    assert_eq!(emu.m_iregs[7], 0xe0);
+}
@@ -0,0 +1,76 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const BOX_H: u64 = 0xffff_ffff_ffff_0000;
+
+#[test]
+fn fmv_nan_boxes() {
+    let mut emu = new_fp_emu();
+    emu.m_iregs[5] = 0x1234_3c00;
+    run(&mut emu, &[fp_r(0x7a, 0, 5, 0, 1)]);
+    assert_eq!(emu.m_fregs[1], BOX_H | 0x3c00);
+    // fmv.x.h sign-extends the 16-bit pattern
+    emu.m_fregs[1] = BOX_H | 0xbc00;
+    run(&mut emu, &[fp_r(0x72, 0, 1, 0, 7)]);
+    assert_eq!(emu.m_iregs[7], 0xffff_ffff_ffff_bc00);
+}
+
+#[test]
+fn flh_fsh() {
+    let mut emu = new_fp_emu();
+    emu.write_mem_4byte(0x8000_2000, 0x0000_3c00);
+    emu.m_iregs[5] = 0x8000_2000;
+    run(&mut emu, &[i_type(0, 5, 0b001, 1, 0x07), s_type(2, 1, 5, 0b001, 0x27)]);
+    assert_eq!(emu.m_fregs[1], BOX_H | 0x3c00);
+    assert_eq!(emu.read_mem_4byte(0x8000_2000), 0x3c00_3c00);
+}
+
+#[test]
+fn unboxed_operand_is_canonical_nan() {
+    let mut emu = new_fp_emu();
+    emu.m_fregs[1] = 0x3c00;
+    emu.m_fregs[2] = BOX_H | 0x3c00;
+    run(&mut emu, &[fp_r(0x02, 2, 1, 0, 3)]);
+    assert_eq!(emu.m_fregs[3], BOX_H | 0x7e00);
+    // A boxed single is not a boxed half either
+    emu.m_fregs[1] = BOX_S | 0x3f80_0000;
+    run(&mut emu, &[fp_r(0x02, 2, 1, 0, 3)]);
+    assert_eq!(emu.m_fregs[3], BOX_H | 0x7e00);
+    emu.m_fregs[1] = BOX_H | 0x3c00;
+    run(&mut emu, &[fp_r(0x02, 2, 1, 0, 3)]);
+    assert_eq!(emu.m_fregs[3], BOX_H | 0x4000);
+}
+
+#[test]
+fn fcvt_h_s() {
+    let mut emu = new_fp_emu();
+    let cases: [(u64, u64, i64); 4] = [
+        (0x3f80_0000, 0x3c00, 0),       // 1.0
+        (0x3eaa_aaab, 0x3555, 0x1),     // 1/3 : inexact
+        (0x477f_f000, 0x7c00, 0x5),     // 65520.0 rounds up to infinity : overflow, inexact
+        (0x7fc0_0000, 0x7e00, 0),       // canonical NaN
+    ];
+    for (single, half, flags) in cases.iter() {
+        emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+        emu.m_fregs[1] = BOX_S | single;
+        run(&mut emu, &[fp_r(0x22, 0, 1, 0, 2)]);
+        assert_eq!(emu.m_fregs[2], BOX_H | half);
+        assert_eq!(fflags(&mut emu), *flags);
+    }
+}
+
+#[test]
+fn fcvt_s_h() {
+    let mut emu = new_fp_emu();
+    emu.m_fregs[1] = BOX_H | 0x3c00;
+    run(&mut emu, &[fp_r(0x20, 2, 1, 0, 2)]);
+    assert_eq!(emu.m_fregs[2], BOX_S | 0x3f80_0000);
+    // Improperly boxed source : canonical NaN
+    emu.m_fregs[1] = 0x3c00;
+    run(&mut emu, &[fp_r(0x20, 2, 1, 0, 2)]);
+    assert_eq!(emu.m_fregs[2], BOX_S | 0x7fc0_0000);
+}
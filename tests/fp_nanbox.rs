@@ -0,0 +1,49 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+
+const ONE: u64 = 0x3f80_0000;
+const CANONICAL_NAN_S: u64 = 0x7fc0_0000;
+
+#[test]
+fn single_writes_are_boxed() {
+    let mut emu = new_fp_emu();
+    emu.write_mem_4byte(0x8000_2000, ONE as u32);
+    emu.m_iregs[5] = 0x8000_2000;
+    emu.m_iregs[6] = 0x1234_5678_4000_0000;
+    // flw f1, 0(x5) ; fmv.w.x f2, x6 ; fadd.s f3, f1, f2
+    run(&mut emu, &[i_type(0, 5, 0b010, 1, 0x07), fp_r(0x78, 0, 6, 0, 2), fp_r(0x00, 2, 1, 0, 3)]);
+    assert_eq!(emu.m_fregs[1], BOX_S | ONE);
+    assert_eq!(emu.m_fregs[2], BOX_S | 0x4000_0000);
+    assert_eq!(emu.m_fregs[3], BOX_S | 0x4040_0000);
+}
+
+#[test]
+fn improperly_boxed_single_is_canonical_nan() {
+    let mut emu = new_fp_emu();
+    emu.m_fregs[1] = ONE;
+    emu.m_fregs[2] = BOX_S | ONE;
+    // fadd.s f3, f1, f2 ; fsgnj.s f4, f1, f2 ; fcvt.d.s f5, f1
+    run(&mut emu, &[fp_r(0x00, 2, 1, 0, 3), fp_r(0x10, 2, 1, 0, 4), fp_r(0x21, 0, 1, 0, 5)]);
+    assert_eq!(emu.m_fregs[3], BOX_S | CANONICAL_NAN_S);
+    assert_eq!(emu.m_fregs[4], BOX_S | CANONICAL_NAN_S);
+    assert_eq!(emu.m_fregs[5], 0x7ff8_0000_0000_0000);
+    // A partially boxed value is not boxed either : fcvt.w.s x7, f1
+    emu.m_fregs[1] = 0xffff_fffe_0000_0000 | ONE;
+    run(&mut emu, &[fp_r(0x60, 0, 1, 1, 7)]);
+    assert_eq!(emu.m_iregs[7], 0x7fff_ffff);
+    assert_eq!(fflags(&mut emu) & 0x10, 0x10);
+}
+
+#[test]
+fn fmv_x_w_and_fsw_ignore_boxing() {
+    let mut emu = new_fp_emu();
+    emu.m_fregs[1] = 0x8000_0000;
+    emu.m_iregs[5] = 0x8000_2000;
+    // fmv.x.w x7, f1 ; fsw f1, 0(x5)
+    run(&mut emu, &[fp_r(0x70, 0, 1, 0, 7), s_type(0, 1, 5, 0b010, 0x27)]);
+    assert_eq!(emu.m_iregs[7], 0xffff_ffff_8000_0000);
+    assert_eq!(emu.read_mem_4byte(0x8000_2000), 0x8000_0000);
+}
@@ -0,0 +1,80 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const FRM: u32 = 0x002;
+
+const ONE: u64 = 0x3f80_0000;
+// 2^-24 : half an ulp of 1.0
+const HALF_ULP: u64 = 0x3380_0000;
+
+// fadd.s f3, f1, f2 with the given rm
+fn fadd_s(emu: &mut EmuEnv, rm: u32, a: u64, b: u64) -> u64 {
+    emu.m_fregs[1] = BOX_S | a;
+    emu.m_fregs[2] = BOX_S | b;
+    run(emu, &[fp_r(0x00, 2, 1, rm, 3)]);
+    emu.m_fregs[3] & 0xffff_ffff
+}
+
+// fcvt.w.s x7, f1 with the given rm
+fn fcvt_w_s(emu: &mut EmuEnv, rm: u32, a: u64) -> u64 {
+    emu.m_fregs[1] = BOX_S | a;
+    run(emu, &[fp_r(0x60, 0, 1, rm, 7)]);
+    emu.m_iregs[7]
+}
+
+#[test]
+fn static_rounding_modes() {
+    let mut emu = new_fp_emu();
+    let expected = [ONE, ONE, ONE, ONE + 1, ONE + 1];
+    for (rm, expected) in expected.iter().enumerate() {
+        assert_eq!(fadd_s(&mut emu, rm as u32, ONE, HALF_ULP), *expected, "rm = {}", rm);
+    }
+    // -1.0 - 2^-24 : RDN and RMM round away from zero
+    let expected = [ONE, ONE, ONE + 1, ONE, ONE + 1];
+    for (rm, expected) in expected.iter().enumerate() {
+        assert_eq!(fadd_s(&mut emu, rm as u32, 0x8000_0000 | ONE, 0x8000_0000 | HALF_ULP), 0x8000_0000 | *expected, "rm = {}", rm);
+    }
+    // 2.5 and -2.5 to integer
+    let expected = [2, 2, 2, 3, 3];
+    for (rm, expected) in expected.iter().enumerate() {
+        assert_eq!(fcvt_w_s(&mut emu, rm as u32, 0x4020_0000), *expected, "rm = {}", rm);
+    }
+    let expected = [-2i64, -2, -3, -2, -3];
+    for (rm, expected) in expected.iter().enumerate() {
+        assert_eq!(fcvt_w_s(&mut emu, rm as u32, 0xc020_0000), *expected as u64, "rm = {}", rm);
+    }
+}
+
+#[test]
+fn dynamic_rounding_mode() {
+    let mut emu = new_fp_emu();
+    for frm in 0..5 {
+        run(&mut emu, &[csrrwi(0, FRM, frm)]);
+        assert_eq!(fadd_s(&mut emu, 0b111, ONE, HALF_ULP), fadd_s(&mut emu, frm, ONE, HALF_ULP), "frm = {}", frm);
+        assert_eq!(fcvt_w_s(&mut emu, 0b111, 0x4020_0000), fcvt_w_s(&mut emu, frm, 0x4020_0000), "frm = {}", frm);
+    }
+}
+
+#[test]
+fn reserved_rounding_mode_traps() {
+    let mut emu = new_fp_emu();
+    for rm in [0b101, 0b110].iter() {
+        emu.m_fregs[3] = 0;
+        emu.m_csr.csrrw(CsrAddr::Mcause, 0);
+        run(&mut emu, &[fp_r(0x00, 2, 1, *rm, 3)]);
+        assert_eq!(mcause(&mut emu), 2);
+        assert_eq!(emu.m_pc[0], MTVEC);
+        assert_eq!(emu.m_fregs[3], 0);
+    }
+    // DYN with a reserved frm
+    run(&mut emu, &[csrrwi(0, FRM, 0b101)]);
+    emu.m_csr.csrrw(CsrAddr::Mcause, 0);
+    run(&mut emu, &[fp_r(0x60, 0, 1, 0b111, 7)]);
+    assert_eq!(mcause(&mut emu), 2);
+    assert_eq!(emu.m_pc[0], MTVEC);
+}
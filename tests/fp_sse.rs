@@ -0,0 +1,123 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+type Helper = fn(&mut EmuEnv, u64, u64, u64, u64) -> usize;
+
+// Inline SSE2 operations are checked against their softfloat helpers : the
+// result register and fflags must agree for every operand pair and rm.
+
+const SINGLES: [u64; 11] = [
+    0x3f80_0000, // 1.0
+    0x4040_0000, // 3.0
+    0xc020_0000, // -2.5
+    0x7f7f_ffff, // max normal
+    0x0080_0000, // min normal
+    0x0000_0001, // min subnormal
+    0x0000_0000, // +0
+    0x8000_0000, // -0
+    0x7f80_0000, // +inf
+    0x7fc0_1234, // qNaN with a payload
+    0x7f80_0001, // sNaN
+];
+
+const DOUBLES: [u64; 11] = [
+    0x3ff0_0000_0000_0000,
+    0x4008_0000_0000_0000,
+    0xc004_0000_0000_0000,
+    0x7fef_ffff_ffff_ffff,
+    0x0010_0000_0000_0000,
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_0000,
+    0x8000_0000_0000_0000,
+    0x7ff0_0000_0000_0000,
+    0x7ff8_0000_0000_1234,
+    0x7ff0_0000_0000_0001,
+];
+
+const FRM: u32 = 0x002;
+
+// (rm, frm) : the static modes, then DYN with each frm
+const RM_FRM: [(u32, u32); 10] = [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (7, 0), (7, 1), (7, 2), (7, 3), (7, 4)];
+
+// fd = f3, fs1 = f1, fs2 = f2. Returns (f3, fflags).
+fn run_inline(emu: &mut EmuEnv, inst: u32, a: u64, b: u64) -> (u64, i64) {
+    emu.m_fregs[1] = a;
+    emu.m_fregs[2] = b;
+    emu.m_fregs[3] = 0;
+    emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    (emu.m_fregs[3], fflags(emu))
+}
+
+fn run_helper(emu: &mut EmuEnv, helper: Helper, args: [u64; 4], a: u64, b: u64) -> (u64, i64) {
+    emu.m_fregs[1] = a;
+    emu.m_fregs[2] = b;
+    emu.m_fregs[3] = 0;
+    emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+    assert_eq!(helper(emu, args[0], args[1], args[2], args[3]), 0);
+    (emu.m_fregs[3], fflags(emu))
+}
+
+fn check_rm_op(emu: &mut EmuEnv, name: &str, funct7: u32, unary: bool, helper: Helper, operands: &[u64], boxing: u64) {
+    for (rm, frm) in RM_FRM.iter() {
+        run(emu, &[csrrwi(0, FRM, *frm)]);
+        let inst = fp_r(funct7, if unary { 0 } else { 2 }, 1, *rm, 3);
+        for a in operands.iter() {
+            for b in operands.iter() {
+                let (a, b) = (boxing | a, boxing | b);
+                let inline = run_inline(emu, inst, a, b);
+                let softfloat = run_helper(emu, helper, [inst as u64, 0, 0, CODE_BASE], a, b);
+                assert_eq!(inline, softfloat, "{} rm={} frm={} {:016x} {:016x}", name, rm, frm, a, b);
+            }
+        }
+    }
+}
+
+fn check_minmax(emu: &mut EmuEnv, name: &str, inst: u32, helper: Helper, operands: &[u64], boxing: u64) {
+    for a in operands.iter() {
+        for b in operands.iter() {
+            let (a, b) = (boxing | a, boxing | b);
+            let inline = run_inline(emu, inst, a, b);
+            let softfloat = run_helper(emu, helper, [3, 1, 2, 0], a, b);
+            assert_eq!(inline, softfloat, "{} {:016x} {:016x}", name, a, b);
+        }
+    }
+}
+
+#[test]
+fn single_matches_softfloat() {
+    let mut emu = new_fp_emu();
+    check_rm_op(&mut emu, "fadd.s", 0x00, false, EmuEnv::helper_func_fadd_s, &SINGLES, BOX_S);
+    check_rm_op(&mut emu, "fsub.s", 0x04, false, EmuEnv::helper_func_fsub_s, &SINGLES, BOX_S);
+    check_rm_op(&mut emu, "fmul.s", 0x08, false, EmuEnv::helper_func_fmul_s, &SINGLES, BOX_S);
+    check_rm_op(&mut emu, "fdiv.s", 0x0c, false, EmuEnv::helper_func_fdiv_s, &SINGLES, BOX_S);
+    check_rm_op(&mut emu, "fsqrt.s", 0x2c, true, EmuEnv::helper_func_fsqrt_s, &SINGLES, BOX_S);
+    check_minmax(&mut emu, "fmin.s", fp_r(0x14, 2, 1, 0, 3), EmuEnv::helper_func_fmin_s, &SINGLES, BOX_S);
+    check_minmax(&mut emu, "fmax.s", fp_r(0x14, 2, 1, 1, 3), EmuEnv::helper_func_fmax_s, &SINGLES, BOX_S);
+}
+
+#[test]
+fn double_matches_softfloat() {
+    let mut emu = new_fp_emu();
+    check_rm_op(&mut emu, "fadd.d", 0x01, false, EmuEnv::helper_func_fadd_d, &DOUBLES, 0);
+    check_rm_op(&mut emu, "fsub.d", 0x05, false, EmuEnv::helper_func_fsub_d, &DOUBLES, 0);
+    check_rm_op(&mut emu, "fmul.d", 0x09, false, EmuEnv::helper_func_fmul_d, &DOUBLES, 0);
+    check_rm_op(&mut emu, "fdiv.d", 0x0d, false, EmuEnv::helper_func_fdiv_d, &DOUBLES, 0);
+    check_rm_op(&mut emu, "fsqrt.d", 0x2d, true, EmuEnv::helper_func_fsqrt_d, &DOUBLES, 0);
+    check_minmax(&mut emu, "fmin.d", fp_r(0x15, 2, 1, 0, 3), EmuEnv::helper_func_fmin_d, &DOUBLES, 0);
+    check_minmax(&mut emu, "fmax.d", fp_r(0x15, 2, 1, 1, 3), EmuEnv::helper_func_fmax_d, &DOUBLES, 0);
+}
+
+#[test]
+fn nan_results_are_canonical() {
+    let mut emu = new_fp_emu();
+    // fadd.s of a qNaN with a payload, and 0 * inf
+    assert_eq!(run_inline(&mut emu, fp_r(0x00, 2, 1, 0, 3), BOX_S | 0x7fc0_1234, BOX_S | 0x3f80_0000), (BOX_S | 0x7fc0_0000, 0));
+    assert_eq!(run_inline(&mut emu, fp_r(0x09, 2, 1, 0, 3), 0, 0x7ff0_0000_0000_0000), (0x7ff8_0000_0000_0000, 0x10));
+}
@@ -0,0 +1,75 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const FS_INITIAL: i64 = 1 << 13;
+const FS_DIRTY: i64 = 3 << 13;
+
+const ONE: u64 = 0x3f80_0000;
+
+fn fs(emu: &mut EmuEnv) -> i64 {
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 0) & FS_DIRTY
+}
+
+fn assert_illegal(emu: &mut EmuEnv, inst: u32) {
+    emu.m_csr.csrrw(CsrAddr::Mcause, 0);
+    run(emu, &[inst]);
+    assert_eq!(mcause(emu), 2, "{:08x}", inst);
+    assert_eq!(emu.m_pc[0], MTVEC);
+}
+
+#[test]
+fn fs_off_traps() {
+    let mut emu = new_emu();
+    emu.m_fregs[3] = 0x1234;
+    emu.m_iregs[5] = 0x8000_2000;
+    // fadd.s f3, f1, f2
+    assert_illegal(&mut emu, fp_r(0x00, 2, 1, 0, 3));
+    assert_eq!(emu.m_fregs[3], 0x1234);
+    // flw f1, 0(x5) ; fsd f1, 0(x5) ; fmv.x.d x7, f1
+    assert_illegal(&mut emu, i_type(0, 5, 0b010, 1, 0x07));
+    assert_illegal(&mut emu, s_type(0, 1, 5, 0b011, 0x27));
+    assert_illegal(&mut emu, fp_r(0x71, 0, 1, 0, 7));
+    // c.fld f8, 0(x8), padded with c.nop
+    assert_illegal(&mut emu, 0x0001_2000);
+    // csrr x7, fflags
+    assert_illegal(&mut emu, csrrs(7, 0x001, 0));
+    assert_eq!(fs(&mut emu), 0);
+}
+
+#[test]
+fn fs_becomes_dirty_on_fp_write() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrs(CsrAddr::Mstatus, FS_INITIAL);
+    emu.m_fregs[1] = BOX_S | ONE;
+    emu.m_iregs[5] = 0x8000_2000;
+    // fmv.x.w x7, f1 ; fsw f1, 0(x5) ; fclass.s x8, f1 leave the state alone
+    run(&mut emu, &[fp_r(0x70, 0, 1, 0, 7), s_type(0, 1, 5, 0b010, 0x27), fp_r(0x70, 0, 1, 1, 8)]);
+    assert_eq!(fs(&mut emu), FS_INITIAL);
+    assert!(emu.m_csr.csrrs(CsrAddr::Mstatus, 0) >= 0);
+    // fadd.s f3, f1, f1
+    run(&mut emu, &[fp_r(0x00, 1, 1, 0, 3)]);
+    assert_eq!(fs(&mut emu), FS_DIRTY);
+    assert!(emu.m_csr.csrrs(CsrAddr::Mstatus, 0) < 0);
+    assert_eq!(emu.m_fregs[3], BOX_S | 0x4000_0000);
+}
+
+#[test]
+fn fs_is_part_of_the_block_key() {
+    let mut emu = new_emu();
+    emu.m_fregs[1] = BOX_S | ONE;
+    emu.load_code(CODE_BASE, &[fp_r(0x00, 1, 1, 0, 3)]);
+    emu.run_blocks(1);
+    assert_eq!(mcause(&mut emu), 2);
+    // The same block, now with FS on, must be translated again
+    emu.m_csr.csrrs(CsrAddr::Mstatus, FS_INITIAL);
+    emu.m_pc[0] = CODE_BASE;
+    emu.run_blocks(1);
+    assert_eq!(emu.m_fregs[3], BOX_S | 0x4000_0000);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    assert_eq!(fs(&mut emu), FS_DIRTY);
+}
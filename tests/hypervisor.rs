@@ -0,0 +1,372 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::mmu::MemAccType;
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+// G-stage (Sv39x4) tables : a 16KiB root at 0x8002_0000, then one VPN[1]
+// and one VPN[0] table. The VS-stage (Sv39) root sits at GPA 0x8002_6000,
+// which the G-stage maps to the same address.
+const G_ROOT_PPN: u64 = 0x80020;
+const G_L1_PPN: u64 = 0x80024;
+const G_L0_PPN: u64 = 0x80025;
+const VS_ROOT_PPN: u64 = 0x80026;
+
+// VA 0xc000_0000 -> GPA 0x4000_0000 -> PA 0x8004_0000, one page only
+const GUEST_VA: u64 = 0xc000_0000;
+const GUEST_PA: u64 = 0x4000_0000;
+const DATA_PA: u64 = 0x8004_0000;
+
+const LD: u32 = 0x0002_b383; // ld x7, 0(x5)
+const SD: u32 = 0x0062_b423; // sd x6, 8(x5)
+const SRET: u32 = 0x1020_0073;
+const WFI: u32 = 0x1050_0073;
+const SFENCE_VMA: u32 = 0x1200_0073;
+const HFENCE_VVMA: u32 = 0x2200_0073;
+const HFENCE_GVMA: u32 = 0x6200_0073;
+
+const CSR_SSCRATCH: u32 = 0x140;
+const CSR_SATP: u32 = 0x180;
+const CSR_HSTATUS: u32 = 0x600;
+
+const HSTATUS_GVA: i64 = 1 << 6;
+const HSTATUS_SPV: i64 = 1 << 7;
+const HSTATUS_SPVP: i64 = 1 << 8;
+const HSTATUS_HU: i64 = 1 << 9;
+const HSTATUS_VTVM: i64 = 1 << 20;
+const HSTATUS_VTW: i64 = 1 << 21;
+const HSTATUS_VTSR: i64 = 1 << 22;
+
+const MSTATUS_TW: i64 = 1 << 21;
+const MSTATUS_GVA: i64 = 1 << 38;
+const MSTATUS_MPV: i64 = 1 << 39;
+
+// hlv.d rd, (rs1) / hlv.w rd, (rs1) / hsv.d rs2, (rs1)
+fn hlv_d(rd: u32, rs1: u32) -> u32 {
+    r_type(0x36, 0, rs1, 0b100, rd, 0x73)
+}
+
+fn hlv_w(rd: u32, rs1: u32) -> u32 {
+    r_type(0x34, 0, rs1, 0b100, rd, 0x73)
+}
+
+fn hsv_d(rs2: u32, rs1: u32) -> u32 {
+    r_type(0x37, rs2, rs1, 0b100, 0, 0x73)
+}
+
+// vsatp and hgatp Bare : guest addresses are host physical addresses
+fn new_guest_emu(priv_mode: PrivMode) -> EmuEnv {
+    let mut emu = new_emu();
+    emu.set_virt(true);
+    emu.m_priv = priv_mode;
+    emu
+}
+
+// Code at CODE_BASE is mapped 1:1 by both stages (gigapages), and
+// GUEST_VA goes through a VS-stage gigapage and a 4KiB G-stage page.
+fn setup_two_stage(emu: &mut EmuEnv) {
+    set_pte(emu, G_ROOT_PPN, 2, leaf(0x80000, U | R | W | X | A | D));
+    set_pte(emu, G_ROOT_PPN, 1, pointer(G_L1_PPN));
+    set_pte(emu, G_L1_PPN, 0, pointer(G_L0_PPN));
+    set_pte(emu, G_L0_PPN, 0, leaf(DATA_PA >> 12, U | R | W | A | D));
+    set_pte(emu, VS_ROOT_PPN, 2, leaf(0x80000, R | W | X | A | D));
+    set_pte(emu, VS_ROOT_PPN, 3, leaf(GUEST_PA >> 12, R | W | A | D));
+    emu.m_csr.csrrw(CsrAddr::Hgatp, (8 << 60) | G_ROOT_PPN as i64);
+    emu.m_csr.csrrw(CsrAddr::Vsatp, (8 << 60) | VS_ROOT_PPN as i64);
+}
+
+fn new_two_stage_emu() -> EmuEnv {
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    setup_two_stage(&mut emu);
+    emu
+}
+
+fn csr(emu: &mut EmuEnv, addr: CsrAddr) -> i64 {
+    emu.m_csr.csrrs(addr, 0)
+}
+
+#[test]
+fn two_stage_load_store() {
+    let mut emu = new_two_stage_emu();
+    emu.write_mem_8byte(DATA_PA + 0x10, 0x1122_3344_5566_7788);
+    emu.m_iregs[5] = GUEST_VA + 0x10;
+    emu.m_iregs[6] = 0xdead_beef;
+    run(&mut emu, &[LD, SD]);
+    assert_eq!(emu.m_iregs[7], 0x1122_3344_5566_7788);
+    assert_eq!(emu.read_mem_8byte(DATA_PA + 0x18), 0xdead_beef);
+    assert!(emu.m_virt);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+}
+
+#[test]
+fn guest_page_fault_to_m_mode() {
+    // The G-stage maps only the first page of GUEST_PA
+    let mut emu = new_two_stage_emu();
+    emu.m_iregs[5] = GUEST_VA + 0x1008;
+    run(&mut emu, &[LD]);
+    assert_eq!(mcause(&mut emu), 21);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), (GUEST_VA + 0x1008) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), ((GUEST_PA + 0x1008) >> 2) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mepc), CODE_BASE as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mstatus) & (MSTATUS_MPV | MSTATUS_GVA), MSTATUS_MPV | MSTATUS_GVA);
+    assert!(emu.m_priv == PrivMode::Machine);
+    assert!(!emu.m_virt);
+    assert_eq!(emu.m_pc[0], MTVEC);
+
+    let mut emu = new_two_stage_emu();
+    emu.m_iregs[5] = GUEST_VA + 0x1000;
+    run(&mut emu, &[SD]);
+    assert_eq!(mcause(&mut emu), 23);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), (GUEST_VA + 0x1008) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), ((GUEST_PA + 0x1008) >> 2) as i64);
+}
+
+#[test]
+fn guest_page_fault_delegated_to_hs() {
+    // Guest-page faults cannot be delegated further with hedeleg
+    let mut emu = new_two_stage_emu();
+    emu.m_csr.csrrw(CsrAddr::Medeleg, 1 << 21);
+    emu.m_csr.csrrw(CsrAddr::Hedeleg, 1 << 21);
+    emu.m_iregs[5] = GUEST_VA + 0x1008;
+    run(&mut emu, &[LD]);
+    assert_eq!(csr(&mut emu, CsrAddr::Scause), 21);
+    assert_eq!(csr(&mut emu, CsrAddr::Stval), (GUEST_VA + 0x1008) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Htval), ((GUEST_PA + 0x1008) >> 2) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), 0);
+    let hstatus = csr(&mut emu, CsrAddr::Hstatus);
+    assert_eq!(hstatus & (HSTATUS_SPV | HSTATUS_SPVP | HSTATUS_GVA), HSTATUS_SPV | HSTATUS_SPVP | HSTATUS_GVA);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert!(!emu.m_virt);
+    assert_eq!(emu.m_pc[0], STVEC);
+}
+
+#[test]
+fn vs_stage_page_fault() {
+    // VPN[2] = 0 is not mapped by vsatp : a plain page fault, without a GPA
+    let mut emu = new_two_stage_emu();
+    emu.m_iregs[5] = 0x1000;
+    run(&mut emu, &[LD]);
+    assert_eq!(mcause(&mut emu), 13);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), 0x1000);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), 0);
+    assert_eq!(csr(&mut emu, CsrAddr::Mstatus) & MSTATUS_GVA, MSTATUS_GVA);
+
+    // Delegated by medeleg and hedeleg : handled by the guest in VS-mode
+    let mut emu = new_two_stage_emu();
+    emu.m_csr.csrrw(CsrAddr::Medeleg, 1 << 13);
+    emu.m_csr.csrrw(CsrAddr::Hedeleg, 1 << 13);
+    emu.m_iregs[5] = 0x1000;
+    run(&mut emu, &[LD]);
+    assert_eq!(csr(&mut emu, CsrAddr::Vscause), 13);
+    assert_eq!(csr(&mut emu, CsrAddr::Vstval), 0x1000);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert!(emu.m_virt);
+    assert_eq!(emu.m_pc[0], VSTVEC);
+}
+
+#[test]
+fn guest_fetch_faults() {
+    let mut emu = new_two_stage_emu();
+    assert!(emu.convert_physical_address(CODE_BASE, GUEST_VA + 0x1000, 2, MemAccType::Fetch).is_err());
+    assert_eq!(mcause(&mut emu), 20);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), (GUEST_VA + 0x1000) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), ((GUEST_PA + 0x1000) >> 2) as i64);
+
+    // The VS-stage root at GPA 0x4000_1000 is not mapped by the G-stage :
+    // htval holds the address of the PTE that could not be read.
+    let mut emu = new_two_stage_emu();
+    emu.m_csr.csrrw(CsrAddr::Vsatp, (8 << 60) | ((GUEST_PA + 0x1000) >> 12) as i64);
+    assert!(emu.convert_physical_address(CODE_BASE, CODE_BASE, 2, MemAccType::Fetch).is_err());
+    assert_eq!(mcause(&mut emu), 20);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), CODE_BASE as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), ((GUEST_PA + 0x1000 + 2 * 8) >> 2) as i64);
+}
+
+#[test]
+fn hlv_hsv_from_hs_mode() {
+    let mut emu = new_emu();
+    setup_two_stage(&mut emu);
+    emu.m_priv = PrivMode::Supervisor;
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_SPVP);
+    emu.write_mem_8byte(DATA_PA + 0x10, 0x0123_4567_89ab_cdef);
+    emu.m_iregs[5] = GUEST_VA + 0x10;
+    emu.m_iregs[6] = 0x5a5a;
+    emu.m_iregs[8] = GUEST_VA + 0x20;
+    run(&mut emu, &[hlv_d(7, 5), hsv_d(6, 8)]);
+    assert_eq!(emu.m_iregs[7], 0x0123_4567_89ab_cdef);
+    assert_eq!(emu.read_mem_8byte(DATA_PA + 0x20), 0x5a5a);
+    assert!(!emu.m_virt);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+
+    // A guest-page fault of hlv is a guest access, taken with V=0
+    emu.m_iregs[5] = GUEST_VA + 0x1000;
+    run(&mut emu, &[hlv_w(7, 5)]);
+    assert_eq!(mcause(&mut emu), 21);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval2), ((GUEST_PA + 0x1000) >> 2) as i64);
+    assert_eq!(csr(&mut emu, CsrAddr::Mstatus) & (MSTATUS_MPV | MSTATUS_GVA), MSTATUS_GVA);
+}
+
+#[test]
+fn hlv_privilege() {
+    // U-mode needs hstatus.HU
+    let mut emu = new_emu();
+    setup_two_stage(&mut emu);
+    emu.m_priv = PrivMode::User;
+    emu.m_iregs[5] = GUEST_VA;
+    run(&mut emu, &[hlv_d(7, 5)]);
+    assert_eq!(mcause(&mut emu), 2);
+
+    let mut emu = new_emu();
+    setup_two_stage(&mut emu);
+    emu.m_priv = PrivMode::User;
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_HU | HSTATUS_SPVP);
+    emu.write_mem_8byte(DATA_PA, 0x77);
+    emu.m_iregs[5] = GUEST_VA;
+    run(&mut emu, &[hlv_d(7, 5)]);
+    assert_eq!(emu.m_iregs[7], 0x77);
+    assert!(emu.m_priv == PrivMode::User);
+
+    // Never from a guest
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_iregs[5] = DATA_PA;
+    run(&mut emu, &[hlv_d(7, 5)]);
+    assert_eq!(mcause(&mut emu), 22);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), hlv_d(7, 5) as i64);
+}
+
+#[test]
+fn hfence_privilege() {
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::Supervisor;
+    run(&mut emu, &[HFENCE_VVMA, HFENCE_GVMA]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+
+    // hstatus.HU does not allow hfence in U-mode
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::User;
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_HU);
+    run(&mut emu, &[HFENCE_GVMA]);
+    assert_eq!(mcause(&mut emu), 2);
+
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    run(&mut emu, &[HFENCE_VVMA]);
+    assert_eq!(mcause(&mut emu), 22);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), HFENCE_VVMA as i64);
+}
+
+#[test]
+fn hypervisor_csr_access() {
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::Supervisor;
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_VTSR);
+    run(&mut emu, &[csrrs(7, CSR_HSTATUS, 0)]);
+    assert_eq!(emu.m_iregs[7] as i64 & HSTATUS_VTSR, HSTATUS_VTSR);
+
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::User;
+    run(&mut emu, &[csrrs(7, CSR_HSTATUS, 0)]);
+    assert_eq!(mcause(&mut emu), 2);
+
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    run(&mut emu, &[csrrs(7, CSR_HSTATUS, 0)]);
+    assert_eq!(mcause(&mut emu), 22);
+}
+
+#[test]
+fn vs_mode_csr_redirection() {
+    // sscratch and satp of a guest are vsscratch and vsatp
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrw(CsrAddr::Sscratch, 0x55);
+    emu.m_csr.csrrw(CsrAddr::Vsscratch, 0x66);
+    emu.m_iregs[5] = 0x77;
+    run(&mut emu, &[csrrw(7, CSR_SSCRATCH, 5), csrrs(8, CSR_SATP, 0)]);
+    assert_eq!(emu.m_iregs[7], 0x66);
+    assert_eq!(csr(&mut emu, CsrAddr::Vsscratch), 0x77);
+    assert_eq!(csr(&mut emu, CsrAddr::Sscratch), 0x55);
+    assert_eq!(emu.m_iregs[8], 0);
+    assert!(emu.m_virt);
+}
+
+#[test]
+fn vtvm_traps_satp_and_sfence() {
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    run(&mut emu, &[SFENCE_VMA, csrrs(7, CSR_SATP, 0)]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_VTVM);
+    run(&mut emu, &[SFENCE_VMA]);
+    assert_eq!(mcause(&mut emu), 22);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), SFENCE_VMA as i64);
+
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_VTVM);
+    run(&mut emu, &[csrrs(7, CSR_SATP, 0)]);
+    assert_eq!(mcause(&mut emu), 22);
+
+    // sfence.vma is never allowed in VU-mode
+    let mut emu = new_guest_emu(PrivMode::User);
+    run(&mut emu, &[SFENCE_VMA]);
+    assert_eq!(mcause(&mut emu), 22);
+}
+
+#[test]
+fn vtw_traps_wfi() {
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    run(&mut emu, &[WFI]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_VTW);
+    run(&mut emu, &[WFI]);
+    assert_eq!(mcause(&mut emu), 22);
+    assert_eq!(csr(&mut emu, CsrAddr::Mtval), WFI as i64);
+
+    let mut emu = new_guest_emu(PrivMode::User);
+    run(&mut emu, &[WFI]);
+    assert_eq!(mcause(&mut emu), 22);
+
+    // mstatus.TW comes first, and does not apply to M-mode
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_TW);
+    run(&mut emu, &[WFI]);
+    assert_eq!(mcause(&mut emu), 2);
+
+    let mut emu = new_emu();
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_TW);
+    run(&mut emu, &[WFI]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::User;
+    run(&mut emu, &[WFI]);
+    assert_eq!(mcause(&mut emu), 2);
+}
+
+#[test]
+fn vtsr_traps_sret() {
+    let mut emu = new_guest_emu(PrivMode::Supervisor);
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_VTSR);
+    run(&mut emu, &[SRET]);
+    assert_eq!(mcause(&mut emu), 22);
+    assert!(emu.m_priv == PrivMode::Machine);
+    assert_eq!(csr(&mut emu, CsrAddr::Mstatus) & MSTATUS_MPV, MSTATUS_MPV);
+}
+
+#[test]
+fn sret_enters_guest_with_spv() {
+    let mut emu = new_emu();
+    emu.m_priv = PrivMode::Supervisor;
+    emu.m_csr.csrrs(CsrAddr::Hstatus, HSTATUS_SPV);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 1 << 8);
+    emu.m_csr.csrrw(CsrAddr::Sepc, 0x8000_0100);
+    run(&mut emu, &[SRET]);
+    assert!(emu.m_virt);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert_eq!(emu.m_pc[0], 0x8000_0100);
+    assert_eq!(csr(&mut emu, CsrAddr::Hstatus) & HSTATUS_SPV, 0);
+}
@@ -0,0 +1,193 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv::{Interrupt, PrivMode};
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const INTERRUPT_BIT: i64 = 1 << 63;
+
+const SSI: i64 = 1 << 1;
+const MSI: i64 = 1 << 3;
+const STI: i64 = 1 << 5;
+const MTI: i64 = 1 << 7;
+const SEI: i64 = 1 << 9;
+const MEI: i64 = 1 << 11;
+
+const VSSI: i64 = 1 << 2;
+const VSTI: i64 = 1 << 6;
+const VSEI: i64 = 1 << 10;
+
+const MSTATUS_SIE: i64 = 1 << 1;
+const MSTATUS_MIE: i64 = 1 << 3;
+
+// Interrupts are taken at this PC
+const EPC: u64 = 0x8000_0100;
+
+fn new_irq_emu() -> EmuEnv {
+    let mut emu = new_irq_emu();
+    emu.m_pc[0] = EPC;
+    emu
+}
+
+#[test]
+fn mip_mie_mideleg_writable_bits() {
+    let mut emu = new_irq_emu();
+    emu.m_csr.csrrw(CsrAddr::Mip, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), SSI | STI | SEI);
+    emu.m_csr.csrrw(CsrAddr::Mie, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mie, 0), SSI | VSSI | MSI | STI | VSTI | MTI | SEI | VSEI | MEI);
+    // VSSIP, VSTIP and VSEIP are always delegated
+    emu.m_csr.csrrw(CsrAddr::Mideleg, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mideleg, 0), 0x666);
+    emu.m_csr.csrrw(CsrAddr::Mideleg, 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mideleg, 0), 0x444);
+}
+
+#[test]
+fn irq_lines_are_read_only_in_mip() {
+    let mut emu = new_irq_emu();
+    emu.raise_interrupt(Interrupt::MachineTimer);
+    emu.raise_interrupt(Interrupt::SupervisorExternal);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), MTI | SEI);
+    // csrrc only clears the bit software wrote : SEIP stays up while its line is
+    emu.m_csr.csrrs(CsrAddr::Mip, SEI);
+    emu.m_csr.csrrc(CsrAddr::Mip, MTI | SEI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), MTI | SEI);
+    emu.lower_interrupt(Interrupt::MachineTimer);
+    emu.lower_interrupt(Interrupt::SupervisorExternal);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), 0);
+}
+
+#[test]
+fn sip_sie_are_views() {
+    let mut emu = new_irq_emu();
+    emu.m_csr.csrrw(CsrAddr::Mideleg, SSI | STI);
+    emu.m_csr.csrrw(CsrAddr::Sie, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Sie, 0), SSI | STI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mie, 0), SSI | STI);
+    // Only SSIP is writable through sip
+    emu.m_csr.csrrw(CsrAddr::Sip, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), SSI);
+    emu.m_csr.csrrs(CsrAddr::Mip, STI | SEI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Sip, 0), SSI | STI);
+    emu.m_csr.csrrc(CsrAddr::Sip, SSI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), STI | SEI);
+}
+
+#[test]
+fn machine_interrupt_priority() {
+    let mut emu = new_irq_emu();
+    emu.m_priv = PrivMode::Supervisor;
+    emu.m_csr.csrrw(CsrAddr::Mideleg, SEI);
+    emu.m_csr.csrrw(CsrAddr::Mie, MSI | MTI | SEI);
+    emu.raise_interrupt(Interrupt::SupervisorExternal);
+    emu.raise_interrupt(Interrupt::MachineTimer);
+    emu.raise_interrupt(Interrupt::MachineSoft);
+    // M-level interrupts are enabled below M-mode whatever mstatus.MIE
+    assert!(emu.check_interrupt());
+    assert!(emu.m_priv == PrivMode::Machine);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), INTERRUPT_BIT | 3);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mepc, 0), EPC as i64);
+    assert_eq!(emu.m_pc[0], MTVEC);
+    // In M-mode with MIE = 0 nothing is taken, and delegated interrupts never are
+    emu.lower_interrupt(Interrupt::MachineSoft);
+    emu.lower_interrupt(Interrupt::MachineTimer);
+    assert!(!emu.check_interrupt());
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_MIE);
+    assert!(!emu.check_interrupt());
+}
+
+#[test]
+fn delegated_interrupt_vectored() {
+    let mut emu = new_irq_emu();
+    emu.m_csr.csrrw(CsrAddr::Stvec, STVEC as i64 | 1);
+    emu.m_csr.csrrw(CsrAddr::Mideleg, SSI | STI | SEI);
+    emu.m_csr.csrrw(CsrAddr::Mie, STI | SEI);
+    emu.m_csr.csrrs(CsrAddr::Mip, STI);
+    emu.raise_interrupt(Interrupt::SupervisorExternal);
+    // S-mode with SIE = 0 : masked
+    emu.m_priv = PrivMode::Supervisor;
+    assert!(!emu.check_interrupt());
+    // U-mode : SEI comes before STI
+    emu.m_priv = PrivMode::User;
+    assert!(emu.check_interrupt());
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Scause, 0), INTERRUPT_BIT | 9);
+    assert_eq!(emu.m_pc[0], STVEC + 4 * 9);
+    // S-mode with SIE = 1
+    emu.lower_interrupt(Interrupt::SupervisorExternal);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_SIE);
+    assert!(emu.check_interrupt());
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Scause, 0), INTERRUPT_BIT | 5);
+    assert_eq!(emu.m_pc[0], STVEC + 4 * 5);
+}
+
+#[test]
+fn hip_hie_vsip_vsie_are_views() {
+    let mut emu = new_irq_emu();
+    // hvip injects VS-level interrupts, seen in mip and hip
+    emu.m_csr.csrrw(CsrAddr::Hvip, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Hvip, 0), VSSI | VSTI | VSEI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mip, 0), VSSI | VSTI | VSEI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Hip, 0), VSSI | VSTI | VSEI);
+    emu.m_csr.csrrw(CsrAddr::Hie, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mie, 0), VSSI | VSTI | VSEI);
+    // vsip and vsie show the bits hideleg delegates, shifted to their S-level place
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vsip, 0), 0);
+    emu.m_csr.csrrw(CsrAddr::Hideleg, VSSI | VSTI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vsip, 0), SSI | STI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vsie, 0), SSI | STI);
+    // Only VSSIP is writable through hip and vsip
+    emu.m_csr.csrrc(CsrAddr::Vsip, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Hvip, 0), VSTI | VSEI);
+    emu.m_csr.csrrs(CsrAddr::Hip, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Hvip, 0), VSSI | VSTI | VSEI);
+    emu.m_csr.csrrc(CsrAddr::Vsie, SSI);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mie, 0), VSTI | VSEI);
+}
+
+#[test]
+fn vs_interrupt_from_hvip() {
+    let mut emu = new_irq_emu();
+    emu.m_csr.csrrw(CsrAddr::Mie, VSTI);
+    emu.m_csr.csrrw(CsrAddr::Hvip, VSTI);
+    // Never taken with V=0, even in U-mode
+    emu.m_priv = PrivMode::User;
+    assert!(!emu.check_interrupt());
+    // Not delegated by hideleg : taken to HS-mode from a guest
+    emu.set_virt(true);
+    emu.m_priv = PrivMode::Supervisor;
+    assert!(emu.check_interrupt());
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert!(!emu.m_virt);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Scause, 0), INTERRUPT_BIT | 6);
+    assert_eq!(emu.m_pc[0], STVEC);
+    // Delegated : taken to VS-mode as a supervisor timer interrupt, in VU-mode
+    // and in VS-mode with vsstatus.SIE
+    emu.m_csr.csrrw(CsrAddr::Hideleg, VSTI);
+    emu.set_virt(true);
+    emu.m_priv = PrivMode::Supervisor;
+    assert!(!emu.check_interrupt());
+    emu.m_priv = PrivMode::User;
+    emu.m_pc[0] = EPC;
+    assert!(emu.check_interrupt());
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert!(emu.m_virt);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vscause, 0), INTERRUPT_BIT | 5);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vsepc, 0), EPC as i64);
+    assert_eq!(emu.m_pc[0], VSTVEC);
+    emu.m_csr.csrrs(CsrAddr::Vsstatus, MSTATUS_SIE);
+    assert!(emu.check_interrupt());
+    assert_eq!(emu.m_pc[0], VSTVEC);
+    // HS-level interrupts come first
+    emu.m_csr.csrrw(CsrAddr::Mideleg, STI);
+    emu.m_csr.csrrs(CsrAddr::Mie, STI);
+    emu.m_csr.csrrs(CsrAddr::Mip, STI);
+    emu.m_csr.csrrs(CsrAddr::Vsstatus, MSTATUS_SIE);
+    assert!(emu.check_interrupt());
+    assert!(!emu.m_virt);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Scause, 0), INTERRUPT_BIT | 5);
+}
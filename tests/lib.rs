@@ -248,3 +248,35 @@ extern crate dydra;
 // #[test]fn rv64um_p_remu      () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv64um-p-remu".to_string(), false),  1); }
 // #[test]fn rv64um_p_remuw     () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv64um-p-remuw".to_string(), false),  1); }
 // #[test]fn rv64um_p_remw      () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv64um-p-remw".to_string(), false),  1); }
+
+#[test]fn rv32ui_p_simple () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-simple".to_string(), false),  1); }
+#[test]fn rv32ui_p_add    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-add".to_string(), false),  1); }
+#[test]fn rv32ui_p_addi   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-addi".to_string(), false),  1); }
+#[test]fn rv32ui_p_sub    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-sub".to_string(), false),  1); }
+#[test]fn rv32ui_p_and    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-and".to_string(), false),  1); }
+#[test]fn rv32ui_p_or     () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-or".to_string(), false),  1); }
+#[test]fn rv32ui_p_xor    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-xor".to_string(), false),  1); }
+#[test]fn rv32ui_p_auipc  () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-auipc".to_string(), false),  1); }
+#[test]fn rv32ui_p_lui    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-lui".to_string(), false),  1); }
+#[test]fn rv32ui_p_sll    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-sll".to_string(), false),  1); }
+#[test]fn rv32ui_p_slli   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-slli".to_string(), false),  1); }
+#[test]fn rv32ui_p_srl    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-srl".to_string(), false),  1); }
+#[test]fn rv32ui_p_srli   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-srli".to_string(), false),  1); }
+#[test]fn rv32ui_p_sra    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-sra".to_string(), false),  1); }
+#[test]fn rv32ui_p_srai   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-srai".to_string(), false),  1); }
+#[test]fn rv32ui_p_slt    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-slt".to_string(), false),  1); }
+#[test]fn rv32ui_p_sltu   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-sltu".to_string(), false),  1); }
+#[test]fn rv32ui_p_beq    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-beq".to_string(), false),  1); }
+#[test]fn rv32ui_p_bltu   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-bltu".to_string(), false),  1); }
+#[test]fn rv32ui_p_jal    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-jal".to_string(), false),  1); }
+#[test]fn rv32ui_p_jalr   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-jalr".to_string(), false),  1); }
+#[test]fn rv32ui_p_lw     () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-lw".to_string(), false),  1); }
+#[test]fn rv32ui_p_sw     () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32ui-p-sw".to_string(), false),  1); }
+#[test]fn rv32um_p_mul    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-mul".to_string(), false),  1); }
+#[test]fn rv32um_p_mulh   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-mulh".to_string(), false),  1); }
+#[test]fn rv32um_p_mulhsu () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-mulhsu".to_string(), false),  1); }
+#[test]fn rv32um_p_mulhu  () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-mulhu".to_string(), false),  1); }
+#[test]fn rv32um_p_div    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-div".to_string(), false),  1); }
+#[test]fn rv32um_p_divu   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-divu".to_string(), false),  1); }
+#[test]fn rv32um_p_rem    () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-rem".to_string(), false),  1); }
+#[test]fn rv32um_p_remu   () { assert_eq!(dydra::run_riscv_test("/riscv64-unknown-elf/share/riscv-tests/isa/rv32um-p-remu".to_string(), false),  1); }
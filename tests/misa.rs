@@ -0,0 +1,91 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const MISA_C: i64 = 1 << 2;
+const MISA: u32 = 0x301;
+
+fn disable_c(emu: &mut dydra::emu_env::EmuEnv) {
+    emu.m_csr.csrrc(CsrAddr::Misa, MISA_C);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0) & MISA_C, 0);
+}
+
+fn assert_misalign_trap(emu: &mut dydra::emu_env::EmuEnv, epc: u64, target: u64) {
+    assert_eq!(mcause(emu), 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mepc, 0), epc as i64);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), target as i64);
+    assert_eq!(emu.m_pc[0], MTVEC);
+}
+
+#[test]
+fn misa_only_c_is_writable() {
+    let mut emu = new_emu();
+    let misa = emu.m_csr.csrrs(CsrAddr::Misa, 0);
+    emu.m_csr.csrrw(CsrAddr::Misa, 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0), misa & !MISA_C);
+    emu.m_csr.csrrw(CsrAddr::Misa, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0), misa);
+    // MXL and the other extensions are read-only
+    emu.m_csr.csrrc(CsrAddr::Misa, -1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0), misa & !MISA_C);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0) as u64 >> 62, 2);
+}
+
+#[test]
+fn jal_misaligned_target() {
+    let mut emu = new_emu();
+    disable_c(&mut emu);
+    run(&mut emu, &[j_type(6, 1)]);
+    assert_misalign_trap(&mut emu, CODE_BASE, CODE_BASE + 6);
+    assert_eq!(emu.m_iregs[1], 0);
+}
+
+#[test]
+fn jalr_misaligned_target() {
+    let mut emu = new_emu();
+    disable_c(&mut emu);
+    // Bit 0 of the target is cleared, bit 1 is not
+    emu.m_iregs[5] = CODE_BASE + 0x101;
+    run(&mut emu, &[i_type(1, 5, 0b000, 1, 0x67)]);
+    assert_misalign_trap(&mut emu, CODE_BASE, CODE_BASE + 0x102);
+    assert_eq!(emu.m_iregs[1], 0);
+}
+
+#[test]
+fn branch_misaligned_target() {
+    let mut emu = new_emu();
+    disable_c(&mut emu);
+    // Not taken : no trap
+    run(&mut emu, &[b_type(6, 0, 0, 0b001)]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    run(&mut emu, &[b_type(6, 0, 0, 0b000)]);
+    assert_misalign_trap(&mut emu, CODE_BASE, CODE_BASE + 6);
+}
+
+#[test]
+fn misa_c_toggle() {
+    let mut emu = new_emu();
+    run(&mut emu, &[j_type(6, 0)]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 6);
+    // csrrci at 0x8000_0100 clears C : the next instruction is aligned
+    emu.load_code(CODE_BASE + 0x100, &[csrrci(0, MISA, 4)]);
+    emu.run_blocks(1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0) & MISA_C, 0);
+    // The block translated with C enabled is dropped
+    emu.m_pc[0] = CODE_BASE;
+    emu.run_blocks(1);
+    assert_misalign_trap(&mut emu, CODE_BASE, CODE_BASE + 6);
+}
+
+#[test]
+fn misa_c_clear_ignored_before_misaligned_pc() {
+    let mut emu = new_emu();
+    // c.nop, then csrrci at 0x8000_0002 : the next instruction would be misaligned
+    let inst = csrrci(0, MISA, 4);
+    run(&mut emu, &[0x0001 | (inst << 16), inst >> 16]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 6);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Misa, 0) & MISA_C, MISA_C);
+}
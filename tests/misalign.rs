@@ -0,0 +1,109 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::{ArgConfig, EmuEnv, MisalignEnum};
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+fn new_misalign_emu(misalign: MisalignEnum) -> EmuEnv {
+    new_emu_with(ArgConfig { misalign, ..Default::default() })
+}
+
+// VA 0x4000_1000 maps to 0x8003_0000, VA 0x4000_2000 is left unmapped.
+fn map_data_page(emu: &mut EmuEnv) {
+    setup_tables(emu);
+    set_pte(emu, L0_PPN, 1, leaf(0x80030, R | W | A | D));
+    enable_sv39(emu);
+}
+
+#[test]
+fn trap_raises_misaligned_load() {
+    let mut emu = new_misalign_emu(MisalignEnum::Trap);
+    emu.m_iregs[5] = 0x8000_1001;
+    let cases: [(fn(&mut EmuEnv, u64, u64, u64, u64) -> usize, u64); 4] = [
+        (EmuEnv::helper_func_load64, 0),
+        (EmuEnv::helper_func_load32, 2),
+        (EmuEnv::helper_func_loadu16, 0),
+        (EmuEnv::helper_func_float_load64, 4),
+    ];
+    for (helper, offset) in cases.iter() {
+        emu.m_priv = PrivMode::Machine;
+        emu.m_iregs[6] = 0x1234;
+        assert_ne!(helper(&mut emu, 6, 5, *offset, 0x8000_0000), 0);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 4);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x8000_1001 + *offset as i64);
+        assert_eq!(emu.m_iregs[6], 0x1234);
+    }
+}
+
+#[test]
+fn trap_raises_misaligned_store() {
+    let mut emu = new_misalign_emu(MisalignEnum::Trap);
+    emu.write_mem_8byte(0x8000_1000, 0);
+    emu.write_mem_8byte(0x8000_1008, 0);
+    emu.m_iregs[5] = 0x8000_1003;
+    emu.m_iregs[6] = u64::MAX;
+    let cases: [(fn(&mut EmuEnv, u64, u64, u64, u64) -> usize, u64); 3] = [
+        (EmuEnv::helper_func_store64, 0),
+        (EmuEnv::helper_func_store32, 2),
+        (EmuEnv::helper_func_store16, 0),
+    ];
+    for (helper, offset) in cases.iter() {
+        emu.m_priv = PrivMode::Machine;
+        assert_ne!(helper(&mut emu, 6, 5, *offset, 0x8000_0000), 0);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 6);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x8000_1003 + *offset as i64);
+    }
+    assert_eq!(emu.read_mem_8byte(0x8000_1000), 0);
+    assert_eq!(emu.read_mem_8byte(0x8000_1008), 0);
+}
+
+#[test]
+fn emulate_load_and_store() {
+    let mut emu = new_misalign_emu(MisalignEnum::Emulate);
+    emu.write_mem_8byte(0x8000_1000, 0x0706_0504_0302_0100);
+    emu.write_mem_8byte(0x8000_1008, 0x0f0e_0d0c_0b0a_0908);
+    emu.m_iregs[5] = 0x8000_1000;
+    assert_eq!(EmuEnv::helper_func_load64(&mut emu, 6, 5, 3, 0x8000_0000), 0);
+    assert_eq!(emu.m_iregs[6], 0x0a09_0807_0605_0403);
+    assert_eq!(EmuEnv::helper_func_load32(&mut emu, 6, 5, 7, 0x8000_0000), 0);
+    assert_eq!(emu.m_iregs[6], 0x0a09_0807);
+    emu.m_iregs[6] = 0xdead_beef;
+    assert_eq!(EmuEnv::helper_func_store32(&mut emu, 6, 5, 5, 0x8000_0000), 0);
+    assert_eq!(emu.read_mem_8byte(0x8000_1000), 0xadbe_ef04_0302_0100);
+    assert_eq!(emu.read_mem_8byte(0x8000_1008), 0x0f0e_0d0c_0b0a_09de);
+}
+
+#[test]
+fn emulate_page_crossing_load() {
+    let mut emu = new_misalign_emu(MisalignEnum::Emulate);
+    map_data_page(&mut emu);
+    emu.write_mem_8byte(0x8003_0ff8, 0x8877_6655_4433_2211);
+    emu.m_iregs[5] = 0x4000_1ffc;
+    emu.m_iregs[6] = 0x1234;
+    // The first half is mapped, the fault is reported on the first byte of the second page
+    assert_ne!(EmuEnv::helper_func_load64(&mut emu, 6, 5, 0, 0x8000_0000), 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 13);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x4000_2000);
+    assert_eq!(emu.m_iregs[6], 0x1234);
+    // Within the first page the access completes
+    emu.m_priv = PrivMode::Supervisor;
+    assert_eq!(EmuEnv::helper_func_load32(&mut emu, 6, 5, -2i64 as u64, 0x8000_0000), 0);
+    assert_eq!(emu.m_iregs[6], 0x6655_4433);
+}
+
+#[test]
+fn emulate_page_crossing_store() {
+    let mut emu = new_misalign_emu(MisalignEnum::Emulate);
+    map_data_page(&mut emu);
+    emu.write_mem_8byte(0x8003_0ff8, 0);
+    emu.m_iregs[5] = 0x4000_1ffe;
+    emu.m_iregs[6] = u64::MAX;
+    // Nothing is written to the first page when the second page faults
+    assert_ne!(EmuEnv::helper_func_store32(&mut emu, 6, 5, 0, 0x8000_0000), 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 15);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x4000_2000);
+    assert_eq!(emu.read_mem_8byte(0x8003_0ff8), 0);
+}
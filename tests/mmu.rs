@@ -0,0 +1,445 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::mmu::{MemAccType, MemResult};
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+use dydra::target::riscv::tlb::{tlb_slot, TLB_ASID_SHIFT, TLB_SIZE};
+
+// S-mode, satp = Sv39 with the root of common::setup_tables()
+fn new_sv39_emu() -> EmuEnv {
+    let mut emu = new_emu();
+    enable_sv39(&mut emu);
+    emu
+}
+
+fn translate(emu: &mut EmuEnv, va: u64, acc_type: MemAccType) -> Option<u64> {
+    emu.convert_physical_address(0x8000_0000, va, 1, acc_type).ok()
+}
+
+#[test]
+fn sv39_4k_page() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | W | A | D));
+    assert_eq!(translate(&mut emu, 0x4000_1234, MemAccType::Read), Some(0x8003_0234));
+    assert_eq!(translate(&mut emu, 0x4000_1ff8, MemAccType::Write), Some(0x8003_0ff8));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv39_megapage() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L1_PPN, 1, leaf(0x80000, R | X | A));
+    assert_eq!(translate(&mut emu, 0x4021_2345, MemAccType::Fetch), Some(0x8001_2345));
+    assert_eq!(translate(&mut emu, 0x4021_2345, MemAccType::Write), None);
+}
+
+#[test]
+fn sv39_gigapage() {
+    let mut emu = new_sv39_emu();
+    set_pte(&emu, ROOT_PPN, 2, leaf(0x80000, R | W | X | A | D));
+    assert_eq!(translate(&mut emu, 0x8004_5678, MemAccType::Read), Some(0x8004_5678));
+}
+
+#[test]
+fn sv39_misaligned_superpage() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L1_PPN, 1, leaf(0x80001, R | A));
+    set_pte(&emu, ROOT_PPN, 2, leaf(0x80200, R | A));
+    assert_eq!(translate(&mut emu, 0x4020_0000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0x8000_0000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv39_reserved_bits() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | A) | (1 << 54));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80030, W | A));
+    set_pte(&emu, L1_PPN, 2, pointer(L0_PPN) | A);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0x4040_1000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv39_non_canonical() {
+    let mut emu = new_sv39_emu();
+    set_pte(&emu, ROOT_PPN, 0, leaf(0x80000, R | A));
+    set_pte(&emu, ROOT_PPN, 0x1ff, leaf(0x80000, R | A));
+    assert_eq!(translate(&mut emu, 0x0000_0000_0000_1000, MemAccType::Read), Some(0x8000_1000));
+    assert_eq!(translate(&mut emu, 0xffff_ffff_c000_1000, MemAccType::Read), Some(0x8000_1000));
+    assert_eq!(translate(&mut emu, 0x0000_0080_0000_1000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0xffff_ff7f_c000_1000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv39_ad_trap() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | W));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80030, R | W | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Write), None);
+    assert_eq!(emu.read_mem_8byte((L0_PPN << 12) + 8), leaf(0x80030, R | W));
+}
+
+#[test]
+fn sv39_ad_update() {
+    let mut emu = new_sv39_emu();
+    emu.m_csr.csrrs(CsrAddr::Menvcfg, 1 << 61);
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | W));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(emu.read_mem_8byte((L0_PPN << 12) + 8), leaf(0x80030, R | W | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Write), Some(0x8003_0000));
+    assert_eq!(emu.read_mem_8byte((L0_PPN << 12) + 8), leaf(0x80030, R | W | A | D));
+}
+
+#[test]
+fn sv48_gigapage() {
+    let mut emu = new_sv39_emu();
+    emu.m_csr.csrrw(CsrAddr::Satp, (9 << 60) | ROOT_PPN as i64);
+    set_pte(&emu, ROOT_PPN, 0, pointer(L1_PPN));
+    set_pte(&emu, L1_PPN, 2, leaf(0x80000, R | A));
+    assert_eq!(translate(&mut emu, 0x8000_1234, MemAccType::Read), Some(0x8000_1234));
+    assert_eq!(translate(&mut emu, 0x0000_8000_8000_1234, MemAccType::Read), None);
+}
+
+#[test]
+fn sv57_gigapage() {
+    let mut emu = new_sv39_emu();
+    emu.m_csr.csrrw(CsrAddr::Satp, (10 << 60) | ROOT_PPN as i64);
+    set_pte(&emu, ROOT_PPN, 0, pointer(L1_PPN));
+    set_pte(&emu, L1_PPN, 0, pointer(L0_PPN));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80000, R | A));
+    assert_eq!(translate(&mut emu, 0x8000_1234, MemAccType::Read), Some(0x8000_1234));
+    assert_eq!(translate(&mut emu, 0x0100_0000_8000_1234, MemAccType::Read), None);
+}
+
+#[test]
+fn satp_unsupported_mode_is_ignored() {
+    let mut emu = new_sv39_emu();
+    let satp = emu.m_csr.csrrs(CsrAddr::Satp, 0);
+    emu.m_iregs[5] = (11 << 60) | 0x80020;
+    EmuEnv::helper_func_csrrw(&mut emu, 0, 5, 0x180, 0x8000_0000);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Satp, 0), satp);
+    emu.m_iregs[5] = (9 << 60) | 0x80020;
+    EmuEnv::helper_func_csrrw(&mut emu, 0, 5, 0x180, 0x8000_0000);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Satp, 0), (9 << 60) | 0x80020);
+}
+
+#[test]
+fn access_fault_outside_ram() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x10000, R | W | A | D));
+    set_pte(&emu, L0_PPN, 2, pointer(0x20000));
+    assert_eq!(emu.convert_physical_address(0x8000_0000, 0x4000_1000, 1, MemAccType::Read), Err(MemResult::AccessFault));
+    assert_eq!(emu.convert_physical_address(0x8000_0000, 0x4000_2000, 1, MemAccType::Read), Err(MemResult::TlbError));
+    set_pte(&emu, L1_PPN, 1, pointer(0x20000));
+    assert_eq!(emu.convert_physical_address(0x8000_0000, 0x4020_0000, 1, MemAccType::Read), Err(MemResult::AccessFault));
+}
+
+#[test]
+fn load_store_fault_causes() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x10000, R | W | A | D));
+    emu.m_iregs[5] = 0x4000_1008;
+    let cases: [(fn(&mut EmuEnv, u64, u64, u64, u64) -> usize, u64, i64); 4] = [
+        (EmuEnv::helper_func_loadu8, 0, 5),
+        (EmuEnv::helper_func_store32, 0, 7),
+        (EmuEnv::helper_func_load64, 0x1000, 13),
+        (EmuEnv::helper_func_float_store64, 0x1000, 15),
+    ];
+    for (helper, offset, cause) in cases.iter() {
+        emu.m_priv = PrivMode::Supervisor;
+        helper(&mut emu, 6, 5, *offset, 0x8000_0000);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), *cause);
+        assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x4000_1008 + *offset as i64);
+    }
+}
+
+#[test]
+fn sv39_user_page_sum() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | W | X | U | A | D));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80031, R | W | X | A | D));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), None);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Write), None);
+    emu.m_csr.csrrs(CsrAddr::Sstatus, 1 << 18);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Write), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Fetch), None);
+    emu.m_priv = PrivMode::User;
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Fetch), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv39_mxr() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, X | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), None);
+    emu.m_csr.csrrs(CsrAddr::Sstatus, 1 << 19);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Write), None);
+}
+
+#[test]
+fn sv39_mprv() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | U | A));
+    emu.m_priv = PrivMode::Machine;
+    assert_eq!(translate(&mut emu, 0x8003_0000, MemAccType::Read), Some(0x8003_0000));
+    // MPRV = 1, MPP = U : loads are translated and checked as U-mode
+    emu.m_csr.csrrc(CsrAddr::Mstatus, 3 << 11);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 1 << 17);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    // MPP = S without SUM
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 1 << 11);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), None);
+}
+
+#[test]
+fn tlb_dropped_when_sum_is_cleared() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | U | A));
+    emu.m_iregs[5] = 1 << 18;
+    EmuEnv::helper_func_csrrs(&mut emu, 0, 5, 0x100, 0x8000_0000);
+    emu.m_iregs[6] = 0x4000_1000;
+    EmuEnv::helper_func_load64(&mut emu, 7, 6, 0, 0x8000_0000);
+    let load_idx = tlb_slot(PrivMode::Supervisor, false) * TLB_SIZE + 1;
+    assert_eq!(emu.m_tlb_vec[load_idx], 0x4000_1000 >> 24);
+    // A load does not let later stores through the inline TLB
+    assert_ne!(emu.m_tlb_vec[tlb_slot(PrivMode::Supervisor, true) * TLB_SIZE + 1], 0x4000_1000 >> 24);
+    EmuEnv::helper_func_csrrc(&mut emu, 0, 5, 0x100, 0x8000_0000);
+    assert_ne!(emu.m_tlb_vec[load_idx], 0x4000_1000 >> 24);
+}
+
+fn set_asid(emu: &mut EmuEnv, asid: u64) {
+    emu.m_csr.csrrw(CsrAddr::Satp, ((8 << 60) | (asid << 44) | ROOT_PPN) as i64);
+}
+
+fn sfence_vma(emu: &mut EmuEnv, vaddr: Option<u64>, asid: Option<u64>) {
+    emu.m_iregs[5] = vaddr.unwrap_or(0);
+    emu.m_iregs[6] = asid.unwrap_or(0);
+    let rs1 = if vaddr.is_some() { 5 } else { 0 };
+    let rs2 = if asid.is_some() { 6 } else { 0 };
+    EmuEnv::helper_func_sfence_vma(emu, rs1, rs2, 0, 0x8000_0000);
+}
+
+#[test]
+fn tlb_asid_tagged() {
+    let mut emu = new_sv39_emu();
+    set_asid(&mut emu, 1);
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    // Without a fence, ASID 1 keeps its translation while ASID 2 walks the new PTE
+    set_pte(&emu, L0_PPN, 1, leaf(0x80031, R | A));
+    set_asid(&mut emu, 2);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_1000));
+    set_asid(&mut emu, 1);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    sfence_vma(&mut emu, None, Some(1));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_1000));
+}
+
+// Inline TLB entries carry the ASID : switching satp keeps them and an ASID
+// fence drops only those of that ASID
+#[test]
+fn inline_tlb_asid_tagged() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | A));
+    write_csr(&mut emu, 0x180, (8 << 60) | (1 << 44) | ROOT_PPN);
+    emu.m_iregs[6] = 0x4000_1000;
+    EmuEnv::helper_func_load64(&mut emu, 7, 6, 0, 0x8000_0000);
+    let load_idx = tlb_slot(PrivMode::Supervisor, false) * TLB_SIZE + 1;
+    let tag_asid1 = (0x4000_1000 >> 24) | (1 << TLB_ASID_SHIFT);
+    assert_eq!(emu.m_tlb_vec[load_idx], tag_asid1);
+    // Another ASID and root page table
+    write_csr(&mut emu, 0x180, (8 << 60) | (2 << 44) | L1_PPN);
+    assert_eq!(emu.m_tlb_asid_tag[0], 2 << TLB_ASID_SHIFT);
+    assert_eq!(emu.m_tlb_vec[load_idx], tag_asid1);
+    sfence_vma(&mut emu, Some(0x4000_1000), Some(2));
+    assert_eq!(emu.m_tlb_vec[load_idx], tag_asid1);
+    sfence_vma(&mut emu, None, Some(1));
+    assert_ne!(emu.m_tlb_vec[load_idx], tag_asid1);
+    // A MODE change drops every entry
+    write_csr(&mut emu, 0x180, (8 << 60) | (1 << 44) | ROOT_PPN);
+    EmuEnv::helper_func_load64(&mut emu, 7, 6, 0, 0x8000_0000);
+    assert_eq!(emu.m_tlb_vec[load_idx], tag_asid1);
+    write_csr(&mut emu, 0x180, 0);
+    assert_ne!(emu.m_tlb_vec[load_idx], tag_asid1);
+}
+
+#[test]
+fn sfence_vma_keeps_global_pages() {
+    let mut emu = new_sv39_emu();
+    set_asid(&mut emu, 1);
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | G | A));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80031, R | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8003_1000));
+    set_pte(&emu, L0_PPN, 1, leaf(0x80040, R | G | A));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80041, R | A));
+    // A global page is shared by every ASID and survives an ASID fence
+    set_asid(&mut emu, 2);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    set_asid(&mut emu, 1);
+    sfence_vma(&mut emu, None, Some(1));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8004_1000));
+    sfence_vma(&mut emu, None, None);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8004_0000));
+}
+
+#[test]
+fn sfence_vma_by_address() {
+    let mut emu = new_sv39_emu();
+    setup_tables(&emu);
+    set_pte(&emu, L0_PPN, 1, leaf(0x80030, R | G | A));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80031, R | A));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8003_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8003_1000));
+    set_pte(&emu, L0_PPN, 1, leaf(0x80040, R | G | A));
+    set_pte(&emu, L0_PPN, 2, leaf(0x80041, R | A));
+    sfence_vma(&mut emu, Some(0x4000_1234), None);
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8004_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8003_1000));
+    // Address and ASID : global pages are not affected
+    set_pte(&emu, L0_PPN, 1, leaf(0x80050, R | G | A));
+    sfence_vma(&mut emu, Some(0x4000_1000), Some(0));
+    sfence_vma(&mut emu, Some(0x4000_2000), Some(0));
+    assert_eq!(translate(&mut emu, 0x4000_1000, MemAccType::Read), Some(0x8004_0000));
+    assert_eq!(translate(&mut emu, 0x4000_2000, MemAccType::Read), Some(0x8004_1000));
+}
+
+const SFENCE_VMA: u64 = 0x1200_0073;
+
+// sfence.vma is not allowed in U-mode, and mstatus.TVM traps it and the
+// satp/hgatp accesses of S-mode
+#[test]
+fn sfence_vma_and_satp_privilege() {
+    let mut emu = new_sv39_emu();
+    emu.m_priv = PrivMode::User;
+    assert_eq!(EmuEnv::helper_func_sfence_vma(&mut emu, 0, 0, SFENCE_VMA, 0x8000_0000), 1);
+    assert_eq!(mcause(&mut emu), 2);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), SFENCE_VMA as i64);
+
+    let mut emu = new_sv39_emu();
+    assert_eq!(EmuEnv::helper_func_sfence_vma(&mut emu, 0, 0, SFENCE_VMA, 0x8000_0000), 0);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 1 << 20);
+    assert_eq!(EmuEnv::helper_func_sfence_vma(&mut emu, 0, 0, SFENCE_VMA, 0x8000_0000), 1);
+    assert_eq!(mcause(&mut emu), 2);
+    for csr_addr in [0x180, 0x680].iter() {
+        emu.m_priv = PrivMode::Supervisor;
+        assert_eq!(EmuEnv::helper_func_csrrs(&mut emu, 7, 0, *csr_addr, 0x8000_0000), 1);
+        assert_eq!(mcause(&mut emu), 2);
+    }
+    // M-mode is not affected
+    emu.m_priv = PrivMode::Machine;
+    assert_eq!(EmuEnv::helper_func_sfence_vma(&mut emu, 0, 0, SFENCE_VMA, 0x8000_0000), 0);
+    assert_eq!(EmuEnv::helper_func_csrrs(&mut emu, 7, 0, 0x180, 0x8000_0000), 0);
+    assert_eq!(emu.m_iregs[7] >> 60, 8);
+}
+
+fn write_csr(emu: &mut EmuEnv, csr_addr: u64, data: u64) {
+    let curr_priv = emu.m_priv;
+    emu.m_priv = PrivMode::Machine;
+    emu.m_iregs[5] = data;
+    EmuEnv::helper_func_csrrw(emu, 0, 5, csr_addr, 0x8000_0000);
+    emu.m_priv = curr_priv;
+}
+
+// pmpcfg : R, W, X, A = NAPOT, L
+const PMP_R: u64 = 1 << 0;
+const PMP_W: u64 = 1 << 1;
+const PMP_X: u64 = 1 << 2;
+const PMP_TOR: u64 = 1 << 3;
+const PMP_NAPOT: u64 = 3 << 3;
+const PMP_L: u64 = 1 << 7;
+
+#[test]
+fn pmp_napot_and_tor() {
+    let mut emu = new_sv39_emu();
+    set_pte(&emu, ROOT_PPN, 2, leaf(0x80000, R | W | X | A | D));
+    // Entry 0 : NAPOT 0x8000_0000 - 0x8003_ffff, R/X. Entry 1 : TOR up to 0x8006_0000, R/W.
+    write_csr(&mut emu, 0x3b0, (0x8000_0000 >> 2) | 0x7fff);
+    write_csr(&mut emu, 0x3b1, 0x8006_0000 >> 2);
+    write_csr(&mut emu, 0x3a0, ((PMP_TOR | PMP_R | PMP_W) << 8) | PMP_NAPOT | PMP_R | PMP_X);
+    assert_eq!(translate(&mut emu, 0x8001_0000, MemAccType::Read), Some(0x8001_0000));
+    assert_eq!(translate(&mut emu, 0x8001_0000, MemAccType::Fetch), Some(0x8001_0000));
+    assert_eq!(emu.convert_physical_address(0x8000_0000, 0x8001_0000, 1, MemAccType::Write), Err(MemResult::AccessFault));
+    assert_eq!(translate(&mut emu, 0x8005_0000, MemAccType::Write), Some(0x8005_0000));
+    assert_eq!(translate(&mut emu, 0x8005_0000, MemAccType::Fetch), None);
+    // No matching entry : denied below M-mode, allowed in M-mode
+    assert_eq!(translate(&mut emu, 0x8007_0000, MemAccType::Read), None);
+    emu.m_priv = PrivMode::Machine;
+    assert_eq!(translate(&mut emu, 0x8007_0000, MemAccType::Read), Some(0x8007_0000));
+    assert_eq!(translate(&mut emu, 0x8001_0000, MemAccType::Write), Some(0x8001_0000));
+}
+
+// An entry matching only part of an access fails it, even when a later
+// entry covers all of it
+#[test]
+fn pmp_tor_boundary_straddle() {
+    let mut emu = new_sv39_emu();
+    set_pte(&emu, ROOT_PPN, 2, leaf(0x80000, R | W | X | A | D));
+    // Entry 0 : TOR 0 - 0x8005_0004, R. Entry 1 : NAPOT 0x8000_0000 - 0x8007_ffff, R/W.
+    write_csr(&mut emu, 0x3b0, 0x8005_0004 >> 2);
+    write_csr(&mut emu, 0x3b1, (0x8000_0000 >> 2) | 0xffff);
+    write_csr(&mut emu, 0x3a0, ((PMP_NAPOT | PMP_R | PMP_W) << 8) | PMP_TOR | PMP_R);
+    let load = |emu: &mut EmuEnv, addr: u64, size: u64| emu.convert_physical_address(0x8000_0000, addr, size, MemAccType::Read);
+    assert_eq!(load(&mut emu, 0x8005_0000, 4), Ok(0x8005_0000));
+    assert_eq!(load(&mut emu, 0x8005_0004, 4), Ok(0x8005_0004));
+    assert_eq!(load(&mut emu, 0x8005_0000, 8), Err(MemResult::AccessFault));
+    // The same holds in M-mode
+    emu.m_priv = PrivMode::Machine;
+    assert_eq!(load(&mut emu, 0x8005_0000, 8), Err(MemResult::AccessFault));
+    // An ld through the helper raises a load access fault
+    emu.m_priv = PrivMode::Supervisor;
+    emu.m_iregs[5] = 0x8005_0000;
+    assert_ne!(EmuEnv::helper_func_load64(&mut emu, 6, 5, 0, 0x8000_0000), 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 5);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mtval, 0), 0x8005_0000);
+}
+
+#[test]
+fn pmp_lock() {
+    let mut emu = new_sv39_emu();
+    emu.m_priv = PrivMode::Machine;
+    write_csr(&mut emu, 0x3b0, (0x8000_0000 >> 2) | 0x7fff);
+    write_csr(&mut emu, 0x3a0, PMP_L | PMP_NAPOT | PMP_R);
+    assert_eq!(translate(&mut emu, 0x8001_0000, MemAccType::Write), None);
+    assert_eq!(translate(&mut emu, 0x8001_0000, MemAccType::Read), Some(0x8001_0000));
+    // A locked entry cannot be changed until reset
+    write_csr(&mut emu, 0x3a0, PMP_NAPOT | PMP_R | PMP_W);
+    write_csr(&mut emu, 0x3b0, 0);
+    assert_eq!(emu.m_pmp.read_csr(0x3a0, false), PMP_L | PMP_NAPOT | PMP_R);
+    assert_eq!(emu.m_pmp.read_csr(0x3b0, false), (0x8000_0000 >> 2) | 0x7fff);
+}
+
+#[test]
+fn pmp_csr_is_machine_only() {
+    let mut emu = new_sv39_emu();
+    emu.m_iregs[5] = 0x1f;
+    assert_eq!(EmuEnv::helper_func_csrrw(&mut emu, 0, 5, 0x3a0, 0x8000_0000), 1);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mcause, 0), 2);
+    assert_eq!(emu.m_pmp.read_csr(0x3a0, false), 0);
+}
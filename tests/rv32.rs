@@ -0,0 +1,261 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::elf_loader::ELFLoader;
+use dydra::emu_env::{EmuEnv, XlenEnum};
+use dydra::target::riscv::mmu::MemAccType;
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const OP: u32 = 0x33;
+const OP_IMM: u32 = 0x13;
+
+// Upper 32 bits of an RV32 register holding a negative value
+const SEXT: u64 = 0xffff_ffff_0000_0000;
+
+// C.NOP, to fill the upper half of a word holding one compressed instruction
+const C_NOP: u32 = 0x0001;
+
+fn new_rv32_emu() -> EmuEnv {
+    let mut emu = new_emu();
+    emu.m_xlen = XlenEnum::Rv32;
+    emu
+}
+
+// rd = x7, rs1 = x5, rs2 = x6
+fn exec(emu: &mut EmuEnv, inst: u32, rs1: u64, rs2: u64) -> u64 {
+    emu.m_iregs[5] = rs1;
+    emu.m_iregs[6] = rs2;
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    emu.m_iregs[7]
+}
+
+fn rr(funct7: u32, funct3: u32) -> u32 {
+    r_type(funct7, 6, 5, funct3, 7, OP)
+}
+
+// One compressed instruction, which must fall through to the next halfword
+fn exec_c(emu: &mut EmuEnv, inst: u32) {
+    run(emu, &[(C_NOP << 16) | inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 2);
+}
+
+#[test]
+fn mulh_rv32() {
+    let mut emu = new_rv32_emu();
+    // mulh : -2^31 * 2 = -2^32
+    assert_eq!(exec(&mut emu, rr(0x01, 0b001), SEXT | 0x8000_0000, 2), u64::MAX);
+    // mulhu : 0xffffffff * 0xffffffff, whatever the sign-extended upper bits
+    assert_eq!(exec(&mut emu, rr(0x01, 0b011), u64::MAX, u64::MAX), SEXT | 0xffff_fffe);
+    assert_eq!(exec(&mut emu, rr(0x01, 0b011), SEXT | 0x8000_0000, SEXT | 0x8000_0000), 0x4000_0000);
+    // mulhsu : -1 * 0xffffffff
+    assert_eq!(exec(&mut emu, rr(0x01, 0b010), u64::MAX, u64::MAX), u64::MAX);
+    assert_eq!(exec(&mut emu, rr(0x01, 0b010), 1, u64::MAX), 0);
+}
+
+#[test]
+fn rev8_rv32_ignores_upper_bits() {
+    let mut emu = new_rv32_emu();
+    let rev8 = i_type(0x698, 5, 0b101, 7, OP_IMM);
+    assert_eq!(exec(&mut emu, rev8, SEXT | 0x8000_0001, 0), 0x0100_0080);
+    assert_eq!(exec(&mut emu, rev8, 0x0000_0080, 0), SEXT | 0x8000_0000);
+}
+
+#[test]
+fn c_srli_rv32() {
+    let mut emu = new_rv32_emu();
+    // c.srli x8, 4 shifts in zeros at bit 31, not at bit 63
+    emu.m_iregs[8] = SEXT | 0x8000_0000;
+    exec_c(&mut emu, 0x8011);
+    assert_eq!(emu.m_iregs[8], 0x0800_0000);
+    // c.srli x8, 1
+    emu.m_iregs[8] = SEXT | 0xffff_fff0;
+    exec_c(&mut emu, 0x8005);
+    assert_eq!(emu.m_iregs[8], 0x7fff_fff8);
+}
+
+#[test]
+fn zbs_rv32_shamt_masked() {
+    let mut emu = new_rv32_emu();
+    // Only the low 5 bits of rs2 index the bit
+    assert_eq!(exec(&mut emu, rr(0x14, 0b001), 0, 35), 0x8);
+    assert_eq!(exec(&mut emu, rr(0x14, 0b001), 0, 31), SEXT | 0x8000_0000);
+    assert_eq!(exec(&mut emu, rr(0x24, 0b001), u64::MAX, 63), 0x7fff_ffff);
+    assert_eq!(exec(&mut emu, rr(0x34, 0b001), 0, 63), SEXT | 0x8000_0000);
+    assert_eq!(exec(&mut emu, rr(0x34, 0b001), SEXT | 0x8000_0000, 31), 0);
+    assert_eq!(exec(&mut emu, rr(0x24, 0b101), 0x8, 35), 1);
+}
+
+#[test]
+fn c_jal() {
+    let mut emu = new_rv32_emu();
+    // c.jal +8 : the link is the sign-extended address of the next halfword
+    run(&mut emu, &[(C_NOP << 16) | 0x2021]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+    assert_eq!(emu.m_iregs[1], SEXT | (CODE_BASE + 2));
+}
+
+#[test]
+fn c_flw_fsw() {
+    let mut emu = new_rv32_emu();
+    enable_fp(&mut emu);
+    emu.write_mem_4byte(0x8000_1004, 0x3f80_0000);
+    emu.write_mem_8byte(0x8000_1008, 0xaaaa_aaaa_aaaa_aaaa);
+    emu.m_iregs[9] = SEXT | 0x8000_1000;
+    // c.flw f8, 4(x9)
+    exec_c(&mut emu, 0x60c0);
+    assert_eq!(emu.m_fregs[8], BOX_S | 0x3f80_0000);
+    // c.fsw f8, 8(x9) only writes 4 bytes
+    emu.m_fregs[8] = BOX_S | 0x4000_0000;
+    exec_c(&mut emu, 0xe480);
+    assert_eq!(emu.read_mem_8byte(0x8000_1008), 0xaaaa_aaaa_4000_0000);
+}
+
+#[test]
+fn c_flwsp_fswsp() {
+    let mut emu = new_rv32_emu();
+    enable_fp(&mut emu);
+    emu.write_mem_4byte(0x8000_100c, 0x4040_0000);
+    emu.write_mem_8byte(0x8000_1010, 0);
+    emu.m_iregs[2] = SEXT | 0x8000_1000;
+    // c.flwsp f9, 12(sp)
+    exec_c(&mut emu, 0x64b2);
+    assert_eq!(emu.m_fregs[9], BOX_S | 0x4040_0000);
+    // c.fswsp f9, 16(sp)
+    exec_c(&mut emu, 0xe826);
+    assert_eq!(emu.read_mem_8byte(0x8000_1010), 0x4040_0000);
+}
+
+// Sv32 : 4-byte PTEs, root at ROOT_PPN and one VPN[0] table at L0_PPN
+fn set_pte32(emu: &EmuEnv, table_ppn: u64, idx: u64, pte: u64) {
+    emu.write_mem_4byte((table_ppn << 12) + idx * 4, pte as u32);
+}
+
+fn enable_sv32(emu: &mut EmuEnv) {
+    emu.m_csr.csrrw(CsrAddr::Satp, (1 << 31) | ROOT_PPN as i64);
+    emu.m_priv = PrivMode::Supervisor;
+}
+
+fn translate(emu: &mut EmuEnv, va: u64, acc_type: MemAccType) -> Option<u64> {
+    emu.convert_physical_address(0x8000_0000, va, 1, acc_type).ok()
+}
+
+#[test]
+fn sv32_4k_page() {
+    let mut emu = new_rv32_emu();
+    enable_sv32(&mut emu);
+    // VA 0x4000_1000 : VPN[1] = 0x100, VPN[0] = 1
+    set_pte32(&emu, ROOT_PPN, 0x100, pointer(L0_PPN));
+    set_pte32(&emu, L0_PPN, 1, leaf(0x80030, R | W | A | D));
+    // The next entry is 4 bytes further on
+    set_pte32(&emu, L0_PPN, 2, leaf(0x80050, R | W | A | D));
+    assert_eq!(translate(&mut emu, 0x4000_1234, MemAccType::Read), Some(0x8003_0234));
+    assert_eq!(translate(&mut emu, 0x4000_2234, MemAccType::Write), Some(0x8005_0234));
+    assert_eq!(translate(&mut emu, 0x4000_3000, MemAccType::Read), None);
+}
+
+#[test]
+fn sv32_megapage() {
+    let mut emu = new_rv32_emu();
+    enable_sv32(&mut emu);
+    // 4 MiB superpage at VA 0x8040_0000 (VPN[1] = 0x201)
+    set_pte32(&emu, ROOT_PPN, 0x201, leaf(0x80000, R | X | A));
+    assert_eq!(translate(&mut emu, 0x8041_2345, MemAccType::Fetch), Some(0x8001_2345));
+    assert_eq!(translate(&mut emu, 0x8047_fffc, MemAccType::Read), Some(0x8007_fffc));
+    assert_eq!(translate(&mut emu, 0x8041_2345, MemAccType::Write), None);
+    // A sign-extended register value names the same address
+    assert_eq!(translate(&mut emu, SEXT | 0x8041_2345, MemAccType::Read), Some(0x8001_2345));
+    // PPN[0] of a superpage must be zero
+    set_pte32(&emu, ROOT_PPN, 0x202, leaf(0x80001, R | A));
+    assert_eq!(translate(&mut emu, 0x8080_0000, MemAccType::Read), None);
+}
+
+fn push16(image: &mut Vec<u8>, data: u16) {
+    image.extend_from_slice(&data.to_le_bytes());
+}
+
+fn push32(image: &mut Vec<u8>, data: u32) {
+    image.extend_from_slice(&data.to_le_bytes());
+}
+
+fn section32(image: &mut Vec<u8>, sh_flags: u32, sh_addr: u32, sh_offset: u32, sh_size: u32) {
+    for field in [0, 1, sh_flags, sh_addr, sh_offset, sh_size, 0, 0, 4, 0].iter() {
+        push32(image, *field);
+    }
+}
+
+// ELF32 executable : a .text section with code at 0x8000_0000, in one
+// PT_LOAD segment, and a .data section at 0x8000_1000.
+fn elf32_image(code: &[u32], data: u32) -> Vec<u8> {
+    let text_off = 0x100;
+    let text_size = code.len() as u32 * 4;
+    let data_off = text_off + text_size;
+
+    let mut image = vec![0x7f, b'E', b'L', b'F', 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    push16(&mut image, 2);          // e_type = ET_EXEC
+    push16(&mut image, 0xf3);       // e_machine = RISC-V
+    push32(&mut image, 1);          // e_version
+    push32(&mut image, 0x8000_0000); // e_entry
+    push32(&mut image, 52);         // e_phoff
+    push32(&mut image, 52 + 32);    // e_shoff
+    push32(&mut image, 0);          // e_flags
+    push16(&mut image, 52);         // e_ehsize
+    push16(&mut image, 32);         // e_phentsize
+    push16(&mut image, 1);          // e_phnum
+    push16(&mut image, 40);         // e_shentsize
+    push16(&mut image, 3);          // e_shnum
+    push16(&mut image, 0);          // e_shstrndx
+
+    // PT_LOAD, with p_flags (R | X) after p_memsz
+    for field in [1, text_off, 0x8000_0000, 0x8000_0000, text_size, text_size, 5, 0x1000].iter() {
+        push32(&mut image, *field);
+    }
+
+    image.extend_from_slice(&[0; 40]);
+    section32(&mut image, 0x6, 0x8000_0000, text_off, text_size);   // AX
+    section32(&mut image, 0x3, 0x8000_1000, data_off, 4);           // WA
+
+    image.resize(text_off as usize, 0);
+    for inst in code.iter() {
+        push32(&mut image, *inst);
+    }
+    push32(&mut image, data);
+    image
+}
+
+#[test]
+fn elf32_load() {
+    // addi x6, x0, -1 ; srli x6, x6, 1
+    let code = [i_type(-1, 0, 0b000, 6, OP_IMM), i_type(1, 6, 0b101, 6, OP_IMM)];
+    let path = std::env::temp_dir().join(format!("dydra_rv32_{}.elf", std::process::id()));
+    std::fs::write(&path, elf32_image(&code, 0xdead_beef)).unwrap();
+    let filename = path.to_str().unwrap().to_string();
+
+    let loader = ELFLoader::new(&filename).unwrap();
+    assert!(loader.is_elf32());
+    let elf_header = loader.get_elf_header();
+    assert_eq!(elf_header.e_entry, 0x8000_0000);
+    assert_eq!(elf_header.e_shnum, 3);
+    let phdr = loader.get_program_header(elf_header.e_phoff, elf_header.e_phentsize, 0);
+    assert_eq!(phdr.p_vaddr, 0x8000_0000);
+    assert_eq!(phdr.p_flags, 5);
+    assert_eq!(phdr.p_align, 0x1000);
+    let shdr = loader.get_section_header(elf_header.e_shoff, elf_header.e_shentsize, 2);
+    assert_eq!(shdr.sh_addr, 0x8000_1000);
+    assert_eq!(shdr.sh_offset, 0x108);
+
+    // The ELF class selects RV32 : misa.MXL = 1 and srli shifts 32 bits
+    let mut emu = new_emu();
+    emu.load_elf(&filename);
+    std::fs::remove_file(&path).unwrap();
+    assert!(emu.m_xlen == XlenEnum::Rv32);
+    assert_eq!(emu.m_csr.m_misa.m_csr >> 30, 1);
+    assert_eq!(emu.read_mem_4byte(0x8000_0000), code[0]);
+    assert_eq!(emu.read_mem_4byte(0x8000_1000), 0xdead_beef);
+    emu.run_blocks(2);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 8);
+    assert_eq!(emu.m_iregs[6], 0x7fff_ffff);
+}
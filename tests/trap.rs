@@ -0,0 +1,138 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv::PrivMode;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const ECALL: u32 = 0x0000_0073;
+const SRET: u32 = 0x1020_0073;
+const MRET: u32 = 0x3020_0073;
+
+const RET_PC: u64 = 0x8000_0100;
+
+const MSTATUS_SIE: i64 = 1 << 1;
+const MSTATUS_MIE: i64 = 1 << 3;
+const MSTATUS_SPIE: i64 = 1 << 5;
+const MSTATUS_MPIE: i64 = 1 << 7;
+const MSTATUS_SPP: i64 = 1 << 8;
+const MSTATUS_MPP: i64 = 3 << 11;
+const MSTATUS_MPRV: i64 = 1 << 17;
+const MSTATUS_TSR: i64 = 1 << 22;
+
+fn mstatus(emu: &mut EmuEnv) -> i64 {
+    emu.m_csr.csrrs(CsrAddr::Mstatus, 0)
+}
+
+fn mpp(priv_mode: PrivMode) -> i64 {
+    (priv_mode as i64) << 11
+}
+
+#[test]
+fn mret_restores_mpp_mie_and_clears_mprv() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Mepc, RET_PC as i64);
+    emu.m_csr.csrrw(CsrAddr::Mstatus, mpp(PrivMode::Supervisor) | MSTATUS_MPIE | MSTATUS_MPRV);
+    run(&mut emu, &[MRET]);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert_eq!(emu.m_pc[0], RET_PC);
+    let status = mstatus(&mut emu);
+    assert_eq!(status & (MSTATUS_MIE | MSTATUS_MPIE), MSTATUS_MIE | MSTATUS_MPIE);
+    assert_eq!(status & MSTATUS_MPP, mpp(PrivMode::User));
+    assert_eq!(status & MSTATUS_MPRV, 0);
+
+    // MPIE = 0 clears MIE, and MPRV stays set when returning to M-mode
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Mepc, RET_PC as i64);
+    emu.m_csr.csrrw(CsrAddr::Mstatus, mpp(PrivMode::Machine) | MSTATUS_MIE | MSTATUS_MPRV);
+    run(&mut emu, &[MRET]);
+    assert!(emu.m_priv == PrivMode::Machine);
+    let status = mstatus(&mut emu);
+    assert_eq!(status & (MSTATUS_MIE | MSTATUS_MPIE), MSTATUS_MPIE);
+    assert_eq!(status & MSTATUS_MPRV, MSTATUS_MPRV);
+}
+
+#[test]
+fn mpp_ignores_the_reserved_encoding() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Mstatus, mpp(PrivMode::Supervisor));
+    emu.m_csr.csrrs(CsrAddr::Mstatus, mpp(PrivMode::Machine));
+    emu.m_csr.csrrc(CsrAddr::Mstatus, mpp(PrivMode::Supervisor));
+    assert_eq!(mstatus(&mut emu) & MSTATUS_MPP, mpp(PrivMode::Machine));
+    emu.m_csr.csrrw(CsrAddr::Mstatus, mpp(PrivMode::Hypervisor));
+    assert_eq!(mstatus(&mut emu) & MSTATUS_MPP, mpp(PrivMode::Machine));
+}
+
+#[test]
+fn sret_traps_with_tsr() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Sepc, RET_PC as i64);
+    emu.m_csr.csrrw(CsrAddr::Mstatus, MSTATUS_TSR | MSTATUS_SPP);
+    emu.m_priv = PrivMode::Supervisor;
+    run(&mut emu, &[SRET]);
+    assert_eq!(mcause(&mut emu), 2);
+    assert!(emu.m_priv == PrivMode::Machine);
+    assert_eq!(emu.m_pc[0], MTVEC);
+    // TSR does not apply to M-mode
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_SPP | MSTATUS_SPIE);
+    run(&mut emu, &[SRET]);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert_eq!(emu.m_pc[0], RET_PC);
+    assert_eq!(mstatus(&mut emu) & (MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP), MSTATUS_SIE | MSTATUS_SPIE);
+}
+
+#[test]
+fn medeleg_is_ignored_in_m_mode() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Stvec, STVEC as i64);
+    emu.m_csr.csrrw(CsrAddr::Medeleg, 1 << 11);
+    run(&mut emu, &[ECALL]);
+    assert_eq!(mcause(&mut emu), 11);
+    assert!(emu.m_priv == PrivMode::Machine);
+    assert_eq!(emu.m_pc[0], MTVEC);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mepc, 0), CODE_BASE as i64);
+    // The same bit delegates an ecall from S-mode
+    emu.m_csr.csrrw(CsrAddr::Medeleg, 1 << 9);
+    emu.m_priv = PrivMode::Supervisor;
+    run(&mut emu, &[ECALL]);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Scause, 0), 9);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    assert_eq!(emu.m_pc[0], STVEC);
+}
+
+#[test]
+fn trap_entry_sets_pp_pie_ie() {
+    // S-mode to M-mode with MIE set
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Mstatus, MSTATUS_MIE);
+    emu.m_priv = PrivMode::Supervisor;
+    run(&mut emu, &[ECALL]);
+    let status = mstatus(&mut emu);
+    assert_eq!(status & (MSTATUS_MIE | MSTATUS_MPIE), MSTATUS_MPIE);
+    assert_eq!(status & MSTATUS_MPP, mpp(PrivMode::Supervisor));
+
+    // U-mode to S-mode with SIE set
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Stvec, STVEC as i64);
+    emu.m_csr.csrrw(CsrAddr::Medeleg, 1 << 8);
+    emu.m_csr.csrrw(CsrAddr::Mstatus, MSTATUS_SIE | MSTATUS_SPP);
+    emu.m_priv = PrivMode::User;
+    run(&mut emu, &[ECALL]);
+    assert!(emu.m_priv == PrivMode::Supervisor);
+    let status = mstatus(&mut emu);
+    assert_eq!(status & (MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP), MSTATUS_SPIE);
+    // M-mode fields are untouched
+    assert_eq!(status & (MSTATUS_MPIE | MSTATUS_MPP), 0);
+}
+
+#[test]
+fn vectored_tvec_exception_goes_to_base() {
+    let mut emu = new_emu();
+    emu.m_csr.csrrw(CsrAddr::Mtvec, MTVEC as i64 | 1);
+    emu.m_priv = PrivMode::User;
+    run(&mut emu, &[ECALL]);
+    assert_eq!(mcause(&mut emu), 8);
+    assert_eq!(emu.m_pc[0], MTVEC);
+}
@@ -0,0 +1,195 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const DATA: u64 = 0x8000_2000;
+const OUT: u64 = 0x8000_2100;
+
+// vtype : e32, m1, tu, mu
+const E32M1: u32 = 0x10;
+
+const MSTATUS_VS_INITIAL: i64 = 1 << 9;
+const MSTATUS_VS_DIRTY: i64 = 3 << 9;
+
+fn new_vec_emu() -> EmuEnv {
+    let mut emu = new_emu();
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_VS_INITIAL);
+    emu
+}
+
+fn vsetvli(rd: u32, rs1: u32, vtypei: u32) -> u32 {
+    ((vtypei & 0x7ff) << 20) | (rs1 << 15) | (0b111 << 12) | (rd << 7) | 0x57
+}
+
+// Unmasked load (opcode 0x07) or store (0x27) with a 32-bit element width
+fn vmem32(mop: u32, rs2: u32, rs1: u32, vd: u32, opcode: u32) -> u32 {
+    (mop << 26) | (1 << 25) | (rs2 << 20) | (rs1 << 15) | (0b110 << 12) | (vd << 7) | opcode
+}
+
+// Unmasked OP-V arithmetic
+fn varith(funct6: u32, vs2: u32, vs1: u32, funct3: u32, vd: u32) -> u32 {
+    (funct6 << 26) | (1 << 25) | (vs2 << 20) | (vs1 << 15) | (funct3 << 12) | (vd << 7) | 0x57
+}
+
+fn vreg32(emu: &EmuEnv, reg: usize, idx: usize) -> u32 {
+    let offset = reg * 16 + idx * 4;
+    u32::from_le_bytes([emu.m_vregs[offset], emu.m_vregs[offset + 1], emu.m_vregs[offset + 2], emu.m_vregs[offset + 3]])
+}
+
+fn set_vreg32(emu: &mut EmuEnv, reg: usize, data: [u32; 4]) {
+    for (idx, d) in data.iter().enumerate() {
+        let offset = reg * 16 + idx * 4;
+        emu.m_vregs[offset..offset + 4].copy_from_slice(&d.to_le_bytes());
+    }
+}
+
+fn write_data(emu: &mut EmuEnv, data: &[u32]) {
+    for (idx, d) in data.iter().enumerate() {
+        emu.write_mem_4byte(DATA + idx as u64 * 4, *d);
+    }
+}
+
+#[test]
+fn vsetvli_sets_vl_and_vtype() {
+    let mut emu = new_vec_emu();
+    emu.m_iregs[5] = 3;
+    run(&mut emu, &[vsetvli(6, 5, E32M1)]);
+    assert_eq!(emu.m_iregs[6], 3);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vl, 0), 3);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vtype, 0), E32M1 as i64);
+    // AVL above VLMAX is clamped
+    emu.m_iregs[5] = 100;
+    run(&mut emu, &[vsetvli(6, 5, E32M1)]);
+    assert_eq!(emu.m_iregs[6], 4);
+    // rs1 = x0 with rd != x0 requests VLMAX : e8, m2
+    run(&mut emu, &[vsetvli(6, 0, 0x01)]);
+    assert_eq!(emu.m_iregs[6], 32);
+    // A reserved SEW sets vill and vl = 0
+    run(&mut emu, &[vsetvli(6, 5, 0x20)]);
+    assert_eq!(emu.m_iregs[6], 0);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Vtype, 0), i64::MIN);
+}
+
+#[test]
+fn unit_stride_load_store() {
+    let mut emu = new_vec_emu();
+    write_data(&mut emu, &[0x11, 0x22, 0x33, 0x44]);
+    emu.m_iregs[5] = 4;
+    emu.m_iregs[10] = DATA;
+    emu.m_iregs[11] = OUT;
+    run(&mut emu, &[vsetvli(0, 5, E32M1), vmem32(0, 0, 10, 1, 0x07), vmem32(0, 0, 11, 1, 0x27)]);
+    assert_eq!([vreg32(&emu, 1, 0), vreg32(&emu, 1, 1), vreg32(&emu, 1, 2), vreg32(&emu, 1, 3)], [0x11, 0x22, 0x33, 0x44]);
+    assert_eq!(emu.read_mem_8byte(OUT), 0x22_0000_0011);
+    assert_eq!(emu.read_mem_8byte(OUT + 8), 0x44_0000_0033);
+}
+
+#[test]
+fn strided_load_store() {
+    let mut emu = new_vec_emu();
+    write_data(&mut emu, &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]);
+    emu.m_iregs[5] = 4;
+    emu.m_iregs[10] = DATA;
+    emu.m_iregs[11] = OUT;
+    emu.m_iregs[12] = 8;
+    run(&mut emu, &[vsetvli(0, 5, E32M1), vmem32(2, 12, 10, 2, 0x07), vmem32(2, 12, 11, 2, 0x27)]);
+    assert_eq!([vreg32(&emu, 2, 0), vreg32(&emu, 2, 1), vreg32(&emu, 2, 2), vreg32(&emu, 2, 3)], [0x11, 0x33, 0x55, 0x77]);
+    assert_eq!(emu.read_mem_4byte(OUT + 8), 0x33);
+    assert_eq!(emu.read_mem_4byte(OUT + 24), 0x77);
+}
+
+#[test]
+fn indexed_load_store() {
+    let mut emu = new_vec_emu();
+    write_data(&mut emu, &[0x11, 0x22, 0x33, 0x44]);
+    set_vreg32(&mut emu, 3, [12, 0, 4, 8]);
+    emu.m_iregs[5] = 4;
+    emu.m_iregs[10] = DATA;
+    emu.m_iregs[11] = OUT;
+    // vluxei32.v v4, (x10), v3 ; vsuxei32.v v4, (x11), v3
+    run(&mut emu, &[vsetvli(0, 5, E32M1), vmem32(1, 3, 10, 4, 0x07), vmem32(1, 3, 11, 4, 0x27)]);
+    assert_eq!([vreg32(&emu, 4, 0), vreg32(&emu, 4, 1), vreg32(&emu, 4, 2), vreg32(&emu, 4, 3)], [0x44, 0x11, 0x22, 0x33]);
+    assert_eq!(emu.read_mem_8byte(OUT), 0x22_0000_0011);
+    assert_eq!(emu.read_mem_8byte(OUT + 8), 0x44_0000_0033);
+}
+
+#[test]
+fn one_op_per_group() {
+    let mut emu = new_vec_emu();
+    enable_fp(&mut emu);
+    emu.m_iregs[5] = 4;
+    set_vreg32(&mut emu, 1, [1, 2, 3, 0xffff_ffff]);
+    set_vreg32(&mut emu, 2, [10, 20, 30, 2]);
+    // OPIVV vadd.vv v3, v1, v2 ; OPMVV vmul.vv v4, v1, v2
+    run(&mut emu, &[vsetvli(0, 5, E32M1), varith(0x00, 1, 2, 0b000, 3), varith(0x25, 1, 2, 0b010, 4)]);
+    assert_eq!([vreg32(&emu, 3, 0), vreg32(&emu, 3, 3)], [11, 1]);
+    assert_eq!([vreg32(&emu, 4, 1), vreg32(&emu, 4, 3)], [40, 0xffff_fffe]);
+    // OPFVV vfadd.vv v5, v1, v2 : 1.5 + 2.25, 1.0 + -1.0
+    set_vreg32(&mut emu, 1, [0x3fc0_0000, 0x3f80_0000, 0, 0]);
+    set_vreg32(&mut emu, 2, [0x4010_0000, 0xbf80_0000, 0, 0]);
+    run(&mut emu, &[varith(0x00, 1, 2, 0b001, 5)]);
+    assert_eq!([vreg32(&emu, 5, 0), vreg32(&emu, 5, 1)], [0x4070_0000, 0]);
+}
+
+#[test]
+fn vfrsqrt7_vfrec7() {
+    let mut emu = new_vec_emu();
+    enable_fp(&mut emu);
+    emu.m_iregs[5] = 4;
+    // 4.0, 1.0, +0.0, -1.0
+    set_vreg32(&mut emu, 1, [0x4080_0000, 0x3f80_0000, 0, 0xbf80_0000]);
+    run(&mut emu, &[vsetvli(0, 5, E32M1), varith(0x13, 1, 0x04, 0b001, 2)]);
+    assert_eq!([vreg32(&emu, 2, 0), vreg32(&emu, 2, 1)], [0x3eff_0000, 0x3f7f_0000]);
+    assert_eq!([vreg32(&emu, 2, 2), vreg32(&emu, 2, 3)], [0x7f80_0000, 0x7fc0_0000]);
+    assert_eq!(fflags(&mut emu), 0x18);
+    // 2.0, -0.5, -0.0, +inf
+    emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+    set_vreg32(&mut emu, 1, [0x4000_0000, 0xbf00_0000, 0x8000_0000, 0x7f80_0000]);
+    run(&mut emu, &[varith(0x13, 1, 0x05, 0b001, 3)]);
+    assert_eq!([vreg32(&emu, 3, 0), vreg32(&emu, 3, 1)], [0x3eff_0000, 0xbfff_0000]);
+    assert_eq!([vreg32(&emu, 3, 2), vreg32(&emu, 3, 3)], [0xff80_0000, 0]);
+    assert_eq!(fflags(&mut emu), 0x08);
+}
+
+#[test]
+fn vs_off_traps() {
+    let mut emu = new_emu();
+    run(&mut emu, &[vsetvli(6, 5, E32M1)]);
+    assert_eq!(mcause(&mut emu), 2);
+    assert_eq!(emu.m_pc[0], MTVEC);
+    // csrr x6, vl
+    run(&mut emu, &[csrrs(6, 0xc20, 0)]);
+    assert_eq!(mcause(&mut emu), 2);
+    // With VS on but misa.V clear the vector unit is still off
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_VS_INITIAL);
+    emu.m_csr.m_misa.m_csr &= !(1 << 21);
+    emu.m_csr.csrrw(CsrAddr::Mcause, 0);
+    run(&mut emu, &[vsetvli(6, 5, E32M1)]);
+    assert_eq!(mcause(&mut emu), 2);
+}
+
+#[test]
+fn vector_fp_needs_fs() {
+    let mut emu = new_vec_emu();
+    emu.m_iregs[5] = 4;
+    run(&mut emu, &[vsetvli(0, 5, E32M1), varith(0x00, 1, 2, 0b001, 5)]);
+    assert_eq!(mcause(&mut emu), 2);
+}
+
+#[test]
+fn vs_becomes_dirty() {
+    let mut emu = new_vec_emu();
+    emu.m_iregs[5] = 4;
+    run(&mut emu, &[vsetvli(0, 5, E32M1)]);
+    let mstatus = emu.m_csr.csrrs(CsrAddr::Mstatus, 0);
+    assert_eq!(mstatus & MSTATUS_VS_DIRTY, MSTATUS_VS_DIRTY);
+    assert!(mstatus < 0);
+    // A vector CSR write also dirties the state
+    emu.m_csr.csrrc(CsrAddr::Mstatus, MSTATUS_VS_DIRTY);
+    emu.m_csr.csrrs(CsrAddr::Mstatus, MSTATUS_VS_INITIAL);
+    run(&mut emu, &[csrrci(0, 0x009, 1)]);
+    assert_eq!(emu.m_csr.csrrs(CsrAddr::Mstatus, 0) & MSTATUS_VS_DIRTY, MSTATUS_VS_DIRTY);
+}
@@ -0,0 +1,110 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::{EmuEnv, XlenEnum};
+use dydra::target::riscv::riscv_csr::CsrAddr;
+
+const FLI_S: [u32; 32] = [
+    0xbf80_0000, 0x0080_0000, 0x3780_0000, 0x3800_0000, 0x3b80_0000, 0x3c00_0000, 0x3d80_0000, 0x3e00_0000,
+    0x3e80_0000, 0x3ea0_0000, 0x3ec0_0000, 0x3ee0_0000, 0x3f00_0000, 0x3f20_0000, 0x3f40_0000, 0x3f60_0000,
+    0x3f80_0000, 0x3fa0_0000, 0x3fc0_0000, 0x3fe0_0000, 0x4000_0000, 0x4020_0000, 0x4040_0000, 0x4080_0000,
+    0x4100_0000, 0x4180_0000, 0x4300_0000, 0x4380_0000, 0x4700_0000, 0x4780_0000, 0x7f80_0000, 0x7fc0_0000,
+];
+
+// Upper 16 bits of each fli.d constant, the rest are 0
+const FLI_D: [u64; 32] = [
+    0xbff0, 0x0010, 0x3ef0, 0x3f00, 0x3f70, 0x3f80, 0x3fb0, 0x3fc0, 0x3fd0, 0x3fd4, 0x3fd8, 0x3fdc, 0x3fe0, 0x3fe4, 0x3fe8, 0x3fec,
+    0x3ff0, 0x3ff4, 0x3ff8, 0x3ffc, 0x4000, 0x4004, 0x4008, 0x4010, 0x4020, 0x4030, 0x4060, 0x4070, 0x40e0, 0x40f0, 0x7ff0, 0x7ff8,
+];
+
+// fd = f3, fs1 = f1. Returns (f3, fflags).
+fn exec_f(emu: &mut EmuEnv, inst: u32, a: u64) -> (u64, i64) {
+    emu.m_fregs[1] = a;
+    emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 4);
+    (emu.m_fregs[3], fflags(emu))
+}
+
+// rd = x7. Returns (x7, fflags).
+fn exec_x(emu: &mut EmuEnv, inst: u32, a: u64, b: u64) -> (u64, i64) {
+    emu.m_fregs[1] = a;
+    emu.m_fregs[2] = b;
+    emu.m_csr.csrrw(CsrAddr::FFlags, 0);
+    run(emu, &[inst]);
+    (emu.m_iregs[7], fflags(emu))
+}
+
+#[test]
+fn fli() {
+    let mut emu = new_fp_emu();
+    for idx in 0..32 {
+        run(&mut emu, &[fp_r(0x78, 1, idx, 0, 3), fp_r(0x79, 1, idx, 0, 4)]);
+        assert_eq!(emu.m_fregs[3], BOX_S | FLI_S[idx as usize] as u64, "fli.s {}", idx);
+        assert_eq!(emu.m_fregs[4], FLI_D[idx as usize] << 48, "fli.d {}", idx);
+    }
+}
+
+#[test]
+fn fround_froundnx() {
+    let mut emu = new_fp_emu();
+    // 2.5 in each static rounding mode
+    let expected = [0x4000_0000, 0x4000_0000, 0x4000_0000, 0x4040_0000, 0x4040_0000];
+    for (rm, expected) in expected.iter().enumerate() {
+        assert_eq!(exec_f(&mut emu, fp_r(0x20, 4, 1, rm as u32, 3), BOX_S | 0x4020_0000), (BOX_S | expected, 0), "rm = {}", rm);
+        assert_eq!(exec_f(&mut emu, fp_r(0x20, 5, 1, rm as u32, 3), BOX_S | 0x4020_0000), (BOX_S | expected, 0x01), "rm = {}", rm);
+    }
+    // -0.25 rounds to -0.0, an integral value is exact
+    assert_eq!(exec_f(&mut emu, fp_r(0x20, 5, 1, 0, 3), BOX_S | 0xbe80_0000), (BOX_S | 0x8000_0000, 0x01));
+    assert_eq!(exec_f(&mut emu, fp_r(0x20, 5, 1, 0, 3), BOX_S | 0x7149_f2ca), (BOX_S | 0x7149_f2ca, 0));
+    // A signaling NaN is invalid for both
+    assert_eq!(exec_f(&mut emu, fp_r(0x20, 4, 1, 0, 3), BOX_S | 0x7f80_0001), (BOX_S | 0x7fc0_0000, 0x10));
+    assert_eq!(exec_f(&mut emu, fp_r(0x21, 4, 1, 1, 3), 0xc004_0000_0000_0000), (0xc000_0000_0000_0000, 0));
+    assert_eq!(exec_f(&mut emu, fp_r(0x21, 5, 1, 2, 3), 0xc004_0000_0000_0000), (0xc008_0000_0000_0000, 0x01));
+}
+
+#[test]
+fn fcvtmod_w_d() {
+    let mut emu = new_fp_emu();
+    let fcvtmod = fp_r(0x61, 8, 1, 0b001, 7);
+    // 3.75, -3.75 : truncated and inexact
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0x400e_0000_0000_0000, 0), (3, 0x01));
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0xc00e_0000_0000_0000, 0), (-3i64 as u64, 0x01));
+    // 2^32 + 5 and 2^31 wrap modulo 2^32, and are invalid
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0x41f0_0000_0050_0000, 0), (5, 0x10));
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0x41e0_0000_0000_0000, 0), (0xffff_ffff_8000_0000, 0x10));
+    // inf and NaN give 0
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0x7ff0_0000_0000_0000, 0), (0, 0x10));
+    assert_eq!(exec_x(&mut emu, fcvtmod, 0x7ff8_0000_0000_0000, 0), (0, 0x10));
+}
+
+#[test]
+fn quiet_compares_and_minmax() {
+    let mut emu = new_fp_emu();
+    let one = BOX_S | 0x3f80_0000;
+    // fltq.s / fleq.s x7, f1, f2 : quiet NaNs do not raise NV, signaling ones do
+    assert_eq!(exec_x(&mut emu, fp_r(0x50, 2, 1, 0b101, 7), one, BOX_S | 0x7fc0_0000), (0, 0));
+    assert_eq!(exec_x(&mut emu, fp_r(0x50, 2, 1, 0b100, 7), one, one), (1, 0));
+    assert_eq!(exec_x(&mut emu, fp_r(0x50, 2, 1, 0b100, 7), one, BOX_S | 0x7f80_0001), (0, 0x10));
+    // fminm.s / fmaxm.s f3, f1, f2 : any NaN gives the canonical NaN
+    emu.m_fregs[2] = BOX_S | 0x7fc0_0000;
+    assert_eq!(exec_f(&mut emu, fp_r(0x14, 2, 1, 0b010, 3), one), (BOX_S | 0x7fc0_0000, 0));
+    assert_eq!(exec_f(&mut emu, fp_r(0x14, 2, 1, 0b011, 3), one), (BOX_S | 0x7fc0_0000, 0));
+    emu.m_fregs[2] = BOX_S | 0x8000_0000;
+    assert_eq!(exec_f(&mut emu, fp_r(0x14, 2, 1, 0b010, 3), BOX_S), (BOX_S | 0x8000_0000, 0));
+}
+
+#[test]
+fn fmvh_fmvp_rv32() {
+    let mut emu = new_fp_emu();
+    emu.m_xlen = XlenEnum::Rv32;
+    // fmvh.x.d x7, f1
+    assert_eq!(exec_x(&mut emu, fp_r(0x71, 1, 1, 0, 7), 0xc008_0000_0000_0001, 0).0, 0xffff_ffff_c008_0000);
+    // fmvp.d.x f3, x5, x6
+    emu.m_iregs[5] = 0xffff_ffff_8000_0001;
+    emu.m_iregs[6] = 0x4008_0000;
+    run(&mut emu, &[fp_r(0x59, 6, 5, 0, 3)]);
+    assert_eq!(emu.m_fregs[3], 0x4008_0000_8000_0001);
+}
@@ -0,0 +1,90 @@
+extern crate dydra;
+
+mod common;
+
+use common::*;
+use dydra::emu_env::EmuEnv;
+
+const DATA: u64 = 0x8000_2000;
+
+// A compressed instruction padded with c.nop, one block runs just the first
+fn c(inst: u32) -> u32 {
+    inst | (0x0001 << 16)
+}
+
+// Zcb loads / stores : rd' (or rs2') = x9, rs1' = x8
+fn c_mem(funct6: u32, bit6: u32, bit5: u32) -> u32 {
+    c((funct6 << 10) | (bit6 << 6) | (bit5 << 5) | (1 << 2))
+}
+
+// Zcb unary ops on rd' = x9
+fn c_unary(op: u32) -> u32 {
+    c((0b100111 << 10) | (1 << 7) | (0b11 << 5) | (op << 2) | 0b01)
+}
+
+fn exec_c(emu: &mut EmuEnv, inst: u32, x9: u64) -> u64 {
+    emu.m_iregs[8] = DATA;
+    emu.m_iregs[9] = x9;
+    run(emu, &[inst]);
+    assert_eq!(emu.m_pc[0], CODE_BASE + 2);
+    emu.m_iregs[9]
+}
+
+#[test]
+fn czero() {
+    let mut emu = new_emu();
+    let eqz = r_type(0b0000111, 6, 5, 0b101, 7, 0x33);
+    let nez = r_type(0b0000111, 6, 5, 0b111, 7, 0x33);
+    for &(inst, rs2, expected) in [(eqz, 0, 0), (eqz, 5, 0x1234), (nez, 0, 0x1234), (nez, 1 << 63, 0)].iter() {
+        emu.m_iregs[5] = 0x1234;
+        emu.m_iregs[6] = rs2;
+        emu.m_iregs[7] = 0xdead;
+        run(&mut emu, &[inst]);
+        assert_eq!(emu.m_iregs[7], expected);
+    }
+}
+
+#[test]
+fn zcb_loads() {
+    let mut emu = new_emu();
+    emu.write_mem_4byte(DATA, 0x8281_ff80);
+    // c.lbu x9, 1(x8) ; c.lbu x9, 3(x8)
+    assert_eq!(exec_c(&mut emu, c_mem(0b100000, 1, 0), 0), 0xff);
+    assert_eq!(exec_c(&mut emu, c_mem(0b100000, 1, 1), 0), 0x82);
+    // c.lhu x9, 2(x8) ; c.lh x9, 2(x8)
+    assert_eq!(exec_c(&mut emu, c_mem(0b100001, 0, 1), 0), 0x8281);
+    assert_eq!(exec_c(&mut emu, c_mem(0b100001, 1, 1), 0), 0xffff_ffff_ffff_8281);
+}
+
+#[test]
+fn zcb_stores() {
+    let mut emu = new_emu();
+    emu.write_mem_4byte(DATA, 0);
+    // c.sb x9, 2(x8) ; c.sh x9, 0(x8)
+    exec_c(&mut emu, c_mem(0b100010, 0, 1), 0x1_23ab);
+    assert_eq!(emu.read_mem_4byte(DATA), 0x00ab_0000);
+    exec_c(&mut emu, c_mem(0b100011, 0, 0), 0x1_cdef);
+    assert_eq!(emu.read_mem_4byte(DATA), 0x00ab_cdef);
+}
+
+#[test]
+fn zcb_unary() {
+    let mut emu = new_emu();
+    let value = 0x1234_5678_9abc_def0;
+    assert_eq!(exec_c(&mut emu, c_unary(0b000), value), 0xf0);
+    assert_eq!(exec_c(&mut emu, c_unary(0b001), value), 0xffff_ffff_ffff_fff0);
+    assert_eq!(exec_c(&mut emu, c_unary(0b010), value), 0xdef0);
+    assert_eq!(exec_c(&mut emu, c_unary(0b011), value), 0xffff_ffff_ffff_def0);
+    assert_eq!(exec_c(&mut emu, c_unary(0b100), value), 0x9abc_def0);
+    assert_eq!(exec_c(&mut emu, c_unary(0b101), value), !value);
+}
+
+#[test]
+fn zcb_mul() {
+    let mut emu = new_emu();
+    // c.mul x9, x8
+    emu.m_iregs[8] = 3;
+    emu.m_iregs[9] = u64::MAX - 1;
+    run(&mut emu, &[c((0b100111 << 10) | (1 << 7) | (0b10 << 5) | 0b01)]);
+    assert_eq!(emu.m_iregs[9], (u64::MAX - 1).wrapping_mul(3));
+}